@@ -0,0 +1,759 @@
+//! Session export to Markdown, standalone HTML and Claude Code JSONL, and
+//! search over directories of exported sessions.
+//!
+//! All exports load messages through `load_normalized_messages`, so every
+//! provider renders the same way.
+
+use crate::commands::multi_provider::load_normalized_messages;
+use crate::models::{ClaudeMessage, RawLogEntry};
+use crate::utils::SearchPattern;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Render a session as Markdown for pasting into an issue or PR: one heading
+/// per message with its timestamp, thinking in collapsible `<details>`, and
+/// tool calls and results as fenced code blocks.
+#[tauri::command]
+pub async fn export_session_markdown(
+    provider: String,
+    session_path: String,
+) -> Result<String, String> {
+    let messages = load_normalized_messages(provider, session_path, None, None).await?;
+    Ok(render_session_markdown(&messages))
+}
+
+/// Render a session as a standalone HTML page (inline CSS, no external
+/// assets) for offline archiving. The header lists the project, provider,
+/// models and total token usage. Without a `project_name` the header uses the
+/// messages' project, then the Claude project directory the session lives in,
+/// and finally the session id.
+#[tauri::command]
+pub async fn export_session_html(
+    provider: String,
+    session_path: String,
+    project_name: Option<String>,
+) -> Result<String, String> {
+    let project_dir_name = (provider == "claude")
+        .then(|| Path::new(&session_path).parent()?.file_name()?.to_str())
+        .flatten()
+        .map(crate::utils::extract_project_name);
+    let messages = load_normalized_messages(provider.clone(), session_path, None, None).await?;
+    let project_name = project_name
+        .filter(|name| !name.trim().is_empty())
+        .or_else(|| messages.iter().find_map(|m| m.project_name.clone()))
+        .or(project_dir_name)
+        .or_else(|| messages.first().map(|m| m.session_id.clone()))
+        .unwrap_or_default();
+    Ok(render_session_html(&messages, &project_name, &provider))
+}
+
+/// Serialize a session as Claude Code JSONL, one line per message, so chats
+/// from other providers can be imported into a Claude Code history. Each line
+/// keeps the message's `uuid`, `parentUuid` and `sessionId`, plus a
+/// `provider` field naming where it came from (ignored by Claude's loader).
+#[tauri::command]
+pub async fn export_session_jsonl(
+    provider: String,
+    session_path: String,
+) -> Result<String, String> {
+    let messages = load_normalized_messages(provider, session_path, None, None).await?;
+    let mut out = String::new();
+    for msg in &messages {
+        let line = serde_json::to_string(&claude_log_entry(msg)?)
+            .map_err(|e| format!("Failed to serialize message {}: {e}", msg.uuid))?;
+        out.push_str(&line);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Search a directory of exported conversations (e.g. sessions pruned from
+/// the live tools) for a query string, matching message text the same way the
+/// provider searches do. `.json` and `.jsonl` files are read recursively; see
+/// `read_archive_file` for the accepted formats. Each hit's `projectName` is
+/// the file it came from.
+#[tauri::command]
+pub async fn search_archive(
+    dir: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<ClaudeMessage>, String> {
+    let root = Path::new(&dir).to_path_buf();
+    if !root.is_dir() {
+        return Err(format!("Archive directory not found: {dir}"));
+    }
+    let max_results = limit.unwrap_or(100);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let pattern = SearchPattern::plain(&query);
+        let mut files: Vec<_> = WalkDir::new(&root)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_file())
+            .map(walkdir::DirEntry::into_path)
+            .filter(|p| {
+                p.extension()
+                    .is_some_and(|ext| ext == "json" || ext == "jsonl")
+            })
+            .collect();
+        files.sort();
+
+        let mut results = Vec::new();
+        for path in files {
+            let file_name = path
+                .strip_prefix(&root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .to_string();
+            for mut msg in read_archive_file(&path) {
+                if results.len() >= max_results {
+                    return results;
+                }
+                if msg
+                    .content
+                    .as_ref()
+                    .is_some_and(|c| pattern.matches_content(c))
+                {
+                    msg.project_name = Some(file_name.clone());
+                    results.push(msg);
+                }
+            }
+        }
+        results
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))
+}
+
+/// Heading for a message, or `None` for types that aren't exported
+fn role_heading(msg: &ClaudeMessage) -> Option<&'static str> {
+    match msg.message_type.as_str() {
+        "user" => Some("User"),
+        "assistant" => Some("Assistant"),
+        "system" => Some("System"),
+        _ => None,
+    }
+}
+
+/// A code fence longer than any backtick run in `text`, so the text can't
+/// close it early
+fn code_fence(text: &str) -> String {
+    let longest_run = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest_run.max(2) + 1)
+}
+
+fn push_code_block(out: &mut String, language: &str, text: &str) {
+    let fence = code_fence(text);
+    out.push_str(&format!("{fence}{language}\n{text}\n{fence}\n\n"));
+}
+
+/// Text of a `tool_result` block's content: a string, a list of text blocks,
+/// or anything else as pretty JSON
+fn tool_result_text(content: &Value) -> String {
+    match content {
+        Value::String(s) => s.clone(),
+        Value::Array(items) if items.iter().all(|i| i.get("text").is_some()) => items
+            .iter()
+            .filter_map(|i| i.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Value::Null => String::new(),
+        other => serde_json::to_string_pretty(other).unwrap_or_default(),
+    }
+}
+
+fn render_content_block(out: &mut String, block: &Value) {
+    match block.get("type").and_then(Value::as_str) {
+        Some("text") => {
+            let text = block.get("text").and_then(Value::as_str).unwrap_or("");
+            if !text.trim().is_empty() {
+                out.push_str(text.trim_end());
+                out.push_str("\n\n");
+            }
+        }
+        Some("thinking") => {
+            let text = block.get("thinking").and_then(Value::as_str).unwrap_or("");
+            let text = if text.trim().is_empty() {
+                "_(redacted)_"
+            } else {
+                text.trim_end()
+            };
+            out.push_str(&format!(
+                "<details>\n<summary>Thinking</summary>\n\n{text}\n\n</details>\n\n"
+            ));
+        }
+        Some("tool_use") => {
+            let name = block
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown");
+            let input = block
+                .get("input")
+                .map(|input| serde_json::to_string_pretty(input).unwrap_or_default())
+                .unwrap_or_default();
+            out.push_str(&format!("**Tool: `{name}`**\n\n"));
+            push_code_block(out, "json", &input);
+        }
+        Some("tool_result") => {
+            let is_error = block.get("is_error").and_then(Value::as_bool) == Some(true);
+            let label = if is_error { "Error" } else { "Result" };
+            let text = tool_result_text(block.get("content").unwrap_or(&Value::Null));
+            out.push_str(&format!("**{label}:**\n\n"));
+            push_code_block(out, "", &text);
+        }
+        _ => {}
+    }
+}
+
+/// Markdown for `export_session_markdown`. Messages stay in load order;
+/// those with nothing renderable (e.g. summaries) are skipped.
+fn render_session_markdown(messages: &[ClaudeMessage]) -> String {
+    let mut out = String::new();
+
+    for msg in messages {
+        let Some(heading) = role_heading(msg) else {
+            continue;
+        };
+        let mut body = String::new();
+        match msg.content.as_ref() {
+            Some(Value::String(text)) if !text.trim().is_empty() => {
+                body.push_str(text.trim_end());
+                body.push_str("\n\n");
+            }
+            Some(Value::Array(blocks)) => {
+                for block in blocks {
+                    render_content_block(&mut body, block);
+                }
+            }
+            _ => {}
+        }
+        if body.is_empty() {
+            continue;
+        }
+
+        out.push_str(&format!("## {heading}"));
+        if !msg.timestamp.is_empty() {
+            out.push_str(&format!(" · {}", msg.timestamp));
+        }
+        out.push_str("\n\n");
+        out.push_str(&body);
+    }
+
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+// ============================================================================
+// Claude Code JSONL
+// ============================================================================
+
+/// Rebuild the on-disk Claude Code entry (`RawLogEntry` shape) for a message.
+///
+/// `ClaudeMessage` already serializes most fields under Claude's names; what
+/// differs is that user and assistant turns nest `role`, `content`, `model`,
+/// `stop_reason`, `usage` and the message id under `message`, and that the
+/// system stop reason is `stopReason`. Null fields are dropped, except
+/// `parentUuid`, which Claude writes as `null` for a conversation's root.
+fn claude_log_entry(msg: &ClaudeMessage) -> Result<Value, String> {
+    let Value::Object(mut entry) = serde_json::to_value(msg)
+        .map_err(|e| format!("Failed to serialize message {}: {e}", msg.uuid))?
+    else {
+        return Err(format!("Message {} is not a JSON object", msg.uuid));
+    };
+
+    for key in [
+        "role",
+        "model",
+        "stop_reason",
+        "usage",
+        "projectName",
+        "matchLocations",
+    ] {
+        entry.remove(key);
+    }
+    let content = entry.remove("content").filter(|c| !c.is_null());
+    if let Some(stop_reason) = entry.remove("stopReasonSystem") {
+        entry.insert("stopReason".to_string(), stop_reason);
+    }
+    entry.retain(|key, value| key == "parentUuid" || !value.is_null());
+
+    if matches!(msg.message_type.as_str(), "user" | "assistant") {
+        let mut message = serde_json::Map::new();
+        let role = msg.role.clone().unwrap_or_else(|| msg.message_type.clone());
+        message.insert("role".to_string(), Value::String(role));
+        message.insert(
+            "content".to_string(),
+            content.unwrap_or_else(|| Value::Array(Vec::new())),
+        );
+        if let Some(id) = entry.remove("messageId") {
+            message.insert("id".to_string(), id);
+        }
+        if let Some(model) = &msg.model {
+            message.insert("model".to_string(), Value::String(model.clone()));
+        }
+        if let Some(stop_reason) = &msg.stop_reason {
+            message.insert(
+                "stop_reason".to_string(),
+                Value::String(stop_reason.clone()),
+            );
+        }
+        if let Some(usage) = &msg.usage {
+            message.insert(
+                "usage".to_string(),
+                serde_json::to_value(usage).map_err(|e| e.to_string())?,
+            );
+        }
+        entry.insert("message".to_string(), Value::Object(message));
+    } else if let Some(content) = content {
+        entry.insert("content".to_string(), content);
+    }
+
+    Ok(Value::Object(entry))
+}
+
+// ============================================================================
+// Archive search
+// ============================================================================
+
+/// Messages of one exported conversation, detected from the contents rather
+/// than the extension: a JSON array of messages (or an object holding one
+/// under `messages`) as loaded by the viewer, or Claude Code JSONL such as
+/// `export_session_jsonl` writes. Unreadable files and entries are skipped.
+fn read_archive_file(path: &Path) -> Vec<ClaudeMessage> {
+    let Ok(text) = fs::read_to_string(path) else {
+        log::warn!("Skipping unreadable archive file: {}", path.display());
+        return Vec::new();
+    };
+
+    if text.trim_start().starts_with('[') {
+        let items = serde_json::from_str::<Vec<Value>>(&text).unwrap_or_default();
+        return items
+            .into_iter()
+            .filter_map(|item| serde_json::from_value(item).ok())
+            .collect();
+    }
+    if let Ok(Value::Object(mut doc)) = serde_json::from_str::<Value>(&text) {
+        if let Some(Value::Array(items)) = doc.remove("messages") {
+            return items
+                .into_iter()
+                .filter_map(|item| serde_json::from_value(item).ok())
+                .collect();
+        }
+    }
+
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter_map(|entry| {
+            let provider = entry
+                .get("provider")
+                .and_then(Value::as_str)
+                .map(String::from);
+            let raw: RawLogEntry = serde_json::from_value(entry).ok()?;
+            if raw.is_meta.unwrap_or(false) {
+                return None;
+            }
+            let mut msg = ClaudeMessage::try_from(raw).ok()?;
+            msg.provider = provider.or_else(|| Some("claude".to_string()));
+            Some(msg)
+        })
+        .collect()
+}
+
+// ============================================================================
+// HTML
+// ============================================================================
+
+const HTML_STYLE: &str = "\
+body { font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', sans-serif; \
+max-width: 960px; margin: 0 auto; padding: 24px; color: #1f2328; background: #fff; }
+header { border-bottom: 1px solid #d0d7de; margin-bottom: 24px; }
+header dl { display: grid; grid-template-columns: max-content 1fr; gap: 4px 16px; }
+header dt { font-weight: 600; }
+header dd { margin: 0; }
+.message { border: 1px solid #d0d7de; border-radius: 6px; padding: 12px 16px; margin-bottom: 16px; }
+.message.user { background: #f6f8fa; }
+.message.system { background: #fff8c5; }
+.meta { display: flex; justify-content: space-between; font-weight: 600; margin-bottom: 8px; }
+.meta time { font-weight: normal; color: #656d76; }
+.text { white-space: pre-wrap; overflow-wrap: anywhere; margin: 8px 0; }
+.thinking { color: #656d76; font-style: italic; margin: 8px 0; }
+.thinking summary { cursor: pointer; font-style: normal; }
+.tool-use, .tool-result { margin: 8px 0; }
+.tool-use .label { color: #0969da; font-weight: 600; }
+.tool-result .label { color: #1a7f37; font-weight: 600; }
+.tool-result.error .label { color: #cf222e; }
+pre { background: #f6f8fa; border: 1px solid #d0d7de; border-radius: 6px; padding: 8px; \
+overflow-x: auto; white-space: pre-wrap; overflow-wrap: anywhere; }
+";
+
+/// Escape text for use in HTML element content and quoted attributes
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn render_html_block(out: &mut String, block: &Value) {
+    match block.get("type").and_then(Value::as_str) {
+        Some("text") => {
+            let text = block.get("text").and_then(Value::as_str).unwrap_or("");
+            if !text.trim().is_empty() {
+                out.push_str(&format!(
+                    "<div class=\"text\">{}</div>\n",
+                    escape_html(text.trim_end())
+                ));
+            }
+        }
+        Some("thinking") => {
+            let text = block.get("thinking").and_then(Value::as_str).unwrap_or("");
+            let text = if text.trim().is_empty() {
+                "(redacted)"
+            } else {
+                text.trim_end()
+            };
+            out.push_str(&format!(
+                "<details class=\"thinking\"><summary>Thinking</summary>\
+                 <div class=\"text\">{}</div></details>\n",
+                escape_html(text)
+            ));
+        }
+        Some("tool_use") => {
+            let name = block
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown");
+            let input = block
+                .get("input")
+                .map(|input| serde_json::to_string_pretty(input).unwrap_or_default())
+                .unwrap_or_default();
+            out.push_str(&format!(
+                "<div class=\"tool-use\"><div class=\"label\">Tool: {}</div>\
+                 <pre>{}</pre></div>\n",
+                escape_html(name),
+                escape_html(&input)
+            ));
+        }
+        Some("tool_result") => {
+            let is_error = block.get("is_error").and_then(Value::as_bool) == Some(true);
+            let (class, label) = if is_error {
+                ("tool-result error", "Error")
+            } else {
+                ("tool-result", "Result")
+            };
+            let text = tool_result_text(block.get("content").unwrap_or(&Value::Null));
+            out.push_str(&format!(
+                "<div class=\"{class}\"><div class=\"label\">{label}</div>\
+                 <pre>{}</pre></div>\n",
+                escape_html(&text)
+            ));
+        }
+        _ => {}
+    }
+}
+
+/// Total tokens across messages, as `(input, output, cache write, cache read)`
+fn total_token_usage(messages: &[ClaudeMessage]) -> (u64, u64, u64, u64) {
+    let mut totals = (0, 0, 0, 0);
+    for usage in messages.iter().filter_map(|m| m.usage.as_ref()) {
+        totals.0 += u64::from(usage.input_tokens.unwrap_or(0));
+        totals.1 += u64::from(usage.output_tokens.unwrap_or(0));
+        totals.2 += u64::from(usage.cache_creation_input_tokens.unwrap_or(0));
+        totals.3 += u64::from(usage.cache_read_input_tokens.unwrap_or(0));
+    }
+    totals
+}
+
+/// HTML for `export_session_html`. Messages stay in load order; those with
+/// nothing renderable are skipped. All message text is escaped.
+fn render_session_html(messages: &[ClaudeMessage], project_name: &str, provider: &str) -> String {
+    let mut models: Vec<&str> = Vec::new();
+    for model in messages.iter().filter_map(|m| m.model.as_deref()) {
+        if !models.contains(&model) {
+            models.push(model);
+        }
+    }
+    let (input, output, cache_write, cache_read) = total_token_usage(messages);
+    let total = input + output + cache_write + cache_read;
+    let project_name = escape_html(project_name);
+
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str(&format!("<title>{project_name}</title>\n"));
+    out.push_str(&format!("<style>\n{HTML_STYLE}</style>\n</head>\n<body>\n"));
+    out.push_str(&format!("<header>\n<h1>{project_name}</h1>\n<dl>\n"));
+    out.push_str(&format!(
+        "<dt>Provider</dt><dd>{}</dd>\n",
+        escape_html(provider)
+    ));
+    if !models.is_empty() {
+        out.push_str(&format!(
+            "<dt>Model</dt><dd>{}</dd>\n",
+            escape_html(&models.join(", "))
+        ));
+    }
+    out.push_str(&format!(
+        "<dt>Tokens</dt><dd>{total} total ({input} input, {output} output, \
+         {cache_write} cache write, {cache_read} cache read)</dd>\n"
+    ));
+    out.push_str("</dl>\n</header>\n<main>\n");
+
+    for msg in messages {
+        let Some(heading) = role_heading(msg) else {
+            continue;
+        };
+        let mut body = String::new();
+        match msg.content.as_ref() {
+            Some(Value::String(text)) if !text.trim().is_empty() => {
+                body.push_str(&format!(
+                    "<div class=\"text\">{}</div>\n",
+                    escape_html(text.trim_end())
+                ));
+            }
+            Some(Value::Array(blocks)) => {
+                for block in blocks {
+                    render_html_block(&mut body, block);
+                }
+            }
+            _ => {}
+        }
+        if body.is_empty() {
+            continue;
+        }
+
+        out.push_str(&format!(
+            "<section class=\"message {}\">\n<div class=\"meta\"><span>{heading}</span>",
+            msg.message_type
+        ));
+        if !msg.timestamp.is_empty() {
+            let timestamp = escape_html(&msg.timestamp);
+            out.push_str(&format!(
+                "<time datetime=\"{timestamp}\">{timestamp}</time>"
+            ));
+        }
+        out.push_str("</div>\n");
+        out.push_str(&body);
+        out.push_str("</section>\n");
+    }
+
+    out.push_str("</main>\n</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{EnvVarGuard, MessageBuilder};
+    use serde_json::json;
+    use serial_test::serial;
+
+    #[tokio::test]
+    async fn search_archive_reads_jsonl_and_json_exports() {
+        use crate::test_utils::create_jsonl_content;
+
+        let tmp = tempfile::TempDir::new().expect("temp dir should be created");
+        let archive = tmp.path();
+        fs::create_dir_all(archive.join("2024")).expect("subdir should be created");
+
+        let mut jsonl = create_jsonl_content(&[
+            MessageBuilder::user().with_text_content("Where is the flaky test?"),
+            MessageBuilder::assistant()
+                .with_content(json!([{ "type": "text", "text": "The Flaky test is in ci.rs" }])),
+            MessageBuilder::user().with_text_content("unrelated"),
+        ]);
+        jsonl.push('\n');
+        let with_provider = json!({
+            "type": "user", "uuid": "oc-1", "parentUuid": null, "sessionId": "ses_1",
+            "timestamp": "2024-01-01T00:00:00Z", "provider": "opencode",
+            "message": { "role": "user", "content": "fix the flaky build" }
+        });
+        jsonl.push_str(&with_provider.to_string());
+        fs::write(archive.join("2024").join("claude.jsonl"), jsonl)
+            .expect("jsonl should be written");
+
+        let viewer_export = vec![
+            MessageBuilder::user()
+                .with_uuid("json-1")
+                .with_text_content("flaky again")
+                .build(),
+            MessageBuilder::assistant()
+                .with_text_content("no match")
+                .build(),
+        ];
+        fs::write(
+            archive.join("viewer.json"),
+            serde_json::to_string(&viewer_export).expect("messages should serialize"),
+        )
+        .expect("json should be written");
+        // Detection goes by contents: this ".json" file holds JSONL
+        fs::write(
+            archive.join("misnamed.json"),
+            create_jsonl_content(&[MessageBuilder::user().with_text_content("flaky misnamed")]),
+        )
+        .expect("misnamed file should be written");
+        fs::write(archive.join("notes.md"), "flaky notes").expect("notes should be written");
+        fs::write(archive.join("broken.json"), "{ not json")
+            .expect("broken file should be written");
+
+        let hits = search_archive(
+            archive.to_string_lossy().to_string(),
+            "FLAKY".to_string(),
+            None,
+        )
+        .await
+        .expect("search should succeed");
+
+        let sources: Vec<_> = hits
+            .iter()
+            .map(|m| {
+                (
+                    m.project_name.clone().unwrap_or_default(),
+                    m.provider.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(hits.len(), 5, "{sources:?}");
+        let claude_file = Path::new("2024")
+            .join("claude.jsonl")
+            .to_string_lossy()
+            .to_string();
+        assert_eq!(hits[0].project_name.as_deref(), Some(claude_file.as_str()));
+        assert_eq!(hits[2].uuid, "oc-1");
+        assert_eq!(hits[2].provider.as_deref(), Some("opencode"));
+        assert_eq!(hits[1].provider.as_deref(), Some("claude"));
+        assert_eq!(hits[3].project_name.as_deref(), Some("misnamed.json"));
+        assert_eq!(hits[4].uuid, "json-1");
+
+        let limited = search_archive(
+            archive.to_string_lossy().to_string(),
+            "flaky".to_string(),
+            Some(2),
+        )
+        .await
+        .expect("search should succeed");
+        assert_eq!(limited.len(), 2);
+        assert!(search_archive(
+            archive.join("missing").to_string_lossy().to_string(),
+            "flaky".to_string(),
+            None
+        )
+        .await
+        .is_err());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn export_session_markdown_renders_tool_calls_and_results() {
+        let tmp = tempfile::TempDir::new().expect("temp dir should be created");
+        let gemini_home = tmp.path().join("gemini-home");
+        let chats_dir = gemini_home.join("tmp").join("abc123").join("chats");
+        fs::create_dir_all(&chats_dir).expect("chats dir should be created");
+        let _guard = EnvVarGuard::set("GEMINI_HOME", &gemini_home);
+
+        let session = json!({
+            "sessionId": "sess-1",
+            "messages": [
+                { "id": "m1", "timestamp": "2025-07-01T10:00:00Z", "type": "user", "content": "List the files" },
+                {
+                    "id": "m2",
+                    "timestamp": "2025-07-01T10:00:05Z",
+                    "type": "gemini",
+                    "content": "Here they are.",
+                    "thoughts": [{ "subject": "Listing", "description": "Use list_directory" }],
+                    "toolCalls": [{
+                        "id": "call-1",
+                        "name": "list_directory",
+                        "args": { "path": "." },
+                        "status": "error",
+                        "resultDisplay": "permission denied"
+                    }]
+                }
+            ]
+        });
+        let session_path = chats_dir.join("session-2025-07-01T10-00-sess1.json");
+        fs::write(&session_path, session.to_string()).expect("session should be written");
+
+        let markdown = export_session_markdown(
+            "gemini".to_string(),
+            session_path.to_string_lossy().to_string(),
+        )
+        .await
+        .expect("export should succeed");
+
+        assert!(markdown.starts_with("## User · 2025-07-01T10:00:00Z\n\nList the files\n"));
+        assert!(markdown.contains("## Assistant · 2025-07-01T10:00:05Z\n\n<details>"));
+        assert!(markdown.contains("**Listing**\nUse list_directory"));
+        assert!(markdown.contains("**Tool: `LS`**"));
+        assert!(markdown.ends_with("**Error:**\n\n```\npermission denied\n```\n"));
+    }
+
+    #[tokio::test]
+    async fn export_session_html_names_claude_project_from_its_directory() {
+        use crate::test_utils::{create_jsonl_content, MockClaudeProject};
+
+        let mock = MockClaudeProject::new();
+        let session_path = mock.add_session(
+            "-Users-me-my-app",
+            "session1",
+            &create_jsonl_content(&[MessageBuilder::user().with_text_content("hello")]),
+        );
+
+        let html = export_session_html(
+            "claude".to_string(),
+            session_path.to_string_lossy().to_string(),
+            None,
+        )
+        .await
+        .expect("export should succeed");
+
+        assert!(html.contains("<h1>my-app</h1>"), "{html}");
+    }
+
+    #[test]
+    fn render_session_html_escapes_content_and_summarizes_usage() {
+        let messages = vec![
+            MessageBuilder::user()
+                .with_text_content("Why does <script>alert(1)</script> run?")
+                .build(),
+            MessageBuilder::assistant()
+                .with_content(json!([
+                    { "type": "thinking", "thinking": "Look for & in the template" },
+                    { "type": "tool_use", "id": "call_1", "name": "Read", "input": { "file_path": "a<b>.html" } },
+                    { "type": "tool_result", "tool_use_id": "call_1", "content": "<img onerror=x>", "is_error": true },
+                    { "type": "text", "text": "It isn't escaped." }
+                ]))
+                .with_usage(120, 30)
+                .build(),
+            MessageBuilder::new().with_type("summary").with_text_content("skipped").build(),
+        ];
+
+        let html = render_session_html(&messages, "my <app>", "claude");
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(!html.contains("<script>") && !html.contains("<img"));
+        assert!(html.contains("<h1>my &lt;app&gt;</h1>"));
+        assert!(html.contains("<dt>Model</dt><dd>claude-opus-4-20250514</dd>"));
+        assert!(html.contains("150 total (120 input, 30 output, 0 cache write, 0 cache read)"));
+        assert!(html.contains("Why does &lt;script&gt;alert(1)&lt;/script&gt; run?"));
+        assert!(html.contains("<details class=\"thinking\"><summary>Thinking</summary>"));
+        assert!(html.contains("Look for &amp; in the template"));
+        assert!(html.contains("&quot;file_path&quot;: &quot;a&lt;b&gt;.html&quot;"));
+        assert!(html.contains(
+            "<div class=\"tool-result error\"><div class=\"label\">Error</div><pre>&lt;img onerror=x&gt;</pre>"
+        ));
+        assert!(!html.contains("skipped"));
+        assert_eq!(html.matches("<section class=\"message").count(), 2);
+    }
+}
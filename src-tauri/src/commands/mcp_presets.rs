@@ -3,10 +3,10 @@
 //! This module provides commands for saving, loading, and managing
 //! MCP server presets stored in ~/.claude-history-viewer/mcp-presets/
 
+use crate::utils::write_json_atomic;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::io::Write;
 use std::path::PathBuf;
 use uuid::Uuid;
 
@@ -152,26 +152,7 @@ pub async fn save_mcp_preset(input: MCPPresetInput) -> Result<MCPPresetData, Str
         ensure_mcp_presets_folder()?;
         let path = get_mcp_preset_path(&preset_clone.id)?;
 
-        // Write to temp file with unique name to avoid collisions
-        let nonce: u64 = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_nanos() as u64;
-        let temp_path = path.with_extension(format!("json.{nonce}.tmp"));
-        let content = serde_json::to_string_pretty(&preset_clone)
-            .map_err(|e| format!("Failed to serialize MCP preset: {e}"))?;
-
-        let mut file =
-            fs::File::create(&temp_path).map_err(|e| format!("Failed to create temp file: {e}"))?;
-        file.write_all(content.as_bytes())
-            .map_err(|e| format!("Failed to write temp file: {e}"))?;
-        file.sync_all()
-            .map_err(|e| format!("Failed to sync temp file: {e}"))?;
-
-        // Cross-platform atomic rename (handles Windows remove internally)
-        super::fs_utils::atomic_rename(&temp_path, &path)?;
-
-        Ok::<(), String>(())
+        write_json_atomic(&path, &preset_clone)
     })
     .await
     .map_err(|e| format!("Task join error: {e}"))??;
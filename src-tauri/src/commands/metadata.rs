@@ -4,8 +4,8 @@
 //! user metadata stored in ~/.claude-history-viewer/user-data.json
 
 use crate::models::{ProjectMetadata, SessionMetadata, UserMetadata, UserSettings};
+use crate::utils::{read_json_or_recover, write_json_atomic};
 use std::fs;
-use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use tauri::State;
@@ -73,15 +73,10 @@ pub async fn get_metadata_folder_path() -> Result<String, String> {
 pub async fn load_user_metadata(state: State<'_, MetadataState>) -> Result<UserMetadata, String> {
     let path = get_user_data_path()?;
 
-    // Perform blocking file I/O off the async runtime
+    // Perform blocking file I/O off the async runtime. A corrupt file is
+    // backed up and replaced with defaults rather than failing the load.
     let metadata = tauri::async_runtime::spawn_blocking(move || {
-        if path.exists() {
-            let content = fs::read_to_string(&path)
-                .map_err(|e| format!("Failed to read metadata file: {e}"))?;
-            serde_json::from_str(&content).map_err(|e| format!("Failed to parse metadata: {e}"))
-        } else {
-            Ok(UserMetadata::new())
-        }
+        read_json_or_recover(&path, UserMetadata::new)
     })
     .await
     .map_err(|e| format!("Task join error: {e}"))??;
@@ -100,23 +95,7 @@ pub async fn load_user_metadata(state: State<'_, MetadataState>) -> Result<UserM
 fn save_metadata_to_disk(metadata: &UserMetadata) -> Result<(), String> {
     ensure_metadata_folder()?;
     let path = get_user_data_path()?;
-
-    // Write to temp file first (atomic write pattern)
-    let temp_path = path.with_extension("json.tmp");
-    let content = serde_json::to_string_pretty(metadata)
-        .map_err(|e| format!("Failed to serialize metadata: {e}"))?;
-
-    let mut file =
-        fs::File::create(&temp_path).map_err(|e| format!("Failed to create temp file: {e}"))?;
-    file.write_all(content.as_bytes())
-        .map_err(|e| format!("Failed to write temp file: {e}"))?;
-    file.sync_all()
-        .map_err(|e| format!("Failed to sync temp file: {e}"))?;
-
-    // Cross-platform atomic rename
-    super::fs_utils::atomic_rename(&temp_path, &path)?;
-
-    Ok(())
+    write_json_atomic(&path, metadata)
 }
 
 /// Save user metadata to disk with atomic write
@@ -289,13 +268,17 @@ pub async fn get_session_display_name(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use lazy_static::lazy_static;
     use std::env;
-    use std::sync::{LazyLock, Mutex, MutexGuard};
+    use std::io::Write;
+    use std::sync::{Mutex, MutexGuard};
     use tempfile::TempDir;
 
-    /// Static mutex to serialize tests that modify the HOME environment variable.
-    /// This prevents race conditions when multiple tests run in parallel.
-    static TEST_ENV_MUTEX: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+    lazy_static! {
+        /// Static mutex to serialize tests that modify the HOME environment variable.
+        /// This prevents race conditions when multiple tests run in parallel.
+        static ref TEST_ENV_MUTEX: Mutex<()> = Mutex::new(());
+    }
 
     /// Sets up a test environment with a temporary HOME directory.
     /// Returns both the mutex guard (to hold the lock) and the `TempDir`.
@@ -1,10 +1,12 @@
 pub mod claude_settings;
+pub mod export;
 pub mod feedback;
 pub mod fs_utils;
 pub mod mcp_presets;
 pub mod metadata;
 pub mod multi_provider;
 pub mod project;
+pub mod search_history;
 pub mod session;
 pub mod settings;
 pub mod stats;
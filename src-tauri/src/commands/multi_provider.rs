@@ -1,8 +1,241 @@
-use crate::models::{ClaudeMessage, ClaudeProject, ClaudeSession};
-use crate::providers;
-use crate::utils::parse_rfc3339_utc;
+use crate::models::{
+    ClaudeMessage, ClaudeProject, ClaudeSession, MessageList, MessageStreamEvent, PageInfo,
+    SearchHit, MESSAGE_SCHEMA_VERSION,
+};
+use crate::providers::{self, user_prompt_text, MessageWindow, ProjectScan, Provider};
+use crate::utils::{parse_rfc3339_utc, SearchPattern, SearchScope};
+use lazy_static::lazy_static;
+use serde::Serialize;
 use serde_json::Value;
 use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::ipc::Channel;
+
+/// How long an identical search is served from the cache
+const SEARCH_CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// Messages per `MessageStreamEvent::Messages` when the caller gives no size
+const DEFAULT_STREAM_BATCH_SIZE: usize = 200;
+
+/// Everything that affects a `search_all_providers` result
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SearchCacheKey {
+    claude_path: Option<String>,
+    query: String,
+    providers: Vec<String>,
+    filters: String,
+    limit: usize,
+    regex: bool,
+    roles: Option<Vec<String>>,
+    tool: Option<String>,
+    collapse_similar: bool,
+}
+
+/// Single-entry cache of the last search, so re-running it (e.g. on back
+/// navigation) skips the scan
+struct SearchCache {
+    last: Option<(SearchCacheKey, Instant, Vec<ClaudeMessage>)>,
+}
+
+impl SearchCache {
+    const fn new() -> Self {
+        Self { last: None }
+    }
+
+    fn get(&self, key: &SearchCacheKey, now: Instant) -> Option<Vec<ClaudeMessage>> {
+        self.last
+            .as_ref()
+            .filter(|(cached_key, stored_at, _)| {
+                cached_key == key && now.duration_since(*stored_at) < SEARCH_CACHE_TTL
+            })
+            .map(|(_, _, results)| results.clone())
+    }
+
+    fn put(&mut self, key: SearchCacheKey, results: Vec<ClaudeMessage>, now: Instant) {
+        self.last = Some((key, now, results));
+    }
+
+    fn clear(&mut self) {
+        self.last = None;
+    }
+}
+
+static SEARCH_CACHE: Mutex<SearchCache> = Mutex::new(SearchCache::new());
+
+/// Drop the cached search, e.g. when session files change on disk
+pub(crate) fn invalidate_search_cache() {
+    if let Ok(mut cache) = SEARCH_CACHE.lock() {
+        cache.clear();
+    }
+}
+
+/// A provider and the directories its scan reads
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ScanCacheKey {
+    provider: String,
+    roots: Vec<PathBuf>,
+}
+
+/// A scan result and the newest mtime seen below its roots before scanning
+struct CachedScan {
+    stamp: SystemTime,
+    scan: ProjectScan,
+}
+
+lazy_static! {
+    /// Last scan per provider and data location. Changing an env override
+    /// (e.g. `$OPENCODE_HOME`) resolves to other roots, so the old entry is no
+    /// longer used.
+    static ref SCAN_CACHE: Mutex<HashMap<ScanCacheKey, CachedScan>> = Mutex::new(HashMap::new());
+}
+
+/// Newest modification time of `roots` and the entries at most `depth`
+/// levels below them, `None` if none exist. Session files deeper down aren't
+/// read, so stamping stays cheap for large histories.
+fn scan_stamp(roots: &[PathBuf], depth: usize) -> Option<SystemTime> {
+    roots
+        .iter()
+        .filter_map(|root| providers::latest_mtime(root, depth))
+        .max()
+}
+
+/// Drop all cached scans, e.g. when a session file is written to: the stamp
+/// doesn't see writes inside existing files
+pub(crate) fn invalidate_scan_cache() {
+    if let Ok(mut cache) = SCAN_CACHE.lock() {
+        cache.clear();
+    }
+}
+
+fn cached_scan(key: &ScanCacheKey, stamp: SystemTime) -> Option<ProjectScan> {
+    let cache = SCAN_CACHE.lock().ok()?;
+    cache
+        .get(key)
+        .filter(|cached| cached.stamp == stamp)
+        .map(|cached| cached.scan.clone())
+}
+
+fn store_scan(key: ScanCacheKey, stamp: SystemTime, scan: &ProjectScan) {
+    if let Ok(mut cache) = SCAN_CACHE.lock() {
+        cache.insert(
+            key,
+            CachedScan {
+                stamp,
+                scan: scan.clone(),
+            },
+        );
+    }
+}
+
+/// Scan a file-backed provider, reusing its last result while nothing below
+/// its `scan_roots` changed. Failed scans aren't cached.
+fn scan_provider_cached(
+    provider: &dyn Provider,
+    force_refresh: bool,
+) -> Result<ProjectScan, String> {
+    let key = ScanCacheKey {
+        provider: provider.id().as_str().to_string(),
+        roots: provider.scan_roots(),
+    };
+    let Some(stamp) = scan_stamp(&key.roots, provider.scan_depth()) else {
+        return provider.scan_projects_with_warnings();
+    };
+    if !force_refresh {
+        if let Some(scan) = cached_scan(&key, stamp) {
+            return Ok(scan);
+        }
+    }
+    let scan = provider.scan_projects_with_warnings()?;
+    store_scan(key, stamp, &scan);
+    Ok(scan)
+}
+
+/// Claude Code counterpart of `scan_provider_cached`; its scan lists
+/// `{base}/projects/{project}`
+async fn scan_claude_cached(
+    base: String,
+    force_refresh: bool,
+) -> Result<Vec<ClaudeProject>, String> {
+    let key = ScanCacheKey {
+        provider: "claude".to_string(),
+        roots: vec![Path::new(&base).join("projects")],
+    };
+    let roots = key.roots.clone();
+    let stamp = tauri::async_runtime::spawn_blocking(move || scan_stamp(&roots, 1))
+        .await
+        .map_err(|e| format!("Task join error: {e}"))?;
+    if let (Some(stamp), false) = (stamp, force_refresh) {
+        if let Some(scan) = cached_scan(&key, stamp) {
+            return Ok(scan.projects);
+        }
+    }
+    let scan = ProjectScan {
+        projects: crate::commands::project::scan_projects(base).await?,
+        warnings: Vec::new(),
+    };
+    if let Some(stamp) = stamp {
+        store_scan(key, stamp, &scan);
+    }
+    Ok(scan.projects)
+}
+
+/// Items returned by the multi-provider commands that carry a provider tag
+trait ProviderTagged {
+    fn provider_mut(&mut self) -> &mut Option<String>;
+}
+
+impl ProviderTagged for ClaudeMessage {
+    fn provider_mut(&mut self) -> &mut Option<String> {
+        &mut self.provider
+    }
+}
+
+impl ProviderTagged for ClaudeSession {
+    fn provider_mut(&mut self) -> &mut Option<String> {
+        &mut self.provider
+    }
+}
+
+impl ProviderTagged for ClaudeProject {
+    fn provider_mut(&mut self) -> &mut Option<String> {
+        &mut self.provider
+    }
+}
+
+/// Fill in the dispatched provider on items a provider left untagged, so every
+/// command result can be grouped by provider
+fn backfill_provider<T: ProviderTagged>(items: &mut [T], provider: &str) {
+    for item in items {
+        let tag = item.provider_mut();
+        if tag.is_none() {
+            *tag = Some(provider.to_string());
+        }
+    }
+}
+
+/// Clear the cached result of the last `search_all_providers` call
+#[tauri::command]
+pub async fn clear_search_cache() -> Result<(), String> {
+    invalidate_search_cache();
+    Ok(())
+}
+
+/// Registered providers whose id is in `selected`
+fn selected_providers(selected: &[String]) -> impl Iterator<Item = &'static dyn Provider> + '_ {
+    providers::registry()
+        .iter()
+        .map(AsRef::as_ref)
+        .filter(|provider| selected.iter().any(|id| id == provider.id().as_str()))
+}
+
+/// The registered provider with this id, or an "Unknown provider" error
+fn registered_provider(id: &str) -> Result<&'static dyn Provider, String> {
+    providers::find(id).ok_or_else(|| format!("Unknown provider: {id}"))
+}
 
 /// Detect all available providers
 #[tauri::command]
@@ -10,64 +243,172 @@ pub async fn detect_providers() -> Result<Vec<providers::ProviderInfo>, String>
     Ok(providers::detect_providers())
 }
 
+/// A provider whose scan or search failed, or had to leave something out,
+/// reported next to the results that did come back so the UI can say why
+/// some are missing
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ProviderWarning {
+    pub provider: String,
+    pub error: String,
+}
+
+impl ProviderWarning {
+    fn new(provider: &str, error: impl Into<String>) -> Self {
+        Self {
+            provider: provider.to_string(),
+            error: error.into(),
+        }
+    }
+}
+
+/// Result of `scan_all_projects_with_warnings`
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanResult {
+    pub projects: Vec<ClaudeProject>,
+    pub warnings: Vec<ProviderWarning>,
+}
+
+/// Result of `search_all_providers_with_warnings`
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    /// Normalizer version that produced `results` (see `MESSAGE_SCHEMA_VERSION`)
+    pub schema_version: u32,
+    pub results: Vec<ClaudeMessage>,
+    pub warnings: Vec<ProviderWarning>,
+}
+
 /// Scan projects from all (or selected) providers
+///
+/// With `only_existing`, projects whose `actual_path` directory is gone are
+/// left out; otherwise they are returned with `path_exists: false`. Projects
+/// with an unknown (empty) `actual_path` are kept either way. With
+/// `include_orphans`, `OpenCode` session directories that lost their project
+/// file are listed as well (see `opencode_reconstruct_projects`).
+///
+/// Each provider's scan is cached and reused until a session is added to or
+/// removed from the directories it lists. `force_refresh` rescans
+/// regardless, e.g. to pick up a project directory or git branch that
+/// changed elsewhere.
+///
+/// Provider failures are only logged; use `scan_all_projects_with_warnings`
+/// to get them back.
 #[tauri::command]
 pub async fn scan_all_projects(
     claude_path: Option<String>,
     active_providers: Option<Vec<String>>,
+    only_existing: Option<bool>,
+    include_orphans: Option<bool>,
+    force_refresh: Option<bool>,
 ) -> Result<Vec<ClaudeProject>, String> {
-    let providers_to_scan = active_providers.unwrap_or_else(|| {
-        vec![
-            "claude".to_string(),
-            "codex".to_string(),
-            "opencode".to_string(),
-        ]
-    });
+    Ok(scan_all_projects_with_warnings(
+        claude_path,
+        active_providers,
+        only_existing,
+        include_orphans,
+        force_refresh,
+    )
+    .await?
+    .projects)
+}
+
+/// Scan projects from all (or selected) providers, reporting each provider
+/// that failed as a `ProviderWarning` instead of dropping it silently. A
+/// project a provider had to skip, e.g. an `OpenCode` project whose session
+/// directory is missing, is reported the same way.
+#[tauri::command]
+pub async fn scan_all_projects_with_warnings(
+    claude_path: Option<String>,
+    active_providers: Option<Vec<String>>,
+    only_existing: Option<bool>,
+    include_orphans: Option<bool>,
+    force_refresh: Option<bool>,
+) -> Result<ScanResult, String> {
+    let providers_to_scan = active_providers.unwrap_or_else(providers::all_provider_ids);
+    let force_refresh = force_refresh.unwrap_or(false);
+
+    // File-backed providers are walked on blocking threads so they run
+    // concurrently with each other and with the async Claude scan.
+    let scans: Vec<_> = selected_providers(&providers_to_scan)
+        .map(|provider| {
+            let handle = tauri::async_runtime::spawn_blocking(move || {
+                scan_provider_cached(provider, force_refresh)
+            });
+            (provider.id(), handle)
+        })
+        .collect();
+    let orphan_scan = (include_orphans.unwrap_or(false)
+        && providers_to_scan.iter().any(|p| p == "opencode"))
+    .then(|| tauri::async_runtime::spawn_blocking(providers::opencode::reconstruct_projects));
 
     let mut all_projects = Vec::new();
+    let mut warnings = Vec::new();
 
     // Claude
     if providers_to_scan.iter().any(|p| p == "claude") {
         let claude_base = claude_path.or_else(providers::claude::get_base_path);
         if let Some(base) = claude_base {
-            match crate::commands::project::scan_projects(base).await {
+            match scan_claude_cached(base, force_refresh).await {
                 Ok(mut projects) => {
-                    for p in &mut projects {
-                        if p.provider.is_none() {
-                            p.provider = Some("claude".to_string());
-                        }
-                    }
+                    backfill_provider(&mut projects, "claude");
                     all_projects.extend(projects);
                 }
                 Err(e) => {
                     log::warn!("Claude scan failed: {e}");
+                    warnings.push(ProviderWarning::new("claude", e));
                 }
             }
         }
     }
 
-    // Codex
-    if providers_to_scan.iter().any(|p| p == "codex") {
-        match providers::codex::scan_projects() {
-            Ok(projects) => all_projects.extend(projects),
+    for (id, handle) in scans {
+        let label = id.display_name();
+        match handle.await {
+            Ok(Ok(mut scan)) => {
+                backfill_provider(&mut scan.projects, id.as_str());
+                all_projects.extend(scan.projects);
+                for e in scan.warnings {
+                    log::warn!("{label} scan skipped a project: {e}");
+                    warnings.push(ProviderWarning::new(id.as_str(), e));
+                }
+            }
+            Ok(Err(e)) => {
+                log::warn!("{label} scan failed: {e}");
+                warnings.push(ProviderWarning::new(id.as_str(), e));
+            }
             Err(e) => {
-                log::warn!("Codex scan failed: {e}");
+                log::warn!("{label} scan task failed: {e}");
+                warnings.push(ProviderWarning::new(
+                    id.as_str(),
+                    format!("Task join error: {e}"),
+                ));
             }
         }
     }
 
-    // OpenCode
-    if providers_to_scan.iter().any(|p| p == "opencode") {
-        match providers::opencode::scan_projects() {
-            Ok(projects) => all_projects.extend(projects),
+    if let Some(handle) = orphan_scan {
+        match handle.await {
+            Ok(Ok(projects)) => all_projects.extend(projects),
+            Ok(Err(e)) => {
+                log::warn!("OpenCode orphan scan failed: {e}");
+                warnings.push(ProviderWarning::new("opencode", e));
+            }
             Err(e) => {
-                log::warn!("OpenCode scan failed: {e}");
+                log::warn!("OpenCode orphan scan task failed: {e}");
+                warnings.push(ProviderWarning::new(
+                    "opencode",
+                    format!("Task join error: {e}"),
+                ));
             }
         }
     }
 
     // Hide empty containers that have no session files regardless of provider.
     all_projects.retain(|project| project.session_count > 0);
+    if only_existing.unwrap_or(false) {
+        // A project without a known path (e.g. a reconstructed orphan) can't
+        // be checked, so it stays
+        all_projects.retain(|project| project.path_exists || project.actual_path.is_empty());
+    }
 
     all_projects.sort_by(|a, b| {
         match (
@@ -80,7 +421,59 @@ pub async fn scan_all_projects(
             (None, None) => b.last_modified.cmp(&a.last_modified),
         }
     });
-    Ok(all_projects)
+    Ok(ScanResult {
+        projects: all_projects,
+        warnings,
+    })
+}
+
+/// `OpenCode` projects synthesized from session directories whose project
+/// file is missing, so their sessions remain visible
+#[tauri::command]
+pub async fn opencode_reconstruct_projects() -> Result<Vec<ClaudeProject>, String> {
+    tauri::async_runtime::spawn_blocking(providers::opencode::reconstruct_projects)
+        .await
+        .map_err(|e| format!("Task join error: {e}"))?
+}
+
+/// Find which provider owns a raw session id (e.g. copied from a log or URL),
+/// returning `(provider, session_path)` for the first match. Claude Code is
+/// probed first, then the registered providers in order; Claude Code and Codex
+/// ids must be UUIDs, `OpenCode` ids a single storage path component. Gemini
+/// CLI keeps ids inside its chat files and never matches.
+#[tauri::command]
+pub async fn resolve_session(
+    id: String,
+    claude_path: Option<String>,
+) -> Result<Option<(String, String)>, String> {
+    let claude_base = claude_path.or_else(providers::claude::get_base_path);
+    tauri::async_runtime::spawn_blocking(move || {
+        let id = id.trim();
+        claude_base
+            .and_then(|base| find_claude_session(Path::new(&base), id))
+            .map(|path| (providers::ProviderId::Claude, path))
+            .or_else(|| {
+                providers::registry().iter().find_map(|provider| {
+                    provider.find_session(id).map(|path| (provider.id(), path))
+                })
+            })
+            .map(|(provider, path)| (provider.as_str().to_string(), path))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))
+}
+
+/// Path of `{claude_path}/projects/*/{id}.jsonl`, if `id` is a UUID and such a
+/// session file exists
+fn find_claude_session(claude_path: &Path, id: &str) -> Option<String> {
+    let uuid = uuid::Uuid::parse_str(id).ok()?;
+    let file_name = format!("{}.jsonl", uuid.hyphenated());
+    std::fs::read_dir(claude_path.join("projects"))
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path().join(&file_name))
+        .find(|path| path.is_file())
+        .map(|path| path.to_string_lossy().to_string())
 }
 
 /// Load sessions for a specific provider's project
@@ -92,116 +485,683 @@ pub async fn load_provider_sessions(
 ) -> Result<Vec<ClaudeSession>, String> {
     let exclude = exclude_sidechain.unwrap_or(false);
 
-    match provider.as_str() {
-        "claude" => {
-            let mut sessions =
-                crate::commands::session::load_project_sessions(project_path, Some(exclude))
-                    .await?;
-            for s in &mut sessions {
-                if s.provider.is_none() {
-                    s.provider = Some("claude".to_string());
-                }
-            }
-            Ok(sessions)
+    let mut sessions = if provider == "claude" {
+        crate::commands::session::load_project_sessions(project_path, Some(exclude)).await?
+    } else {
+        registered_provider(&provider)?.load_sessions(&project_path, exclude)?
+    };
+    backfill_provider(&mut sessions, &provider);
+    Ok(sessions)
+}
+
+/// List sessions flagged with errors across all (or selected) providers,
+/// most recently modified first.
+///
+/// Codex sessions don't record errors yet, so they never appear here.
+#[tauri::command]
+pub async fn sessions_with_errors(
+    claude_path: Option<String>,
+    active_providers: Option<Vec<String>>,
+    limit: Option<usize>,
+) -> Result<Vec<ClaudeSession>, String> {
+    let max_results = limit.unwrap_or(100);
+    let projects = scan_all_projects(claude_path, active_providers, None, None, None).await?;
+
+    let mut errored = Vec::new();
+    for project in projects {
+        let provider = project
+            .provider
+            .clone()
+            .unwrap_or_else(|| "claude".to_string());
+        match load_provider_sessions(provider, project.path.clone(), None).await {
+            Ok(sessions) => errored.extend(sessions.into_iter().filter(|s| s.has_errors)),
+            Err(e) => log::warn!("Skipping project {} in error listing: {e}", project.path),
+        }
+    }
+
+    errored.sort_by(|a, b| {
+        match (
+            parse_rfc3339_utc(&a.last_modified),
+            parse_rfc3339_utc(&b.last_modified),
+        ) {
+            (Some(a_ts), Some(b_ts)) => b_ts.cmp(&a_ts),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => b.last_modified.cmp(&a.last_modified),
+        }
+    });
+    errored.truncate(max_results);
+    Ok(errored)
+}
+
+/// Read a Claude Code JSONL session only up to its first user prompt
+fn claude_first_user_message(session_path: &Path) -> Result<Option<String>, String> {
+    let file = std::fs::File::open(session_path)
+        .map_err(|e| format!("Failed to open session file: {e}"))?;
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| format!("Failed to read session file: {e}"))?;
+        let Ok(val) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        if val.get("type").and_then(Value::as_str) != Some("user")
+            || val.get("isMeta").and_then(Value::as_bool) == Some(true)
+        {
+            continue;
         }
-        "codex" => providers::codex::load_sessions(&project_path, exclude),
-        "opencode" => providers::opencode::load_sessions(&project_path, exclude),
-        _ => Err(format!("Unknown provider: {provider}")),
+        if let Some(text) = val
+            .get("message")
+            .and_then(|m| m.get("content"))
+            .and_then(user_prompt_text)
+        {
+            return Ok(Some(text));
+        }
+    }
+    Ok(None)
+}
+
+/// Text of a session's first user message, for previews; `None` when the
+/// session has no user prompt.
+///
+/// Claude Code and `OpenCode` sessions are read only as far as needed;
+/// Codex and Gemini CLI sessions are loaded in full.
+#[tauri::command]
+pub async fn session_first_user_message(
+    provider: String,
+    session_path: String,
+) -> Result<Option<String>, String> {
+    if provider == "claude" {
+        return claude_first_user_message(Path::new(&session_path));
     }
+    registered_provider(&provider)?.first_user_message(&session_path)
+}
+
+/// Best-effort dominant programming language of a session (e.g. `"rust"`),
+/// counted from file extensions in tool inputs and fenced code-block hints.
+/// `None` when nothing recognizable turns up.
+#[tauri::command]
+pub async fn session_primary_language(
+    provider: String,
+    session_path: String,
+) -> Result<Option<String>, String> {
+    let messages = load_normalized_messages(provider, session_path, None, None).await?;
+    Ok(detect_primary_language(&messages))
+}
+
+/// Get the normalized message schema version (`MESSAGE_SCHEMA_VERSION`),
+/// e.g. to check compatibility before loading anything. Load and search
+/// results carry it on their envelope as `schema_version`.
+#[tauri::command]
+pub fn get_message_schema_version() -> u32 {
+    MESSAGE_SCHEMA_VERSION
 }
 
 /// Load messages from a specific provider's session
+///
+/// When `combine_tool_pairs` is set, each `tool_use` and its matching
+/// `tool_result` are collapsed into a single `{"type": "tool", ...}` item.
+///
+/// When `project_root` (the project's `actual_path`) is given, relative paths
+/// in file tool inputs are resolved against it; see `annotate_resolved_paths`.
+///
+/// With `offset` or `limit`, only one page is loaded, counted back from the
+/// newest message like `load_session_messages_paginated`: the newest
+/// `offset` messages are skipped and up to `limit` before them are returned,
+/// with the paging state in `page`. A page starts early enough that each
+/// tool result in it is merged with its tool use, so it can hold more than
+/// `limit` messages; pass `page.next_offset` back for the next page.
+/// `OpenCode` reads parts only for the loaded page.
+///
+/// No returned message has an empty content array; see `clear_empty_content`.
 #[tauri::command]
 pub async fn load_provider_messages(
     provider: String,
     session_path: String,
+    combine_tool_pairs: Option<bool>,
+    project_root: Option<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<MessageList, String> {
+    if offset.is_none() && limit.is_none() {
+        return load_normalized_messages(provider, session_path, combine_tool_pairs, project_root)
+            .await
+            .map(MessageList::new);
+    }
+
+    let offset = offset.unwrap_or(0);
+    let (mut messages, total_count) =
+        load_paired_page(&provider, session_path, offset, limit.unwrap_or(usize::MAX)).await?;
+    backfill_provider(&mut messages, &provider);
+    let next_offset = offset + messages.len();
+    Ok(MessageList::paged(
+        normalize_loaded_messages(
+            messages,
+            combine_tool_pairs.unwrap_or(false),
+            project_root.as_deref(),
+        ),
+        PageInfo {
+            total_count,
+            has_more: next_offset < total_count,
+            next_offset,
+        },
+    ))
+}
+
+/// The messages of `load_provider_messages`, without the envelope
+pub(crate) async fn load_normalized_messages(
+    provider: String,
+    session_path: String,
+    combine_tool_pairs: Option<bool>,
+    project_root: Option<String>,
 ) -> Result<Vec<ClaudeMessage>, String> {
-    let messages = match provider.as_str() {
-        "claude" => {
-            let mut messages =
-                crate::commands::session::load_session_messages(session_path).await?;
-            for m in &mut messages {
-                if m.provider.is_none() {
-                    m.provider = Some("claude".to_string());
+    let messages = load_raw_messages(&provider, session_path).await?;
+    Ok(normalize_loaded_messages(
+        messages,
+        combine_tool_pairs.unwrap_or(false),
+        project_root.as_deref(),
+    ))
+}
+
+/// A session's messages as the provider returns them, before normalizing
+async fn load_raw_messages(
+    provider: &str,
+    session_path: String,
+) -> Result<Vec<ClaudeMessage>, String> {
+    let mut messages = if provider == "claude" {
+        crate::commands::session::load_session_messages(session_path).await?
+    } else {
+        registered_provider(provider)?.load_messages(&session_path)?
+    };
+    backfill_provider(&mut messages, provider);
+    Ok(messages)
+}
+
+/// Streaming variant of `load_provider_messages`: the same messages are sent
+/// in order over `on_event` in batches of about `batch_size`, followed by a
+/// single `Done` event with the total count. Batches are read from the
+/// provider as they are sent, so the frontend can render the first one
+/// before the rest of the session is read.
+///
+/// Loading stops early if the channel is closed.
+#[tauri::command]
+pub async fn stream_provider_messages(
+    provider: String,
+    session_path: String,
+    on_event: Channel<MessageStreamEvent>,
+    batch_size: Option<usize>,
+    combine_tool_pairs: Option<bool>,
+    project_root: Option<String>,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        stream_messages(
+            &provider,
+            &session_path,
+            batch_size.unwrap_or(DEFAULT_STREAM_BATCH_SIZE),
+            MessageStream::new(
+                &provider,
+                combine_tool_pairs.unwrap_or(false),
+                project_root.as_deref(),
+                |event| {
+                    on_event
+                        .send(event)
+                        .map_err(|e| format!("Failed to send message batch: {e}"))
+                },
+            ),
+        )
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))?
+}
+
+/// Read `session_path` in batches and pass them through `stream`
+fn stream_messages(
+    provider: &str,
+    session_path: &str,
+    batch_size: usize,
+    mut stream: MessageStream<'_, impl FnMut(MessageStreamEvent) -> Result<(), String>>,
+) -> Result<(), String> {
+    let mut on_batch = |batch| stream.push(batch);
+    if provider == "claude" {
+        crate::commands::session::load_session_message_batches(
+            session_path,
+            batch_size,
+            &mut on_batch,
+        )?;
+    } else {
+        registered_provider(provider)?.load_message_batches(
+            session_path,
+            batch_size,
+            &mut on_batch,
+        )?;
+    }
+    stream.finish()
+}
+
+/// Normalizes batches for `stream_provider_messages` and sends them. Tool
+/// results are merged into the tool use they answer, so assistant messages
+/// still waiting for their results are held back, with everything after
+/// them, and sent with the next batch.
+struct MessageStream<'a, F> {
+    provider: &'a str,
+    combine_tool_pairs: bool,
+    project_root: Option<&'a str>,
+    held_back: Vec<ClaudeMessage>,
+    sent: usize,
+    send: F,
+}
+
+impl<'a, F: FnMut(MessageStreamEvent) -> Result<(), String>> MessageStream<'a, F> {
+    fn new(
+        provider: &'a str,
+        combine_tool_pairs: bool,
+        project_root: Option<&'a str>,
+        send: F,
+    ) -> Self {
+        Self {
+            provider,
+            combine_tool_pairs,
+            project_root,
+            held_back: Vec::new(),
+            sent: 0,
+            send,
+        }
+    }
+
+    fn push(&mut self, batch: Vec<ClaudeMessage>) -> Result<(), String> {
+        let mut messages = std::mem::take(&mut self.held_back);
+        messages.extend(batch);
+        let wait_from = awaiting_tool_results_from(&messages);
+        self.held_back = messages.split_off(wait_from);
+        self.send_normalized(messages)
+    }
+
+    fn finish(mut self) -> Result<(), String> {
+        let rest = std::mem::take(&mut self.held_back);
+        self.send_normalized(rest)?;
+        (self.send)(MessageStreamEvent::Done {
+            schema_version: MESSAGE_SCHEMA_VERSION,
+            total_count: self.sent,
+        })
+    }
+
+    fn send_normalized(&mut self, mut messages: Vec<ClaudeMessage>) -> Result<(), String> {
+        backfill_provider(&mut messages, self.provider);
+        let messages =
+            normalize_loaded_messages(messages, self.combine_tool_pairs, self.project_root);
+        if messages.is_empty() {
+            return Ok(());
+        }
+        self.sent += messages.len();
+        (self.send)(MessageStreamEvent::Messages { messages })
+    }
+}
+
+/// Where `messages` must be split so no tool use is sent apart from its
+/// result: the earliest assistant message with a tool use that has no result
+/// after it yet, or whose result lies past a later such split. It and what
+/// follows wait for the next batch; `messages.len()` if nothing waits.
+fn awaiting_tool_results_from(messages: &[ClaudeMessage]) -> usize {
+    let result_at: HashMap<&str, usize> = messages
+        .iter()
+        .enumerate()
+        .filter_map(|(i, m)| Some((i, m.content.as_ref()?)))
+        .flat_map(|(i, content)| tool_result_ids(content).map(move |id| (id, i)))
+        .collect();
+    let mut split = messages.len();
+    for (i, message) in messages.iter().enumerate().rev() {
+        if message.message_type != "assistant" {
+            continue;
+        }
+        let waiting = message
+            .content
+            .as_ref()
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter(|block| block.get("type").and_then(Value::as_str) == Some("tool_use"))
+            .filter_map(|block| block.get("id").and_then(Value::as_str))
+            .any(|id| result_at.get(id).map_or(true, |&at| at <= i || at >= split));
+        if waiting {
+            split = i;
+        }
+    }
+    split
+}
+
+/// The page of `load_provider_messages` at `offset` / `limit`, before
+/// normalizing, and the session's total message count. The window read is
+/// doubled toward older messages until every tool result in the page comes
+/// with its tool use.
+async fn load_paired_page(
+    provider: &str,
+    session_path: String,
+    offset: usize,
+    limit: usize,
+) -> Result<(Vec<ClaudeMessage>, usize), String> {
+    let mut window = MessageWindow { offset, limit };
+    loop {
+        let (mut messages, total) = load_raw_page(provider, session_path.clone(), window).await?;
+        let reaches_start = window.range(total).start == 0;
+        if let Some(start) = paired_page_start(&messages, limit, reaches_start) {
+            messages.drain(..start);
+            return Ok((messages, total));
+        }
+        window.limit = window.limit.saturating_mul(2);
+    }
+}
+
+/// One window of a session as the provider returns it, and its total count
+async fn load_raw_page(
+    provider: &str,
+    session_path: String,
+    window: MessageWindow,
+) -> Result<(Vec<ClaudeMessage>, usize), String> {
+    if provider == "claude" {
+        let page = crate::commands::session::load_session_messages_paginated(
+            session_path,
+            window.offset,
+            window.limit,
+            None,
+        )
+        .await?;
+        return Ok((page.messages, page.total_count));
+    }
+    let registered = registered_provider(provider)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        registered.load_messages_page(&session_path, window)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))?
+}
+
+/// Where a page made of the newest `limit` of `messages` has to start so
+/// every tool result in it is merged with its tool use, following tool
+/// results pulled in along the way. `None` if a tool use may lie before
+/// `messages`; with `reaches_start` there is nothing before them and
+/// results without a tool use stay unpaired.
+fn paired_page_start(
+    messages: &[ClaudeMessage],
+    limit: usize,
+    reaches_start: bool,
+) -> Option<usize> {
+    let mut start = messages.len().saturating_sub(limit);
+    loop {
+        let mut earliest = start;
+        for (i, message) in messages.iter().enumerate().skip(start) {
+            if message.message_type != "user" {
+                continue;
+            }
+            for id in message.content.iter().flat_map(tool_result_ids) {
+                match messages[..i]
+                    .iter()
+                    .rposition(|m| has_matching_tool_use(m, id))
+                {
+                    Some(at) => earliest = earliest.min(at),
+                    None if reaches_start => {}
+                    None => return None,
                 }
             }
-            messages
         }
-        "codex" => providers::codex::load_messages(&session_path)?,
-        "opencode" => providers::opencode::load_messages(&session_path)?,
-        _ => return Err(format!("Unknown provider: {provider}")),
-    };
+        if earliest == start {
+            return Some(start);
+        }
+        start = earliest;
+    }
+}
 
-    Ok(merge_tool_execution_messages(messages))
+/// Shared post-processing of loaded messages: merge tool execution records,
+/// annotate touched and resolved file paths, optionally combine tool pairs,
+/// and clear empty content arrays
+fn normalize_loaded_messages(
+    messages: Vec<ClaudeMessage>,
+    combine_tool_pairs: bool,
+    project_root: Option<&str>,
+) -> Vec<ClaudeMessage> {
+    let mut messages = merge_tool_execution_messages(messages);
+    for msg in &mut messages {
+        annotate_files_touched(msg);
+    }
+    if let Some(root) = project_root.map(Path::new) {
+        if root.is_absolute() {
+            for msg in &mut messages {
+                annotate_resolved_paths(msg, root);
+            }
+        }
+    }
+    if combine_tool_pairs {
+        messages = messages.into_iter().map(combine_tool_pair_blocks).collect();
+    }
+    for msg in &mut messages {
+        clear_empty_content(msg);
+    }
+    messages
+}
+
+/// The message `uuid` of a session plus up to `radius` messages before and
+/// after it, for showing a search hit in its conversation. The window stops
+/// at the start and end of the session.
+///
+/// Search reports messages before tool results are merged, so a hit on a
+/// user message holding only tool results is centered on the message its
+/// results were merged into.
+#[tauri::command]
+pub async fn get_message_context(
+    provider: String,
+    session_path: String,
+    uuid: String,
+    radius: usize,
+) -> Result<Vec<ClaudeMessage>, String> {
+    let not_found = || format!("Message {uuid} not found in session {session_path}");
+    let raw = load_raw_messages(&provider, session_path.clone()).await?;
+    let hit_result_ids: Vec<String> = raw
+        .iter()
+        .find(|m| m.uuid == uuid)
+        .ok_or_else(not_found)?
+        .content
+        .as_ref()
+        .map(|content| tool_result_ids(content).map(String::from).collect())
+        .unwrap_or_default();
+
+    let messages = normalize_loaded_messages(raw, false, None);
+    let index = messages
+        .iter()
+        .position(|m| m.uuid == uuid)
+        .or_else(|| {
+            messages.iter().position(|m| {
+                m.content.as_ref().is_some_and(|c| {
+                    tool_result_ids(c).any(|id| hit_result_ids.iter().any(|hit| hit == id))
+                })
+            })
+        })
+        .ok_or_else(not_found)?;
+
+    let start = index.saturating_sub(radius);
+    let end = index
+        .saturating_add(radius)
+        .saturating_add(1)
+        .min(messages.len());
+    Ok(messages[start..end].to_vec())
 }
 
 /// Search across all (or selected) providers
+///
+/// With `regex` set, `query` is a case-insensitive regular expression matched
+/// against message text only (tool inputs/outputs are skipped). With `roles`
+/// (e.g. `["user"]`), only messages with one of those roles are searched, and
+/// with `tool` (a canonical name such as `Bash` or `Edit`) only messages that
+/// called that tool, so `limit` counts matching messages only.
+///
+/// A message reported twice by the same provider is returned once. With
+/// `collapse_similar`, messages of the same type whose text differs only in
+/// case and whitespace are also collapsed across providers, keeping the
+/// newest.
+///
+/// Provider failures are only logged; use `search_all_providers_with_warnings`
+/// to get them back.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri commands take named, optional args
 pub async fn search_all_providers(
     claude_path: Option<String>,
     query: String,
     active_providers: Option<Vec<String>>,
     filters: Option<Value>,
     limit: Option<usize>,
-) -> Result<Vec<ClaudeMessage>, String> {
+    regex: Option<bool>,
+    roles: Option<Vec<String>>,
+    tool: Option<String>,
+    collapse_similar: Option<bool>,
+) -> Result<MessageList, String> {
+    Ok(MessageList::new(
+        search_all_providers_with_warnings(
+            claude_path,
+            query,
+            active_providers,
+            filters,
+            limit,
+            regex,
+            roles,
+            tool,
+            collapse_similar,
+        )
+        .await?
+        .results,
+    ))
+}
+
+/// [`search_all_providers`], with each result wrapped in a [`SearchHit`]
+/// carrying a snippet of the text around the match
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri commands take named, optional args
+pub async fn search_all_providers_hits(
+    claude_path: Option<String>,
+    query: String,
+    active_providers: Option<Vec<String>>,
+    filters: Option<Value>,
+    limit: Option<usize>,
+    regex: Option<bool>,
+    roles: Option<Vec<String>>,
+    tool: Option<String>,
+    collapse_similar: Option<bool>,
+) -> Result<MessageList<SearchHit>, String> {
+    let pattern = SearchPattern::new(&query, regex.unwrap_or(false))?;
+    let results = search_all_providers(
+        claude_path,
+        query,
+        active_providers,
+        filters,
+        limit,
+        regex,
+        roles,
+        tool,
+        collapse_similar,
+    )
+    .await?;
+    Ok(MessageList::new(
+        results
+            .messages
+            .into_iter()
+            .map(|message| {
+                let (snippet, match_ranges) = message
+                    .content
+                    .as_ref()
+                    .and_then(|content| pattern.snippet(content))
+                    .unwrap_or_default();
+                SearchHit {
+                    message,
+                    snippet,
+                    match_ranges,
+                }
+            })
+            .collect(),
+    ))
+}
+
+/// Search across all (or selected) providers, reporting each provider that
+/// failed as a `ProviderWarning`.
+///
+/// Only complete (warning-free) searches are cached.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri commands take named, optional args
+pub async fn search_all_providers_with_warnings(
+    claude_path: Option<String>,
+    query: String,
+    active_providers: Option<Vec<String>>,
+    filters: Option<Value>,
+    limit: Option<usize>,
+    regex: Option<bool>,
+    roles: Option<Vec<String>>,
+    tool: Option<String>,
+    collapse_similar: Option<bool>,
+) -> Result<SearchResult, String> {
     let max_results = limit.unwrap_or(100);
+    let regex = regex.unwrap_or(false);
+    let collapse_similar = collapse_similar.unwrap_or(false);
+    let pattern = SearchPattern::new(&query, regex)?;
     let search_filters =
         filters.unwrap_or_else(|| serde_json::Value::Object(serde_json::Map::default()));
     crate::commands::session::validate_search_filters(&search_filters)?;
 
-    let providers_to_search = active_providers.unwrap_or_else(|| {
-        vec![
-            "claude".to_string(),
-            "codex".to_string(),
-            "opencode".to_string(),
-        ]
-    });
+    let providers_to_search = active_providers.unwrap_or_else(providers::all_provider_ids);
+
+    let cache_key = SearchCacheKey {
+        claude_path: claude_path.clone(),
+        query: query.clone(),
+        providers: providers_to_search.clone(),
+        filters: search_filters.to_string(),
+        limit: max_results,
+        regex,
+        roles: roles.clone(),
+        tool: tool.clone(),
+        collapse_similar,
+    };
+    let scope = SearchScope { roles, tool };
+    if let Some(cached) = SEARCH_CACHE
+        .lock()
+        .ok()
+        .and_then(|cache| cache.get(&cache_key, Instant::now()))
+    {
+        return Ok(SearchResult {
+            schema_version: MESSAGE_SCHEMA_VERSION,
+            results: cached,
+            warnings: Vec::new(),
+        });
+    }
 
     let mut all_results = Vec::new();
+    let mut warnings = Vec::new();
 
     // Claude
     if providers_to_search.iter().any(|p| p == "claude") {
         let claude_base = claude_path.or_else(providers::claude::get_base_path);
         if let Some(base) = claude_base {
-            match crate::commands::session::search_messages(
+            match crate::commands::session::search_messages_matching(
                 base,
-                query.clone(),
+                &pattern,
+                &scope,
                 search_filters.clone(),
                 Some(max_results),
-            )
-            .await
-            {
+            ) {
                 Ok(mut results) => {
-                    for m in &mut results {
-                        if m.provider.is_none() {
-                            m.provider = Some("claude".to_string());
-                        }
-                    }
+                    backfill_provider(&mut results, "claude");
                     all_results.extend(results);
                 }
                 Err(e) => {
                     log::warn!("Claude search failed: {e}");
+                    warnings.push(ProviderWarning::new("claude", e));
                 }
             }
         }
     }
 
-    // Codex
-    if providers_to_search.iter().any(|p| p == "codex") {
-        match providers::codex::search(&query, max_results) {
-            Ok(results) => all_results.extend(results),
-            Err(e) => {
-                log::warn!("Codex search failed: {e}");
+    for provider in selected_providers(&providers_to_search) {
+        let id = provider.id();
+        match provider.search(&pattern, &scope, max_results) {
+            Ok(mut results) => {
+                backfill_provider(&mut results, id.as_str());
+                all_results.extend(results);
             }
-        }
-    }
-
-    // OpenCode
-    if providers_to_search.iter().any(|p| p == "opencode") {
-        match providers::opencode::search(&query, max_results) {
-            Ok(results) => all_results.extend(results),
             Err(e) => {
-                log::warn!("OpenCode search failed: {e}");
+                log::warn!("{} search failed: {e}", id.display_name());
+                warnings.push(ProviderWarning::new(id.as_str(), e));
             }
         }
     }
@@ -219,10 +1179,105 @@ pub async fn search_all_providers(
             (None, Some(_)) => Ordering::Greater,
             (None, None) => b.timestamp.cmp(&a.timestamp),
         }
-    });
-    all_results.truncate(max_results);
+    });
+    all_results = dedup_search_results(all_results, collapse_similar);
+    all_results.truncate(max_results);
+
+    // Tag where each match fell so the UI can filter code-only hits
+    for m in &mut all_results {
+        if let Some(content) = &m.content {
+            m.match_locations = Some(pattern.content_match_locations(content));
+        }
+    }
+
+    if warnings.is_empty() {
+        if let Ok(mut cache) = SEARCH_CACHE.lock() {
+            cache.put(cache_key, all_results.clone(), Instant::now());
+        }
+    }
+
+    Ok(SearchResult {
+        schema_version: MESSAGE_SCHEMA_VERSION,
+        results: all_results,
+        warnings,
+    })
+}
+
+/// Drop repeated search results, keeping the first of each: the same
+/// `(provider, uuid)`, and with `collapse_similar` the same message type and
+/// normalized text regardless of provider
+fn dedup_search_results(results: Vec<ClaudeMessage>, collapse_similar: bool) -> Vec<ClaudeMessage> {
+    let mut seen_ids = HashSet::new();
+    let mut seen_texts = HashSet::new();
+    results
+        .into_iter()
+        .filter(|m| seen_ids.insert((m.provider.clone(), m.uuid.clone())))
+        .filter(|m| {
+            if !collapse_similar {
+                return true;
+            }
+            let text = m.content.as_ref().map(normalized_text).unwrap_or_default();
+            text.is_empty() || seen_texts.insert((m.message_type.clone(), text))
+        })
+        .collect()
+}
+
+/// Lowercased text of a message's content with whitespace runs collapsed:
+/// a plain string or the `text` items of a content array
+fn normalized_text(content: &Value) -> String {
+    let text = match content {
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|item| item.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => String::new(),
+    };
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Remove sessions last modified before `older_than` (RFC 3339), returning
+/// the affected session paths.
+///
+/// With `dry_run` nothing is touched. Otherwise `OpenCode` sessions are moved
+/// to `~/.claude-history-viewer/trash/opencode`; other providers are refused.
+#[tauri::command]
+pub async fn prune_old_sessions(
+    provider: String,
+    older_than: String,
+    dry_run: bool,
+) -> Result<Vec<String>, String> {
+    let cutoff = parse_rfc3339_utc(&older_than)
+        .ok_or_else(|| format!("Invalid older_than (expected RFC 3339): {older_than}"))?;
+
+    if provider != "opencode" {
+        return Err(format!(
+            "Pruning is only supported for OpenCode sessions, not {provider}"
+        ));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let stale = providers::opencode::sessions_modified_before(cutoff)?;
+        if dry_run || stale.is_empty() {
+            return Ok(stale);
+        }
 
-    Ok(all_results)
+        let home = dirs::home_dir().ok_or("Could not find home directory")?;
+        let trash_dir = home
+            .join(".claude-history-viewer")
+            .join("trash")
+            .join("opencode");
+        for session_path in &stale {
+            providers::opencode::trash_session(session_path, &trash_dir)?;
+        }
+        Ok(stale)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))?
 }
 
 fn merge_tool_execution_messages(messages: Vec<ClaudeMessage>) -> Vec<ClaudeMessage> {
@@ -283,6 +1338,287 @@ fn merge_tool_execution_messages(messages: Vec<ClaudeMessage>) -> Vec<ClaudeMess
     merged
 }
 
+/// Tool input keys that name a single file
+const FILE_INPUT_KEYS: &[&str] = &["file_path", "filePath", "notebook_path"];
+
+/// Tool calls `detect_primary_language` samples before it stops reading
+const LANGUAGE_SAMPLE_TOOL_CALLS: usize = 50;
+
+/// Language of a file extension or code-fence hint (`rs`, `rust`, `tsx`, ...).
+/// Data and markup formats (json, yaml, markdown, shell) don't count.
+fn language_for_hint(hint: &str) -> Option<&'static str> {
+    let language = match hint.to_ascii_lowercase().as_str() {
+        "rs" | "rust" => "rust",
+        "py" | "python" => "python",
+        "ts" | "tsx" | "mts" | "cts" | "typescript" => "typescript",
+        "js" | "jsx" | "mjs" | "cjs" | "javascript" => "javascript",
+        "go" | "golang" => "go",
+        "java" => "java",
+        "kt" | "kts" | "kotlin" => "kotlin",
+        "swift" => "swift",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" | "c++" => "cpp",
+        "cs" | "csharp" | "c#" => "csharp",
+        "rb" | "ruby" => "ruby",
+        "php" => "php",
+        "scala" => "scala",
+        "ex" | "exs" | "elixir" => "elixir",
+        "hs" | "haskell" => "haskell",
+        "lua" => "lua",
+        "dart" => "dart",
+        "zig" => "zig",
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Most frequent language across the file paths of the first
+/// `LANGUAGE_SAMPLE_TOOL_CALLS` tool calls and the code fences of the text
+/// read alongside them. Ties go to the alphabetically first language.
+fn detect_primary_language(messages: &[ClaudeMessage]) -> Option<String> {
+    let mut counts: HashMap<&'static str, usize> = HashMap::new();
+    let mut tool_calls = 0;
+
+    for msg in messages {
+        if tool_calls >= LANGUAGE_SAMPLE_TOOL_CALLS {
+            break;
+        }
+        let Some(Value::Array(blocks)) = msg.content.as_ref() else {
+            continue;
+        };
+        for block in blocks {
+            match block.get("type").and_then(Value::as_str) {
+                Some("tool_use") if tool_calls < LANGUAGE_SAMPLE_TOOL_CALLS => {
+                    tool_calls += 1;
+                    let Some(input) = block.get("input") else {
+                        continue;
+                    };
+                    let languages = FILE_INPUT_KEYS
+                        .iter()
+                        .filter_map(|key| input.get(*key).and_then(Value::as_str))
+                        .filter_map(|path| Path::new(path).extension()?.to_str())
+                        .filter_map(language_for_hint);
+                    for language in languages {
+                        *counts.entry(language).or_default() += 1;
+                    }
+                }
+                Some("text") => {
+                    let text = block.get("text").and_then(Value::as_str).unwrap_or("");
+                    let languages = text
+                        .lines()
+                        .filter_map(|line| line.trim_start().strip_prefix("```"))
+                        .filter_map(|hint| hint.split_whitespace().next())
+                        .filter_map(language_for_hint);
+                    for language in languages {
+                        *counts.entry(language).or_default() += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(language, _)| language.to_string())
+}
+
+/// Replace an empty content array with `content: None`.
+///
+/// Providers can end up with `Some([])` when every item was filtered out, and
+/// the frontend treats an empty array as renderable content. The message
+/// itself is kept since it may still carry usage, cost or metadata.
+fn clear_empty_content(msg: &mut ClaudeMessage) {
+    if matches!(&msg.content, Some(Value::Array(items)) if items.is_empty()) {
+        msg.content = None;
+    }
+}
+
+/// Lexically normalize a path (drop `.` segments, fold `..`) without
+/// touching the filesystem.
+fn normalize_touched_path(raw: &str) -> String {
+    use std::path::Component;
+
+    let mut normalized = std::path::PathBuf::new();
+    for component in Path::new(raw).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized.to_string_lossy().to_string()
+}
+
+/// Record the distinct files named in a message's `tool_use` inputs as
+/// `data.files_touched`, in first-seen order. Progress messages keep their
+/// own `data` untouched.
+fn annotate_files_touched(msg: &mut ClaudeMessage) {
+    if msg.message_type == "progress" {
+        return;
+    }
+    let Some(Value::Array(blocks)) = msg.content.as_ref() else {
+        return;
+    };
+
+    let mut files: Vec<String> = Vec::new();
+    for block in blocks {
+        if block.get("type").and_then(Value::as_str) != Some("tool_use") {
+            continue;
+        }
+        let Some(input) = block.get("input") else {
+            continue;
+        };
+        for key in FILE_INPUT_KEYS {
+            if let Some(raw) = input
+                .get(*key)
+                .and_then(Value::as_str)
+                .filter(|p| !p.is_empty())
+            {
+                let path = normalize_touched_path(raw);
+                if !files.contains(&path) {
+                    files.push(path);
+                }
+            }
+        }
+    }
+    if files.is_empty() {
+        return;
+    }
+
+    let files = Value::from(files);
+    match msg.data.as_mut() {
+        Some(Value::Object(data)) => {
+            data.insert("files_touched".to_string(), files);
+        }
+        Some(_) => {}
+        None => msg.data = Some(serde_json::json!({ "files_touched": files })),
+    }
+}
+
+/// Input key holding the file path for tools whose path can be resolved
+fn tool_path_input_key(tool_name: &str) -> Option<&'static str> {
+    match tool_name {
+        "Read" | "Edit" | "Write" => Some("file_path"),
+        "Grep" => Some("path"),
+        _ => None,
+    }
+}
+
+/// Resolve relative paths in `Read`/`Edit`/`Write`/`Grep` inputs against
+/// `project_root`, recording the absolute path as `data.resolved_path` on the
+/// `tool_use` block. Absolute paths and the input itself are left untouched.
+fn annotate_resolved_paths(msg: &mut ClaudeMessage, project_root: &Path) {
+    let Some(Value::Array(blocks)) = msg.content.as_mut() else {
+        return;
+    };
+
+    for block in blocks {
+        if block.get("type").and_then(Value::as_str) != Some("tool_use") {
+            continue;
+        }
+        let Some(key) = block
+            .get("name")
+            .and_then(Value::as_str)
+            .and_then(tool_path_input_key)
+        else {
+            continue;
+        };
+        let Some(raw_path) = block
+            .get("input")
+            .and_then(|input| input.get(key))
+            .and_then(Value::as_str)
+            .filter(|p| !p.is_empty() && !Path::new(p).is_absolute())
+        else {
+            continue;
+        };
+
+        let resolved = project_root.join(raw_path).to_string_lossy().to_string();
+        match block.get_mut("data") {
+            Some(Value::Object(data)) => {
+                data.insert("resolved_path".to_string(), Value::String(resolved));
+            }
+            _ => block["data"] = serde_json::json!({ "resolved_path": resolved }),
+        }
+    }
+}
+
+/// Collapse `tool_use` + `tool_result` pairs (matched by id) within a message
+/// into `{"type":"tool","id","name","input","result","is_error"}` items.
+///
+/// The combined item takes the `tool_use` position. Unmatched `tool_use` and
+/// `tool_result` blocks are left as they are.
+fn combine_tool_pair_blocks(mut msg: ClaudeMessage) -> ClaudeMessage {
+    let Some(Value::Array(blocks)) = msg.content.take() else {
+        return msg;
+    };
+
+    let results: HashMap<String, Value> = blocks
+        .iter()
+        .filter(|block| block.get("type").and_then(Value::as_str) == Some("tool_result"))
+        .filter_map(|block| {
+            let id = block.get("tool_use_id").and_then(Value::as_str)?;
+            Some((id.to_string(), block.clone()))
+        })
+        .collect();
+    let paired_ids: std::collections::HashSet<&str> = blocks
+        .iter()
+        .filter(|block| block.get("type").and_then(Value::as_str) == Some("tool_use"))
+        .filter_map(|block| block.get("id").and_then(Value::as_str))
+        .filter(|id| results.contains_key(*id))
+        .collect();
+
+    let mut combined = Vec::with_capacity(blocks.len());
+    for block in &blocks {
+        match block.get("type").and_then(Value::as_str) {
+            Some("tool_use") => {
+                let id = block.get("id").and_then(Value::as_str).unwrap_or("");
+                let Some(result) = results.get(id) else {
+                    combined.push(block.clone());
+                    continue;
+                };
+                let mut tool = serde_json::json!({
+                    "type": "tool",
+                    "id": id,
+                    "name": block.get("name").cloned().unwrap_or(Value::Null),
+                    "input": block.get("input").cloned().unwrap_or(Value::Null),
+                    "result": result.get("content").cloned().unwrap_or(Value::Null),
+                    "is_error": result.get("is_error").and_then(Value::as_bool).unwrap_or(false),
+                });
+                if let Some(data) = block.get("data") {
+                    tool["data"] = data.clone();
+                }
+                combined.push(tool);
+            }
+            Some("tool_result") => {
+                let id = block.get("tool_use_id").and_then(Value::as_str);
+                if !id.is_some_and(|id| paired_ids.contains(id)) {
+                    combined.push(block.clone());
+                }
+            }
+            _ => combined.push(block.clone()),
+        }
+    }
+
+    msg.content = Some(Value::Array(combined));
+    msg
+}
+
+/// `tool_use_id`s of the tool result blocks in `content`
+fn tool_result_ids(content: &Value) -> impl Iterator<Item = &str> {
+    content
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|block| block.get("type").and_then(Value::as_str) == Some("tool_result"))
+        .filter_map(|block| block.get("tool_use_id").and_then(Value::as_str))
+}
+
 fn has_matching_tool_use(msg: &ClaudeMessage, tool_use_id: &str) -> bool {
     if msg.message_type != "assistant" {
         return false;
@@ -308,6 +1644,35 @@ fn append_content_block(msg: &mut ClaudeMessage, block: Value) {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn session_first_user_message_skips_meta_and_tool_results() {
+        let tmp = tempfile::TempDir::new().expect("temp dir should be created");
+        let session = tmp.path().join("session.jsonl");
+        let lines = [
+            serde_json::json!({ "type": "summary", "summary": "Fix the bug" }),
+            serde_json::json!({ "type": "user", "isMeta": true, "message": { "role": "user", "content": "Caveat: generated" } }),
+            serde_json::json!({ "type": "user", "message": { "role": "user", "content": "<command-name>/clear</command-name>" } }),
+            serde_json::json!({ "type": "user", "message": { "role": "user", "content": [{ "type": "text", "text": "Why does the build fail?" }] } }),
+            serde_json::json!({ "type": "user", "message": { "role": "user", "content": "Second prompt" } }),
+        ];
+        let body: Vec<String> = lines.iter().map(ToString::to_string).collect();
+        std::fs::write(&session, body.join("\n")).expect("session should be written");
+
+        let first =
+            session_first_user_message("claude".to_string(), session.to_string_lossy().to_string())
+                .await
+                .expect("lookup should succeed");
+        assert_eq!(first.as_deref(), Some("Why does the build fail?"));
+
+        let empty = tmp.path().join("empty.jsonl");
+        std::fs::write(&empty, "").expect("empty session should be written");
+        let none =
+            session_first_user_message("claude".to_string(), empty.to_string_lossy().to_string())
+                .await
+                .expect("lookup should succeed");
+        assert_eq!(none, None);
+    }
+
     fn make_message(message_type: &str, content: Value) -> ClaudeMessage {
         ClaudeMessage {
             uuid: format!("{message_type}-id"),
@@ -342,9 +1707,466 @@ mod tests {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: Some("claude".to_string()),
+            match_locations: None,
+            session_path: None,
+            project_path: None,
         }
     }
 
+    fn streamed_batches(events: &[MessageStreamEvent]) -> Vec<Vec<String>> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                MessageStreamEvent::Messages { messages } => {
+                    Some(messages.iter().map(|m| m.uuid.clone()).collect())
+                }
+                MessageStreamEvent::Done { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn message_stream_keeps_order_and_ends_with_done() {
+        let message = |i: usize| {
+            let mut msg = make_message("user", serde_json::json!("hi"));
+            msg.uuid = format!("msg-{i}");
+            msg
+        };
+        let mut events = Vec::new();
+        let mut stream = MessageStream::new("claude", false, None, |event| {
+            events.push(event);
+            Ok(())
+        });
+        stream
+            .push(vec![message(0), message(1)])
+            .expect("batch should be sent");
+        stream
+            .push(vec![message(2), message(3)])
+            .expect("batch should be sent");
+        stream.push(vec![message(4)]).expect("batch should be sent");
+        stream.finish().expect("stream should finish");
+
+        assert_eq!(
+            streamed_batches(&events),
+            vec![
+                vec!["msg-0", "msg-1"],
+                vec!["msg-2", "msg-3"],
+                vec!["msg-4"]
+            ]
+        );
+        assert!(matches!(
+            events.last(),
+            Some(MessageStreamEvent::Done { total_count: 5, .. })
+        ));
+
+        let mut sent = 0;
+        let mut stream = MessageStream::new("claude", false, None, |_| {
+            sent += 1;
+            Err("channel closed".to_string())
+        });
+        let err = stream
+            .push(vec![message(0)])
+            .expect_err("send failure should stop the stream");
+        assert_eq!(err, "channel closed");
+        assert_eq!(sent, 1);
+
+        let serialized = serde_json::to_value(MessageStreamEvent::Done {
+            schema_version: MESSAGE_SCHEMA_VERSION,
+            total_count: 3,
+        })
+        .expect("event should serialize");
+        assert_eq!(
+            serialized,
+            serde_json::json!({ "event": "done", "data": { "schema_version": 1, "total_count": 3 } })
+        );
+    }
+
+    #[test]
+    fn message_stream_merges_tool_results_from_the_next_batch() {
+        let with_uuid = |mut msg: ClaudeMessage, uuid: &str| {
+            msg.uuid = uuid.to_string();
+            msg
+        };
+        let mut events = Vec::new();
+        let mut stream = MessageStream::new("claude", false, None, |event| {
+            events.push(event);
+            Ok(())
+        });
+        stream
+            .push(vec![
+                with_uuid(make_message("user", serde_json::json!("Run it")), "ask"),
+                with_uuid(
+                    make_message(
+                        "assistant",
+                        serde_json::json!([{ "type": "tool_use", "id": "toolu_1", "name": "Bash", "input": {} }]),
+                    ),
+                    "call",
+                ),
+            ])
+            .expect("batch should be sent");
+        stream
+            .push(vec![
+                with_uuid(
+                    make_message(
+                        "user",
+                        serde_json::json!([{ "type": "tool_result", "tool_use_id": "toolu_1", "content": "ok" }]),
+                    ),
+                    "result",
+                ),
+                with_uuid(make_message("assistant", serde_json::json!("Done")), "reply"),
+            ])
+            .expect("batch should be sent");
+        stream.finish().expect("stream should finish");
+
+        assert_eq!(
+            streamed_batches(&events),
+            vec![vec!["ask"], vec!["call", "reply"]]
+        );
+        let MessageStreamEvent::Messages { messages } = &events[1] else {
+            panic!("second event should be a batch");
+        };
+        let content = messages[0].content.as_ref().expect("content");
+        assert_eq!(content[1]["type"], "tool_result");
+        assert!(matches!(
+            events.last(),
+            Some(MessageStreamEvent::Done { total_count: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn stream_messages_sends_the_first_batch_before_reading_the_rest() {
+        use crate::test_utils::{create_jsonl_content, MessageBuilder, MockClaudeProject};
+        use std::io::Write;
+
+        let mock = MockClaudeProject::new();
+        let builders: Vec<_> = (0..3)
+            .map(|i| {
+                MessageBuilder::user()
+                    .with_uuid(&format!("msg-{i}"))
+                    .with_text_content(&format!("message {i}"))
+            })
+            .collect();
+        let mut content = create_jsonl_content(&builders);
+        content.push('\n');
+        let session = mock.add_session("project", "session", &content);
+
+        // Append a message once the first batch is out; it is only streamed
+        // if the file is still being read at that point
+        let mut events = Vec::new();
+        let stream = MessageStream::new("claude", false, None, |event| {
+            if events.is_empty() {
+                let late = MessageBuilder::user()
+                    .with_uuid("msg-late")
+                    .with_text_content("appended");
+                let mut file = std::fs::OpenOptions::new()
+                    .append(true)
+                    .open(&session)
+                    .expect("session should open");
+                writeln!(file, "{}", late.to_jsonl()).expect("line should be appended");
+            }
+            events.push(event);
+            Ok(())
+        });
+        stream_messages("claude", &session.to_string_lossy(), 2, stream)
+            .expect("stream should finish");
+
+        assert_eq!(
+            streamed_batches(&events),
+            vec![vec!["msg-0", "msg-1"], vec!["msg-2", "msg-late"]]
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_matches_load_when_parallel_tool_uses_straddle_a_batch() {
+        use crate::test_utils::{create_jsonl_content, MessageBuilder, MockClaudeProject};
+
+        let tool_use = |uuid: &str, id: &str| {
+            MessageBuilder::assistant().with_uuid(uuid).with_content(
+                serde_json::json!([{ "type": "tool_use", "id": id, "name": "Read", "input": {} }]),
+            )
+        };
+        let tool_result = |uuid: &str, id: &str| {
+            MessageBuilder::user().with_uuid(uuid).with_content(
+                serde_json::json!([{ "type": "tool_result", "tool_use_id": id, "content": "ok" }]),
+            )
+        };
+        let builders = vec![
+            MessageBuilder::user()
+                .with_uuid("ask")
+                .with_text_content("Read both"),
+            tool_use("call-1", "toolu_1"),
+            tool_use("call-2", "toolu_2"),
+            tool_result("result-1", "toolu_1"),
+            tool_result("result-2", "toolu_2"),
+            MessageBuilder::assistant()
+                .with_uuid("reply")
+                .with_text_content("Done"),
+        ];
+        let mock = MockClaudeProject::new();
+        let session = mock
+            .add_session("project", "session", &create_jsonl_content(&builders))
+            .to_string_lossy()
+            .to_string();
+
+        let mut streamed = Vec::new();
+        let stream = MessageStream::new("claude", true, None, |event| {
+            if let MessageStreamEvent::Messages { messages } = event {
+                streamed.extend(messages);
+            }
+            Ok(())
+        });
+        // Batches of two put each tool use in a different batch than its result
+        stream_messages("claude", &session, 2, stream).expect("stream should finish");
+
+        let loaded =
+            load_provider_messages("claude".to_string(), session, Some(true), None, None, None)
+                .await
+                .expect("session should load");
+        assert_eq!(
+            serde_json::to_value(&streamed).expect("streamed messages should serialize"),
+            serde_json::to_value(&loaded.messages).expect("loaded messages should serialize")
+        );
+        let uuids: Vec<_> = streamed.iter().map(|m| m.uuid.as_str()).collect();
+        assert_eq!(uuids, vec!["ask", "call-1", "call-2", "reply"]);
+    }
+
+    #[tokio::test]
+    async fn get_message_context_clamps_window_at_session_bounds() {
+        use crate::test_utils::{create_jsonl_content, MessageBuilder, MockClaudeProject};
+
+        let mock = MockClaudeProject::new();
+        let builders: Vec<_> = (0..5)
+            .map(|i| {
+                MessageBuilder::user()
+                    .with_uuid(&format!("msg-{i}"))
+                    .with_text_content(&format!("message {i}"))
+            })
+            .collect();
+        let session = mock
+            .add_session("project", "session", &create_jsonl_content(&builders))
+            .to_string_lossy()
+            .to_string();
+        let context = |uuid: &str, radius: usize| {
+            get_message_context(
+                "claude".to_string(),
+                session.clone(),
+                uuid.to_string(),
+                radius,
+            )
+        };
+        let uuids = |messages: Vec<ClaudeMessage>| -> Vec<String> {
+            messages.into_iter().map(|m| m.uuid).collect()
+        };
+
+        let middle = context("msg-2", 1).await.expect("context should load");
+        assert_eq!(uuids(middle), vec!["msg-1", "msg-2", "msg-3"]);
+        let start = context("msg-0", 2).await.expect("context should load");
+        assert_eq!(uuids(start), vec!["msg-0", "msg-1", "msg-2"]);
+        let end = context("msg-4", 10).await.expect("context should load");
+        assert_eq!(uuids(end).len(), 5);
+        let only = context("msg-3", 0).await.expect("context should load");
+        assert_eq!(uuids(only), vec!["msg-3"]);
+
+        let missing = context("msg-9", 1)
+            .await
+            .expect_err("unknown uuid should fail");
+        assert!(missing.contains("msg-9"), "{missing}");
+    }
+
+    #[tokio::test]
+    async fn get_message_context_centers_tool_result_hits_on_the_merged_message() {
+        use crate::test_utils::{create_jsonl_content, MessageBuilder, MockClaudeProject};
+
+        let mock = MockClaudeProject::new();
+        let session = mock
+            .add_session(
+                "project",
+                "session",
+                &create_jsonl_content(&[
+                    MessageBuilder::user()
+                        .with_uuid("ask")
+                        .with_text_content("Run the tests"),
+                    MessageBuilder::assistant().with_uuid("call").with_content(
+                        serde_json::json!([
+                            { "type": "tool_use", "id": "toolu_1", "name": "Bash", "input": { "command": "cargo test" } }
+                        ]),
+                    ),
+                    MessageBuilder::user().with_uuid("result").with_content(
+                        serde_json::json!([
+                            { "type": "tool_result", "tool_use_id": "toolu_1", "content": "test result: FAILED" }
+                        ]),
+                    ),
+                    MessageBuilder::assistant()
+                        .with_uuid("reply")
+                        .with_text_content("One test fails"),
+                ]),
+            )
+            .to_string_lossy()
+            .to_string();
+
+        let context = get_message_context("claude".to_string(), session, "result".to_string(), 0)
+            .await
+            .expect("context should load");
+
+        assert_eq!(context.len(), 1);
+        assert_eq!(context[0].uuid, "call");
+        let content = context[0].content.as_ref().expect("content should be set");
+        assert_eq!(content[1]["type"], "tool_result");
+        assert_eq!(content[1]["tool_use_id"], "toolu_1");
+    }
+
+    #[tokio::test]
+    async fn paged_load_keeps_tool_pairs_split_by_the_page_start() {
+        use crate::test_utils::{create_jsonl_content, MessageBuilder, MockClaudeProject};
+
+        // The result is far more messages after its tool use than a page holds
+        let notes = (0..20).map(|i| {
+            MessageBuilder::assistant()
+                .with_uuid(&format!("note-{i}"))
+                .with_text_content("Still running")
+        });
+        let builders: Vec<_> = [
+            MessageBuilder::user()
+                .with_uuid("ask")
+                .with_text_content("Run the tests"),
+            MessageBuilder::assistant().with_uuid("call").with_content(serde_json::json!([
+                { "type": "tool_use", "id": "toolu_1", "name": "Bash", "input": { "command": "cargo test" } }
+            ])),
+        ]
+        .into_iter()
+        .chain(notes)
+        .chain([
+            MessageBuilder::user().with_uuid("result").with_content(serde_json::json!([
+                { "type": "tool_result", "tool_use_id": "toolu_1", "content": "ok" }
+            ])),
+            MessageBuilder::assistant()
+                .with_uuid("reply")
+                .with_text_content("All green"),
+        ])
+        .collect();
+        let mock = MockClaudeProject::new();
+        let session = mock
+            .add_session("project", "session", &create_jsonl_content(&builders))
+            .to_string_lossy()
+            .to_string();
+        let page = |offset| {
+            load_provider_messages(
+                "claude".to_string(),
+                session.clone(),
+                None,
+                None,
+                Some(offset),
+                Some(2),
+            )
+        };
+
+        let newest = page(0).await.expect("page should load");
+        let uuids: Vec<_> = newest.messages.iter().map(|m| m.uuid.as_str()).collect();
+        assert_eq!(uuids.first(), Some(&"call"));
+        assert_eq!(uuids.last(), Some(&"reply"));
+        assert!(!uuids.contains(&"result"));
+        let content = newest.messages[0].content.as_ref().expect("content");
+        assert_eq!(content[1]["type"], "tool_result");
+        let info = newest.page.expect("a paged load carries its page");
+        assert_eq!(info.total_count, 24);
+        assert_eq!(info.next_offset, 23);
+        assert!(info.has_more);
+
+        let oldest = page(info.next_offset).await.expect("page should load");
+        assert_eq!(oldest.messages.len(), 1);
+        assert_eq!(oldest.messages[0].uuid, "ask");
+        assert!(!oldest.page.expect("a paged load carries its page").has_more);
+
+        let full = load_provider_messages("claude".to_string(), session, None, None, None, None)
+            .await
+            .expect("session should load");
+        assert!(full.page.is_none());
+        assert_eq!(full.messages.len(), 23);
+    }
+
+    #[tokio::test]
+    async fn load_provider_messages_never_returns_empty_content_arrays() {
+        use crate::test_utils::{create_jsonl_content, MessageBuilder, MockClaudeProject};
+
+        let mock = MockClaudeProject::new();
+        let session = mock.add_session(
+            "project",
+            "session",
+            &create_jsonl_content(&[
+                MessageBuilder::user().with_text_content("Hello"),
+                MessageBuilder::assistant()
+                    .with_content(serde_json::json!([]))
+                    .with_usage(10, 0),
+                MessageBuilder::assistant()
+                    .with_content(serde_json::json!([{ "type": "text", "text": "Hi" }])),
+            ]),
+        );
+
+        let loaded = load_provider_messages(
+            "claude".to_string(),
+            session.to_string_lossy().to_string(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("messages should load");
+        assert_eq!(loaded.schema_version, MESSAGE_SCHEMA_VERSION);
+        let messages = loaded.messages;
+
+        assert_eq!(messages.len(), 3);
+        assert!(messages
+            .iter()
+            .all(|m| !matches!(&m.content, Some(Value::Array(items)) if items.is_empty())));
+        assert!(messages[1].content.is_none());
+        assert_eq!(
+            messages[1].usage.as_ref().and_then(|u| u.input_tokens),
+            Some(10)
+        );
+    }
+
+    #[test]
+    fn dedup_search_results_collapses_repeats_within_a_provider() {
+        let message = |provider: &str, uuid: &str, text: &str| {
+            let mut msg = make_message("user", serde_json::json!(text));
+            msg.provider = Some(provider.to_string());
+            msg.uuid = uuid.to_string();
+            msg
+        };
+        let ids = |messages: &[ClaudeMessage]| -> Vec<(String, String)> {
+            messages
+                .iter()
+                .map(|m| (m.provider.clone().unwrap_or_default(), m.uuid.clone()))
+                .collect()
+        };
+        let results = vec![
+            message("claude", "a", "Fix the build"),
+            message("claude", "a", "Fix the build"),
+            message("codex", "a", "fix  the build"),
+            message("claude", "b", "Something else"),
+        ];
+
+        let deduped = dedup_search_results(results.clone(), false);
+        assert_eq!(
+            ids(&deduped),
+            vec![
+                ("claude".to_string(), "a".to_string()),
+                ("codex".to_string(), "a".to_string()),
+                ("claude".to_string(), "b".to_string()),
+            ]
+        );
+
+        let collapsed = dedup_search_results(results, true);
+        assert_eq!(
+            ids(&collapsed),
+            vec![
+                ("claude".to_string(), "a".to_string()),
+                ("claude".to_string(), "b".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn merge_tool_result_into_previous_tool_use_message() {
         let tool_use = make_message(
@@ -482,4 +2304,314 @@ mod tests {
             Some("text")
         );
     }
+
+    #[test]
+    fn combine_tool_pair_blocks_merges_matching_pair() {
+        let merged = merge_tool_execution_messages(vec![
+            make_message(
+                "assistant",
+                serde_json::json!([
+                    { "type": "text", "text": "running" },
+                    { "type": "tool_use", "id": "call_1", "name": "Bash", "input": { "command": "pwd" } },
+                    { "type": "tool_use", "id": "call_2", "name": "Read", "input": { "file_path": "/a" } }
+                ]),
+            ),
+            make_message(
+                "user",
+                serde_json::json!([
+                    { "type": "tool_result", "tool_use_id": "call_1", "content": "/tmp", "is_error": true }
+                ]),
+            ),
+        ]);
+
+        let combined = combine_tool_pair_blocks(merged.into_iter().next().unwrap());
+        let arr = combined
+            .content
+            .as_ref()
+            .and_then(Value::as_array)
+            .expect("combined content should be array");
+
+        assert_eq!(arr.len(), 3);
+        assert_eq!(arr[0].get("type").and_then(Value::as_str), Some("text"));
+        assert_eq!(
+            arr[1],
+            serde_json::json!({
+                "type": "tool",
+                "id": "call_1",
+                "name": "Bash",
+                "input": { "command": "pwd" },
+                "result": "/tmp",
+                "is_error": true
+            })
+        );
+        // Unmatched tool_use is left untouched
+        assert_eq!(arr[2].get("type").and_then(Value::as_str), Some("tool_use"));
+    }
+
+    #[test]
+    fn annotate_resolved_paths_resolves_relative_read_path() {
+        let mut msg = make_message(
+            "assistant",
+            serde_json::json!([
+                { "type": "tool_use", "id": "call_1", "name": "Read", "input": { "file_path": "src/main.rs" } },
+                { "type": "tool_use", "id": "call_2", "name": "Read", "input": { "file_path": "/etc/hosts" } },
+                { "type": "tool_use", "id": "call_3", "name": "Bash", "input": { "command": "ls" } }
+            ]),
+        );
+
+        annotate_resolved_paths(&mut msg, Path::new("/work/project"));
+
+        let arr = msg.content.as_ref().and_then(Value::as_array).unwrap();
+        let expected = Path::new("/work/project")
+            .join("src/main.rs")
+            .to_string_lossy()
+            .to_string();
+        assert_eq!(
+            arr[0]["data"]["resolved_path"].as_str(),
+            Some(expected.as_str())
+        );
+        assert_eq!(arr[0]["input"]["file_path"].as_str(), Some("src/main.rs"));
+        assert!(arr[1].get("data").is_none());
+        assert!(arr[2].get("data").is_none());
+    }
+
+    #[test]
+    fn detect_primary_language_counts_paths_and_fences() {
+        let messages = vec![
+            make_message(
+                "assistant",
+                serde_json::json!([
+                    { "type": "tool_use", "id": "call_1", "name": "Read", "input": { "file_path": "/work/src/lib.rs" } },
+                    { "type": "tool_use", "id": "call_2", "name": "Edit", "input": { "file_path": "/work/src/main.rs" } },
+                    { "type": "tool_use", "id": "call_3", "name": "Read", "input": { "file_path": "/work/Cargo.toml" } }
+                ]),
+            ),
+            make_message(
+                "assistant",
+                serde_json::json!([
+                    { "type": "text", "text": "Run this:\n```bash\ncargo test\n```\nand compare with\n```python\nprint(1)\n```" },
+                    { "type": "tool_use", "id": "call_4", "name": "Write", "input": { "file_path": "/work/src/parser.rs" } }
+                ]),
+            ),
+        ];
+
+        assert_eq!(detect_primary_language(&messages), Some("rust".to_string()));
+        assert_eq!(
+            detect_primary_language(&[make_message("user", serde_json::json!("hello"))]),
+            None
+        );
+    }
+
+    #[test]
+    fn annotate_files_touched_dedups_and_normalizes_paths() {
+        let mut msg = make_message(
+            "assistant",
+            serde_json::json!([
+                { "type": "text", "text": "Updating both files" },
+                { "type": "tool_use", "id": "call_1", "name": "Edit", "input": { "file_path": "/work/src/./lib.rs" } },
+                { "type": "tool_use", "id": "call_2", "name": "Edit", "input": { "file_path": "/work/src/util/../main.rs" } },
+                { "type": "tool_use", "id": "call_3", "name": "Read", "input": { "file_path": "/work/src/lib.rs" } }
+            ]),
+        );
+
+        annotate_files_touched(&mut msg);
+
+        let expected: Vec<String> = ["/work/src/lib.rs", "/work/src/main.rs"]
+            .iter()
+            .map(|p| Path::new(p).to_string_lossy().to_string())
+            .collect();
+        assert_eq!(
+            msg.data.as_ref().map(|d| d["files_touched"].clone()),
+            Some(serde_json::json!(expected))
+        );
+    }
+
+    #[test]
+    fn scan_stamp_only_reads_entries_down_to_depth() {
+        let tmp = tempfile::TempDir::new().expect("temp dir should be created");
+        let day = tmp.path().join("2025").join("07").join("01");
+        std::fs::create_dir_all(&day).expect("dirs should be created");
+        let rollout = day.join("rollout.jsonl");
+        std::fs::write(&rollout, "{}").expect("rollout should be written");
+        let later = SystemTime::now() + Duration::from_secs(3600);
+        std::fs::File::options()
+            .write(true)
+            .open(&rollout)
+            .and_then(|f| f.set_modified(later))
+            .expect("mtime should be set");
+        let roots = vec![tmp.path().to_path_buf()];
+
+        let shallow = scan_stamp(&roots, 3).expect("roots should be stamped");
+        assert!(shallow < later);
+        assert_eq!(scan_stamp(&roots, 4), Some(later));
+        assert_eq!(scan_stamp(&[tmp.path().join("missing")], 3), None);
+    }
+
+    #[test]
+    fn search_cache_serves_identical_search_within_ttl() {
+        let key = SearchCacheKey {
+            claude_path: None,
+            query: "parser".to_string(),
+            providers: vec!["codex".to_string()],
+            filters: "{}".to_string(),
+            limit: 100,
+            regex: false,
+            roles: None,
+            tool: None,
+            collapse_similar: false,
+        };
+        let stored_at = Instant::now();
+        let mut cache = SearchCache::new();
+        cache.put(
+            key.clone(),
+            vec![make_message("user", serde_json::json!("parser"))],
+            stored_at,
+        );
+
+        let hit = cache.get(&key, stored_at + Duration::from_secs(1));
+        assert_eq!(hit.map(|r| r.len()), Some(1));
+
+        let other_query = SearchCacheKey {
+            query: "lexer".to_string(),
+            ..key.clone()
+        };
+        assert!(cache.get(&other_query, stored_at).is_none());
+        assert!(cache.get(&key, stored_at + SEARCH_CACHE_TTL).is_none());
+
+        cache.clear();
+        assert!(cache.get(&key, stored_at).is_none());
+    }
+
+    #[test]
+    fn backfill_provider_only_fills_missing_tags() {
+        let mut tagged = make_message("user", serde_json::json!("hi"));
+        tagged.provider = Some("claude".to_string());
+        let mut untagged = make_message("user", serde_json::json!("hi"));
+        untagged.provider = None;
+        let mut messages = vec![tagged, untagged];
+
+        backfill_provider(&mut messages, "opencode");
+
+        assert_eq!(messages[0].provider.as_deref(), Some("claude"));
+        assert_eq!(messages[1].provider.as_deref(), Some("opencode"));
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn every_command_result_carries_its_provider() {
+        use serde_json::json;
+
+        let tmp = tempfile::TempDir::new().expect("temp dir should be created");
+        let home = tmp.path().join("opencode-home");
+        let storage = home.join("storage");
+        let _guard = crate::test_utils::EnvVarGuard::set("OPENCODE_HOME", &home);
+        let write = |path: PathBuf, value: Value| {
+            std::fs::create_dir_all(path.parent().expect("fixture path has a parent"))
+                .expect("fixture dir should be created");
+            std::fs::write(path, value.to_string()).expect("fixture should be written");
+        };
+        write(
+            storage.join("project").join("proj.json"),
+            json!({ "id": "proj", "worktree": tmp.path().join("work") }),
+        );
+        write(
+            storage.join("session").join("proj").join("ses_1.json"),
+            json!({ "id": "ses_1", "time": { "created": 1_704_067_200_000_u64 } }),
+        );
+        for (id, role, created) in [
+            ("msg_1", "user", 1_704_067_200_000_u64),
+            ("msg_2", "assistant", 1_704_067_201_000),
+        ] {
+            write(
+                storage
+                    .join("message")
+                    .join("ses_1")
+                    .join(format!("{id}.json")),
+                json!({ "id": id, "role": role, "time": { "created": created } }),
+            );
+            write(
+                storage.join("part").join(id).join("prt_1.json"),
+                json!({ "type": "text", "text": format!("Rename the crate ({id})") }),
+            );
+        }
+        let opencode = || "opencode".to_string();
+        let assert_tagged = |providers: Vec<Option<&str>>| {
+            assert!(!providers.is_empty(), "results should not be empty");
+            assert!(
+                providers.iter().all(|p| *p == Some("opencode")),
+                "every result should be tagged: {providers:?}"
+            );
+        };
+
+        let projects = scan_all_projects(None, Some(vec![opencode()]), None, None, Some(true))
+            .await
+            .expect("scan should succeed");
+        assert_tagged(projects.iter().map(|p| p.provider.as_deref()).collect());
+
+        let sessions = load_provider_sessions(opencode(), projects[0].path.clone(), None)
+            .await
+            .expect("sessions should load");
+        assert_tagged(sessions.iter().map(|s| s.provider.as_deref()).collect());
+        let session_path = sessions[0].file_path.clone();
+
+        let loaded =
+            load_provider_messages(opencode(), session_path.clone(), None, None, None, None)
+                .await
+                .expect("messages should load");
+        assert_tagged(
+            loaded
+                .messages
+                .iter()
+                .map(|m| m.provider.as_deref())
+                .collect(),
+        );
+
+        let page = load_provider_messages(
+            opencode(),
+            session_path.clone(),
+            None,
+            None,
+            Some(0),
+            Some(1),
+        )
+        .await
+        .expect("page should load");
+        assert_tagged(
+            page.messages
+                .iter()
+                .map(|m| m.provider.as_deref())
+                .collect(),
+        );
+
+        let mut streamed = Vec::new();
+        let stream = MessageStream::new("opencode", false, None, |event| {
+            if let MessageStreamEvent::Messages { messages } = event {
+                streamed.extend(messages);
+            }
+            Ok(())
+        });
+        stream_messages("opencode", &session_path, 1, stream).expect("stream should finish");
+        assert_tagged(streamed.iter().map(|m| m.provider.as_deref()).collect());
+
+        let hits = search_all_providers(
+            None,
+            "rename".to_string(),
+            Some(vec![opencode()]),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("search should succeed");
+        assert_eq!(hits.messages.len(), 2);
+        assert_tagged(
+            hits.messages
+                .iter()
+                .map(|m| m.provider.as_deref())
+                .collect(),
+        );
+    }
 }
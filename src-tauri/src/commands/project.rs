@@ -4,7 +4,7 @@ use crate::utils::{
 };
 use chrono::{DateTime, Utc};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use walkdir::WalkDir;
 
@@ -180,6 +180,7 @@ pub async fn scan_projects(claude_path: String) -> Result<Vec<ClaudeProject>, St
         projects.push(ClaudeProject {
             name: project_name,
             path: project_path,
+            path_exists: Path::new(&actual_path).is_dir(),
             actual_path,
             session_count,
             message_count,
@@ -0,0 +1,93 @@
+//! Tauri commands for recent search history
+//!
+//! Recent queries are stored in ~/.claude-history-viewer/search-history.json
+//! as a most-recent-first list of distinct strings.
+
+use crate::utils::{read_json_or_recover, write_json_atomic};
+use std::path::{Path, PathBuf};
+
+/// Maximum number of distinct queries kept on disk
+const MAX_STORED_SEARCHES: usize = 50;
+
+/// Default number of queries returned by `recent_searches`
+const DEFAULT_RECENT_LIMIT: usize = 10;
+
+/// Get the search history file path (~/.claude-history-viewer/search-history.json)
+fn get_search_history_path() -> Result<PathBuf, String> {
+    let home = dirs::home_dir().ok_or("Could not find home directory")?;
+    Ok(home
+        .join(".claude-history-viewer")
+        .join("search-history.json"))
+}
+
+/// Read stored queries, treating a missing or corrupt file as empty history
+fn read_history(path: &Path) -> Result<Vec<String>, String> {
+    read_json_or_recover(path, Vec::new)
+}
+
+fn write_history(path: &Path, queries: &[String]) -> Result<(), String> {
+    write_json_atomic(path, queries)
+}
+
+/// Move `query` to the front of the history, dropping duplicates and
+/// trimming to `MAX_STORED_SEARCHES`. Blank queries are ignored.
+fn record_search_at(path: &Path, query: &str) -> Result<(), String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(());
+    }
+
+    let mut queries = read_history(path)?;
+    queries.retain(|q| q != query);
+    queries.insert(0, query.to_string());
+    queries.truncate(MAX_STORED_SEARCHES);
+
+    write_history(path, &queries)
+}
+
+fn recent_searches_at(path: &Path, limit: usize) -> Result<Vec<String>, String> {
+    let mut queries = read_history(path)?;
+    queries.truncate(limit);
+    Ok(queries)
+}
+
+/// Remember a search query (most-recent first, deduplicated)
+#[tauri::command]
+pub async fn record_search(query: String) -> Result<(), String> {
+    let path = get_search_history_path()?;
+    tauri::async_runtime::spawn_blocking(move || record_search_at(&path, &query))
+        .await
+        .map_err(|e| format!("Task join error: {e}"))?
+}
+
+/// Get the most recent distinct search queries
+#[tauri::command]
+pub async fn recent_searches(limit: Option<usize>) -> Result<Vec<String>, String> {
+    let path = get_search_history_path()?;
+    let limit = limit.unwrap_or(DEFAULT_RECENT_LIMIT);
+    tauri::async_runtime::spawn_blocking(move || recent_searches_at(&path, limit))
+        .await
+        .map_err(|e| format!("Task join error: {e}"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_and_read_recent_searches() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("search-history.json");
+
+        assert!(recent_searches_at(&path, 10).unwrap().is_empty());
+
+        record_search_at(&path, "foo").unwrap();
+        record_search_at(&path, "bar").unwrap();
+        record_search_at(&path, "  ").unwrap();
+        record_search_at(&path, "foo").unwrap();
+
+        assert_eq!(recent_searches_at(&path, 10).unwrap(), vec!["foo", "bar"]);
+        assert_eq!(recent_searches_at(&path, 1).unwrap(), vec!["foo"]);
+    }
+}
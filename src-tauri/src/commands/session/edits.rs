@@ -520,7 +520,12 @@ fn get_provider_recent_edits(
     for session in sessions {
         let messages = match provider {
             EditsProvider::Codex => providers::codex::load_messages(&session.file_path)?,
-            EditsProvider::OpenCode => providers::opencode::load_messages(&session.file_path)?,
+            // A session with broken storage shouldn't hide the project's other edits.
+            EditsProvider::OpenCode => providers::opencode::load_messages(&session.file_path)
+                .unwrap_or_else(|e| {
+                    log::warn!("Skipping OpenCode session in recent edits: {e}");
+                    Vec::new()
+                }),
             EditsProvider::Claude => Vec::new(),
         };
         all_edits.extend(collect_provider_recent_edits_from_messages(
@@ -1,6 +1,8 @@
 //! Session loading functions
 
-use crate::models::{ClaudeMessage, ClaudeSession, MessagePage, RawLogEntry};
+use crate::models::{
+    ClaudeMessage, ClaudeSession, MessagePage, RawLogEntry, MESSAGE_SCHEMA_VERSION,
+};
 use crate::utils::{extract_project_name, find_line_ranges, find_line_starts};
 use chrono::{DateTime, Utc};
 use memmap2::Mmap;
@@ -504,6 +506,7 @@ fn extract_session_metadata_internal(
             has_errors,
             summary: final_summary,
             provider: None,
+            data: None,
         },
         sidechain_count,
         final_byte_offset: file_size,
@@ -1000,6 +1003,9 @@ fn parse_line_to_message(
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            match_locations: None,
+            session_path: None,
+            project_path: None,
         });
     }
 
@@ -1061,6 +1067,9 @@ fn parse_line_to_message(
         compact_metadata: log_entry.compact_metadata,
         microcompact_metadata: log_entry.microcompact_metadata,
         provider: None,
+        match_locations: None,
+        session_path: None,
+        project_path: None,
     })
 }
 
@@ -1130,6 +1139,9 @@ fn parse_line_simd(
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            match_locations: None,
+            session_path: None,
+            project_path: None,
         });
     }
 
@@ -1204,6 +1216,9 @@ fn parse_line_simd(
         compact_metadata: log_entry.compact_metadata,
         microcompact_metadata: log_entry.microcompact_metadata,
         provider: None,
+        match_locations: None,
+        session_path: None,
+        project_path: None,
     })
 }
 
@@ -1262,6 +1277,37 @@ pub async fn load_session_messages(session_path: String) -> Result<Vec<ClaudeMes
     Ok(messages)
 }
 
+/// The messages of `load_session_messages`, read line by line and handed to
+/// `on_batch` in batches of at most `batch_size`, so the first batch can be
+/// shown before the rest of the file is read. Stops at the first error.
+pub(crate) fn load_session_message_batches(
+    session_path: &str,
+    batch_size: usize,
+    on_batch: &mut dyn FnMut(Vec<ClaudeMessage>) -> Result<(), String>,
+) -> Result<(), String> {
+    let file =
+        fs::File::open(session_path).map_err(|e| format!("Failed to open session file: {e}"))?;
+    let batch_size = batch_size.max(1);
+    let mut batch = Vec::with_capacity(batch_size);
+
+    for (line_num, line) in BufReader::new(file).split(b'\n').enumerate() {
+        let mut line = line.map_err(|e| format!("Failed to read session file: {e}"))?;
+        let Some(msg) = parse_line_simd(line_num, &mut line, false)
+            .filter(|msg| !is_system_message_type(&msg.message_type))
+        else {
+            continue;
+        };
+        batch.push(msg);
+        if batch.len() >= batch_size {
+            on_batch(std::mem::take(&mut batch))?;
+        }
+    }
+    if !batch.is_empty() {
+        on_batch(batch)?;
+    }
+    Ok(())
+}
+
 /// Fast line classifier for simd-json (mutable slice)
 fn classify_line_fast(line: &[u8], exclude_sidechain: bool) -> bool {
     if line
@@ -1333,6 +1379,7 @@ pub async fn load_session_messages_paginated(
     // Chat-style pagination: offset=0 means newest messages (at the end)
     if total_count == 0 {
         return Ok(MessagePage {
+            schema_version: MESSAGE_SCHEMA_VERSION,
             messages: vec![],
             total_count: 0,
             has_more: false,
@@ -1379,6 +1426,7 @@ pub async fn load_session_messages_paginated(
     }
 
     Ok(MessagePage {
+        schema_version: MESSAGE_SCHEMA_VERSION,
         messages,
         total_count,
         has_more,
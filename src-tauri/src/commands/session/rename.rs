@@ -436,7 +436,10 @@ pub async fn rename_opencode_session_title(
 ) -> Result<NativeRenameResult, String> {
     let (project_id, session_id) = parse_opencode_session_path(&session_path)?;
 
-    let base_path = crate::providers::opencode::get_base_path().ok_or_else(|| {
+    let base_path = crate::providers::opencode::find_base_path_containing(
+        &Path::new("session").join(&project_id),
+    )
+    .ok_or_else(|| {
         RenameError::FileNotFound("OpenCode base path not found".to_string()).to_string()
     })?;
     let session_root = Path::new(&base_path).join("storage").join("session");
@@ -1,8 +1,8 @@
 //! Session search functions
 
 use crate::models::{ClaudeMessage, RawLogEntry};
-use crate::utils::find_line_ranges;
-use chrono::{DateTime, Utc};
+use crate::utils::{find_line_ranges, SearchPattern, SearchScope};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
 use memmap2::Mmap;
 use rayon::prelude::*;
 use std::fs;
@@ -43,9 +43,12 @@ fn extract_project_name(file_path: &PathBuf) -> Option<String> {
 ///
 /// Uses a reusable buffer to avoid repeated heap allocations during JSON parsing.
 #[allow(unsafe_code)] // Required for mmap performance optimization
-fn search_in_file(file_path: &PathBuf, query: &str) -> Vec<ClaudeMessage> {
-    let query_lower = query.to_lowercase();
+fn search_in_file(file_path: &PathBuf, pattern: &SearchPattern) -> Vec<ClaudeMessage> {
     let project_name = extract_project_name(file_path);
+    let session_path = file_path.to_string_lossy().to_string();
+    let project_path = file_path
+        .parent()
+        .map(|dir| dir.to_string_lossy().to_string());
 
     let file = match fs::File::open(file_path) {
         Ok(f) => f,
@@ -88,11 +91,15 @@ fn search_in_file(file_path: &PathBuf, query: &str) -> Vec<ClaudeMessage> {
         };
 
         // Use recursive search to avoid JSON serialization overhead
-        let matches = match &message_content.content {
-            serde_json::Value::String(s) => s.to_lowercase().contains(&query_lower),
-            serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
-                search_in_value(&message_content.content, &query_lower)
+        let matches = match (pattern, &message_content.content) {
+            (SearchPattern::Regex(_), content) => pattern.matches_content(content),
+            (SearchPattern::Plain(query_lower), serde_json::Value::String(s)) => {
+                s.to_lowercase().contains(query_lower)
             }
+            (
+                SearchPattern::Plain(query_lower),
+                content @ (serde_json::Value::Array(_) | serde_json::Value::Object(_)),
+            ) => search_in_value(content, query_lower),
             _ => false,
         };
 
@@ -139,6 +146,9 @@ fn search_in_file(file_path: &PathBuf, query: &str) -> Vec<ClaudeMessage> {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            match_locations: None,
+            session_path: Some(session_path.clone()),
+            project_path: project_path.clone(),
         };
         results.push(claude_message);
     }
@@ -217,11 +227,62 @@ fn filter_value_to_string(value: &serde_json::Value) -> String {
         .unwrap_or_else(|| value.to_string())
 }
 
+/// Half-open UTC range `[start, end)`
+type UtcRange = (DateTime<Utc>, DateTime<Utc>);
+
+/// Resolve midnight of `date` in the given timezone to UTC.
+///
+/// Falls back to the first representable instant after midnight when a DST
+/// transition skips local midnight.
+fn start_of_day_utc<Tz: TimeZone>(tz: &Tz, date: NaiveDate) -> Option<DateTime<Utc>> {
+    let midnight: NaiveDateTime = date.and_hms_opt(0, 0, 0)?;
+    (0..=2)
+        .find_map(|hour| {
+            tz.from_local_datetime(&(midnight + chrono::Duration::hours(hour)))
+                .earliest()
+        })
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Resolve an `onDate` filter (`YYYY-MM-DD`) to a half-open UTC range
+/// `[start_of_day, start_of_next_day)`.
+///
+/// The day is interpreted in `timezone` (an IANA name such as `Asia/Tokyo`,
+/// as for stats) when given, otherwise in the system local timezone, so days
+/// with a DST change get their real length.
+fn resolve_on_date_range(date: &str, timezone: Option<&str>) -> Result<UtcRange, String> {
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid onDate filter: {date} (expected YYYY-MM-DD)"))?;
+    let next_day = day
+        .succ_opt()
+        .ok_or_else(|| format!("Invalid onDate filter: {date} is out of range"))?;
+
+    let range = match crate::commands::stats::parse_stats_timezone(timezone)? {
+        Some(tz) => start_of_day_utc(&tz, day).zip(start_of_day_utc(&tz, next_day)),
+        None => start_of_day_utc(&Local, day).zip(start_of_day_utc(&Local, next_day)),
+    };
+
+    range.ok_or_else(|| format!("Invalid onDate filter: {date} has no local midnight"))
+}
+
+fn on_date_range_from_filters(filters: &serde_json::Value) -> Result<Option<UtcRange>, String> {
+    let Some(obj) = filters.as_object() else {
+        return Ok(None);
+    };
+    let Some(on_date) = obj.get("onDate").and_then(serde_json::Value::as_str) else {
+        return Ok(None);
+    };
+    let timezone = obj.get("timezone").and_then(serde_json::Value::as_str);
+    resolve_on_date_range(on_date, timezone).map(Some)
+}
+
 pub(crate) fn validate_search_filters(filters: &serde_json::Value) -> Result<(), String> {
     let Some(obj) = filters.as_object() else {
         return Ok(());
     };
 
+    on_date_range_from_filters(filters)?;
+
     let Some(date_range) = obj.get("dateRange").and_then(serde_json::Value::as_array) else {
         return Ok(());
     };
@@ -334,9 +395,21 @@ pub fn apply_search_filters(
     messages: Vec<ClaudeMessage>,
     filters: &serde_json::Value,
 ) -> Vec<ClaudeMessage> {
+    // Filters are validated up front, so an invalid `onDate` never reaches here.
+    let on_date_range = on_date_range_from_filters(filters).ok().flatten();
+
     messages
         .into_iter()
         .filter(|message| matches_filters(message, filters))
+        .filter(|message| {
+            let Some((start_at, end_at)) = on_date_range else {
+                return true;
+            };
+            DateTime::parse_from_rfc3339(&message.timestamp)
+                .ok()
+                .map(|dt| dt.with_timezone(&Utc))
+                .is_some_and(|ts| ts >= start_at && ts < end_at)
+        })
         .collect()
 }
 
@@ -346,6 +419,24 @@ pub async fn search_messages(
     query: String,
     filters: serde_json::Value,
     limit: Option<usize>,
+) -> Result<Vec<ClaudeMessage>, String> {
+    search_messages_matching(
+        claude_path,
+        &SearchPattern::plain(&query),
+        &SearchScope::default(),
+        filters,
+        limit,
+    )
+}
+
+/// [`search_messages`] with an explicit [`SearchPattern`], e.g. a regex, keeping only
+/// messages in `scope`
+pub fn search_messages_matching(
+    claude_path: String,
+    pattern: &SearchPattern,
+    scope: &SearchScope,
+    filters: serde_json::Value,
+    limit: Option<usize>,
 ) -> Result<Vec<ClaudeMessage>, String> {
     #[cfg(debug_assertions)]
     let start_time = std::time::Instant::now();
@@ -372,7 +463,8 @@ pub async fn search_messages(
     // 2. Parallel search using rayon
     let mut all_messages: Vec<ClaudeMessage> = file_paths
         .par_iter()
-        .flat_map(|path| search_in_file(path, &query))
+        .flat_map(|path| search_in_file(path, pattern))
+        .filter(|message| scope.includes(message))
         .collect();
 
     all_messages = apply_search_filters(all_messages, &filters);
@@ -539,4 +631,72 @@ mod tests {
             .unwrap_or_default()
             .contains("Invalid dateRange start"));
     }
+
+    #[tokio::test]
+    async fn test_search_messages_on_date_uses_requested_timezone() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_dir = temp_dir.path().join("projects").join("test-project");
+        std::fs::create_dir_all(&project_dir).unwrap();
+
+        // 2025-03-14 in Asia/Tokyo (+09:00) spans [2025-03-13T15:00Z, 2025-03-14T15:00Z)
+        let lines = [
+            ("before", "2025-03-13T14:59:59Z"),
+            ("start", "2025-03-13T15:00:00Z"),
+            ("end", "2025-03-14T14:59:59Z"),
+            ("after", "2025-03-14T15:00:00Z"),
+        ]
+        .iter()
+        .map(|(uuid, ts)| {
+            format!(
+                r#"{{"uuid":"{uuid}","sessionId":"s1","timestamp":"{ts}","type":"user","message":{{"role":"user","content":"daily note"}}}}"#
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+        std::fs::write(project_dir.join("test.jsonl"), format!("{lines}\n")).unwrap();
+
+        let messages = search_messages(
+            temp_dir.path().to_string_lossy().to_string(),
+            "daily".to_string(),
+            serde_json::json!({ "onDate": "2025-03-14", "timezone": "Asia/Tokyo" }),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let mut uuids: Vec<&str> = messages.iter().map(|m| m.uuid.as_str()).collect();
+        uuids.sort_unstable();
+        assert_eq!(uuids, vec!["end", "start"]);
+    }
+
+    #[test]
+    fn test_resolve_on_date_range_follows_dst_changes() {
+        let utc = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+
+        // Clocks spring forward in New York on 2025-03-09: a 23-hour day
+        let (start, end) = resolve_on_date_range("2025-03-09", Some("America/New_York")).unwrap();
+        assert_eq!(start, utc("2025-03-09T05:00:00Z"));
+        assert_eq!(end, utc("2025-03-10T04:00:00Z"));
+
+        let err = resolve_on_date_range("2025-03-09", Some("+09:00")).unwrap_err();
+        assert!(err.contains("Invalid timezone"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_search_messages_invalid_on_date_returns_error() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let result = search_messages(
+            temp_dir.path().to_string_lossy().to_string(),
+            "test".to_string(),
+            serde_json::json!({ "onDate": "14/03/2025" }),
+            None,
+        )
+        .await;
+
+        assert!(result
+            .err()
+            .unwrap_or_default()
+            .contains("Invalid onDate filter"));
+    }
 }
@@ -4,12 +4,11 @@
 //! user settings presets stored in ~/.claude-history-viewer/presets/
 
 use crate::models::UserSettings;
+use crate::utils::write_json_atomic;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::io::Write;
 use std::path::PathBuf;
-use tempfile::Builder;
 use uuid::Uuid;
 
 /// Data structure for a settings preset
@@ -134,32 +133,7 @@ pub async fn save_preset(input: PresetInput) -> Result<PresetData, String> {
         ensure_presets_folder()?;
         let path = get_preset_path(&preset_clone.id)?;
 
-        // Write to temp file first (atomic write pattern)
-        let content = serde_json::to_string_pretty(&preset_clone)
-            .map_err(|e| format!("Failed to serialize preset: {e}"))?;
-
-        let dir = path
-            .parent()
-            .ok_or_else(|| "Failed to get parent directory".to_string())?;
-        let mut tmp_file = Builder::new()
-            .prefix(".preset-")
-            .suffix(".tmp")
-            .tempfile_in(dir)
-            .map_err(|e| format!("Failed to create temp file: {e}"))?;
-
-        tmp_file
-            .write_all(content.as_bytes())
-            .map_err(|e| format!("Failed to write temp file: {e}"))?;
-        tmp_file
-            .as_file()
-            .sync_all()
-            .map_err(|e| format!("Failed to sync temp file: {e}"))?;
-
-        let temp_path = tmp_file.into_temp_path();
-        // Cross-platform atomic rename
-        super::fs_utils::atomic_rename(&temp_path, &path)?;
-
-        Ok::<(), String>(())
+        write_json_atomic(&path, &preset_clone)
     })
     .await
     .map_err(|e| format!("Task join error: {e}"))??;
@@ -2,13 +2,14 @@ use crate::commands::session::load_session_messages;
 #[cfg(test)]
 use crate::models::MessageContent;
 use crate::models::{
-    ActivityHeatmap, ClaudeMessage, DailyStats, GlobalStatsSummary, ModelStats, ProjectRanking,
-    ProjectStatsSummary, RawLogEntry, SessionComparison, SessionTokenStats, TokenDistribution,
-    TokenUsage, ToolUsageStats,
+    ActivityHeatmap, ClaudeMessage, ClaudeSession, DailyStats, GlobalStatsSummary, ModelStats,
+    ModelTokenTotals, ProjectRanking, ProjectStatsSummary, RawLogEntry, SessionComparison,
+    SessionTokenStats, TokenDistribution, TokenTotals, TokenUsage, ToolUsageStats,
 };
 use crate::providers;
 use crate::utils::find_line_ranges;
-use chrono::{DateTime, Datelike, Timelike, Utc};
+use chrono::{DateTime, Datelike, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
 use memmap2::Mmap;
 use rayon::prelude::*;
 use serde::Deserialize;
@@ -273,7 +274,10 @@ struct SessionFileStats {
 /// Process a single session file using lightweight deserialization for global stats.
 /// Only parses fields needed for stats (timestamp, usage, model, tool names).
 #[allow(unsafe_code)] // Required for mmap performance optimization
-fn process_session_file_for_global_stats(session_path: &PathBuf) -> Option<SessionFileStats> {
+fn process_session_file_for_global_stats(
+    session_path: &PathBuf,
+    tz: Option<Tz>,
+) -> Option<SessionFileStats> {
     let file = fs::File::open(session_path).ok()?;
 
     // SAFETY: We're only reading the file, and the file handle is kept open
@@ -343,8 +347,7 @@ fn process_session_file_for_global_stats(session_path: &PathBuf) -> Option<Sessi
             stats.last_message = Some(timestamp);
         }
 
-        let hour = timestamp.hour() as u8;
-        let day = timestamp.weekday().num_days_from_sunday() as u8;
+        let (hour, day, date) = time_buckets(timestamp, tz);
         let usage = extract_token_usage_from_global_entry(&entry);
         let input_tokens = u64::from(usage.input_tokens.unwrap_or(0));
         let output_tokens = u64::from(usage.output_tokens.unwrap_or(0));
@@ -360,7 +363,6 @@ fn process_session_file_for_global_stats(session_path: &PathBuf) -> Option<Sessi
         activity_entry.1 += tokens;
 
         // Daily stats
-        let date = timestamp.format("%Y-%m-%d").to_string();
         let daily_entry = stats
             .daily_stats
             .entry(date.clone())
@@ -442,6 +444,7 @@ fn calculate_session_duration(
 fn build_global_session_file_stats_from_messages(
     project_name: String,
     messages: &[ClaudeMessage],
+    tz: Option<Tz>,
 ) -> Option<SessionFileStats> {
     if messages.is_empty() {
         return None;
@@ -468,8 +471,7 @@ fn build_global_session_file_stats_from_messages(
                 stats.last_message = Some(timestamp);
             }
 
-            let hour = timestamp.hour() as u8;
-            let day = timestamp.weekday().num_days_from_sunday() as u8;
+            let (hour, day, date) = time_buckets(timestamp, tz);
             let usage = extract_token_usage(message);
             let tokens = u64::from(usage.input_tokens.unwrap_or(0))
                 + u64::from(usage.output_tokens.unwrap_or(0))
@@ -488,7 +490,6 @@ fn build_global_session_file_stats_from_messages(
             activity_entry.1 += tokens;
 
             // Daily stats
-            let date = timestamp.format("%Y-%m-%d").to_string();
             let daily_entry = stats
                 .daily_stats
                 .entry(date.clone())
@@ -560,6 +561,7 @@ fn build_global_session_file_stats_from_messages(
 
 fn collect_provider_global_file_stats(
     provider: StatsProvider,
+    tz: Option<Tz>,
 ) -> (Vec<SessionFileStats>, HashSet<String>) {
     let mut project_keys = HashSet::new();
 
@@ -605,7 +607,7 @@ fn collect_provider_global_file_stats(
             }
             .unwrap_or_default();
 
-            build_global_session_file_stats_from_messages(project_name.clone(), &messages)
+            build_global_session_file_stats_from_messages(project_name.clone(), &messages, tz)
         })
         .collect();
 
@@ -629,6 +631,7 @@ struct ProjectSessionFileStats {
 #[allow(unsafe_code)] // Required for mmap performance optimization
 fn process_session_file_for_project_stats(
     session_path: &PathBuf,
+    tz: Option<Tz>,
 ) -> Option<ProjectSessionFileStats> {
     let file = fs::File::open(session_path).ok()?;
 
@@ -654,8 +657,7 @@ fn process_session_file_for_project_stats(
                     let timestamp = timestamp.with_timezone(&Utc);
                     session_timestamps.push(timestamp);
 
-                    let hour = timestamp.hour() as u8;
-                    let day = timestamp.weekday().num_days_from_sunday() as u8;
+                    let (hour, day, date) = time_buckets(timestamp, tz);
                     let usage = extract_token_usage(&message);
                     let tokens = usage.input_tokens.unwrap_or(0)
                         + usage.output_tokens.unwrap_or(0)
@@ -666,7 +668,6 @@ fn process_session_file_for_project_stats(
                     activity_entry.0 += 1;
                     activity_entry.1 += u64::from(tokens);
 
-                    let date = timestamp.format("%Y-%m-%d").to_string();
                     stats.session_dates.insert(date.clone());
 
                     let daily_entry =
@@ -882,6 +883,34 @@ fn parse_date_limit(date_str: Option<String>, label: &str) -> Option<DateTime<Ut
     }
 }
 
+/// Parse an optional IANA timezone name used for stats bucketing
+pub(crate) fn parse_stats_timezone(timezone: Option<&str>) -> Result<Option<Tz>, String> {
+    timezone
+        .map(|name| {
+            name.parse::<Tz>().map_err(|_| {
+                format!("Invalid timezone: {name} (expected an IANA name such as Asia/Tokyo)")
+            })
+        })
+        .transpose()
+}
+
+/// Hour, weekday (0 = Sunday) and `YYYY-MM-DD` date of `timestamp` in `tz`,
+/// or in UTC when no zone is given. Range filtering stays in UTC.
+fn time_buckets(timestamp: DateTime<Utc>, tz: Option<Tz>) -> (u8, u8, String) {
+    fn buckets<Z: TimeZone>(dt: &DateTime<Z>) -> (u8, u8, String) {
+        (
+            dt.hour() as u8,
+            dt.weekday().num_days_from_sunday() as u8,
+            dt.date_naive().format("%Y-%m-%d").to_string(),
+        )
+    }
+
+    match tz {
+        Some(tz) => buckets(&timestamp.with_timezone(&tz)),
+        None => buckets(&timestamp),
+    }
+}
+
 fn parse_timestamp_utc(timestamp: &str) -> Option<DateTime<Utc>> {
     DateTime::parse_from_rfc3339(timestamp)
         .map(|dt| dt.with_timezone(&Utc))
@@ -1018,7 +1047,13 @@ fn load_provider_messages_for_stats(
 ) -> Result<Vec<ClaudeMessage>, String> {
     match provider {
         StatsProvider::Codex => providers::codex::load_messages(&session.file_path),
-        StatsProvider::OpenCode => providers::opencode::load_messages(&session.file_path),
+        // A session with broken storage shouldn't fail the whole project's stats.
+        StatsProvider::OpenCode => {
+            providers::opencode::load_messages(&session.file_path).or_else(|e| {
+                log::warn!("Skipping OpenCode session in stats: {e}");
+                Ok(Vec::new())
+            })
+        }
         StatsProvider::Claude => {
             Err("Claude messages are handled by legacy stats path".to_string())
         }
@@ -1154,6 +1189,7 @@ fn get_provider_project_stats_summary(
     project_path: &str,
     start_date: Option<String>,
     end_date: Option<String>,
+    tz: Option<Tz>,
 ) -> Result<ProjectStatsSummary, String> {
     let project_name = resolve_provider_project_name(provider, project_path);
     let sessions = load_provider_sessions_for_stats(provider, project_path)?;
@@ -1214,9 +1250,7 @@ fn get_provider_project_stats_summary(
             summary.token_distribution.cache_read += cache_read_tokens;
 
             if let Some(timestamp) = parse_timestamp_utc(&message.timestamp) {
-                let hour = timestamp.hour() as u8;
-                let day = timestamp.weekday().num_days_from_sunday() as u8;
-                let date = timestamp.format("%Y-%m-%d").to_string();
+                let (hour, day, date) = time_buckets(timestamp, tz);
                 session_dates.insert(date.clone());
 
                 let activity_entry = activity_map.entry((hour, day)).or_insert((0, 0));
@@ -1744,15 +1778,25 @@ pub async fn get_project_token_stats(
     })
 }
 
+/// Summarize a project's stats. `timezone` (IANA name, e.g. `Asia/Tokyo`)
+/// controls the hour/day/date buckets; it defaults to UTC.
 #[tauri::command]
 pub async fn get_project_stats_summary(
     project_path: String,
     start_date: Option<String>,
     end_date: Option<String>,
+    timezone: Option<String>,
 ) -> Result<ProjectStatsSummary, String> {
+    let tz = parse_stats_timezone(timezone.as_deref())?;
     let provider = detect_project_provider(&project_path);
     if provider != StatsProvider::Claude {
-        return get_provider_project_stats_summary(provider, &project_path, start_date, end_date);
+        return get_provider_project_stats_summary(
+            provider,
+            &project_path,
+            start_date,
+            end_date,
+            tz,
+        );
     }
 
     if project_path.trim().is_empty() {
@@ -1799,7 +1843,7 @@ pub async fn get_project_stats_summary(
     // Phase 2: Process all session files in parallel
     let mut file_stats: Vec<ProjectSessionFileStats> = session_files
         .par_iter()
-        .filter_map(process_session_file_for_project_stats)
+        .filter_map(|path| process_session_file_for_project_stats(path, tz))
         .collect();
 
     // Filter by date
@@ -2187,15 +2231,22 @@ impl TryFrom<RawLogEntry> for ClaudeMessage {
             compact_metadata: log_entry.compact_metadata,
             microcompact_metadata: log_entry.microcompact_metadata,
             provider: None,
+            match_locations: None,
+            session_path: None,
+            project_path: None,
         })
     }
 }
 
+/// Summarize stats across all projects. `timezone` (IANA name) controls the
+/// hour/day/date buckets; it defaults to UTC.
 #[tauri::command]
 pub async fn get_global_stats_summary(
     claude_path: String,
     active_providers: Option<Vec<String>>,
+    timezone: Option<String>,
 ) -> Result<GlobalStatsSummary, String> {
+    let tz = parse_stats_timezone(timezone.as_deref())?;
     let providers_to_include = parse_active_stats_providers(active_providers);
     let projects_path = PathBuf::from(&claude_path).join("projects");
 
@@ -2244,19 +2295,19 @@ pub async fn get_global_stats_summary(
     // Phase 2: Process all session files in parallel
     let mut file_stats: Vec<SessionFileStats> = session_files
         .par_iter()
-        .filter_map(process_session_file_for_global_stats)
+        .filter_map(|path| process_session_file_for_global_stats(path, tz))
         .collect();
 
     if providers_to_include.contains(&StatsProvider::Codex) {
         let (codex_stats, codex_projects) =
-            collect_provider_global_file_stats(StatsProvider::Codex);
+            collect_provider_global_file_stats(StatsProvider::Codex, tz);
         project_names.extend(codex_projects);
         file_stats.extend(codex_stats);
     }
 
     if providers_to_include.contains(&StatsProvider::OpenCode) {
         let (opencode_stats, opencode_projects) =
-            collect_provider_global_file_stats(StatsProvider::OpenCode);
+            collect_provider_global_file_stats(StatsProvider::OpenCode, tz);
         project_names.extend(opencode_projects);
         file_stats.extend(opencode_stats);
     }
@@ -2429,6 +2480,259 @@ pub async fn get_global_stats_summary(
     Ok(summary)
 }
 
+/// Accumulate `(timestamp, cumulative_input, cumulative_output)` across the
+/// assistant messages of a session, in message order.
+///
+/// Assistant messages without usage still produce a (flat) point so the
+/// timeline lines up with the conversation.
+fn build_token_timeline(messages: &[ClaudeMessage]) -> Vec<(String, u32, u32)> {
+    let mut input_total: u32 = 0;
+    let mut output_total: u32 = 0;
+
+    messages
+        .iter()
+        .filter(|message| message.message_type == "assistant")
+        .map(|message| {
+            if let Some(usage) = &message.usage {
+                input_total = input_total.saturating_add(usage.input_tokens.unwrap_or(0));
+                output_total = output_total.saturating_add(usage.output_tokens.unwrap_or(0));
+            }
+            (message.timestamp.clone(), input_total, output_total)
+        })
+        .collect()
+}
+
+/// Token growth within a single session, for the per-session usage chart
+#[tauri::command]
+pub async fn session_token_timeline(
+    provider: String,
+    session_path: String,
+) -> Result<Vec<(String, u32, u32)>, String> {
+    let messages = crate::commands::multi_provider::load_normalized_messages(
+        provider,
+        session_path,
+        None,
+        None,
+    )
+    .await?;
+    Ok(build_token_timeline(&messages))
+}
+
+/// Model name used for usage reported without a model
+const UNKNOWN_MODEL: &str = "unknown";
+
+/// Sum usage and cost per model, then across models. Only messages reporting
+/// usage or a cost are counted.
+fn build_token_totals(messages: &[ClaudeMessage]) -> TokenTotals {
+    let mut by_model: HashMap<&str, ModelTokenTotals> = HashMap::new();
+    for message in messages {
+        if message.usage.is_none() && message.cost_usd.is_none() {
+            continue;
+        }
+        let model = message.model.as_deref().unwrap_or(UNKNOWN_MODEL);
+        let entry = by_model.entry(model).or_insert_with(|| ModelTokenTotals {
+            model: model.to_string(),
+            ..ModelTokenTotals::default()
+        });
+        if let Some(usage) = &message.usage {
+            entry.input_tokens += u64::from(usage.input_tokens.unwrap_or(0));
+            entry.output_tokens += u64::from(usage.output_tokens.unwrap_or(0));
+            entry.cache_creation_input_tokens +=
+                u64::from(usage.cache_creation_input_tokens.unwrap_or(0));
+            entry.cache_read_input_tokens += u64::from(usage.cache_read_input_tokens.unwrap_or(0));
+        }
+        if let Some(cost) = message.cost_usd {
+            entry.cost_usd = Some(entry.cost_usd.unwrap_or(0.0) + cost);
+        }
+    }
+
+    let mut totals = TokenTotals::default();
+    for model in by_model.values_mut() {
+        model.total_tokens = model.input_tokens
+            + model.output_tokens
+            + model.cache_creation_input_tokens
+            + model.cache_read_input_tokens;
+        totals.input_tokens += model.input_tokens;
+        totals.output_tokens += model.output_tokens;
+        totals.cache_creation_input_tokens += model.cache_creation_input_tokens;
+        totals.cache_read_input_tokens += model.cache_read_input_tokens;
+        totals.total_tokens += model.total_tokens;
+        if let Some(cost) = model.cost_usd {
+            totals.cost_usd = Some(totals.cost_usd.unwrap_or(0.0) + cost);
+        }
+    }
+    totals.by_model = by_model.into_values().collect();
+    totals.by_model.sort_by(|a, b| {
+        b.total_tokens
+            .cmp(&a.total_tokens)
+            .then_with(|| a.model.cmp(&b.model))
+    });
+    totals
+}
+
+/// Load model price overrides from a JSON file (see `pricing::load_overrides`),
+/// or reset to the built-in prices when `path` is `None`. Returns the number
+/// of overridden models.
+#[tauri::command]
+pub async fn set_model_pricing_file(path: Option<String>) -> Result<usize, String> {
+    crate::pricing::load_overrides(path.as_deref().map(std::path::Path::new))
+}
+
+/// Token usage and cost summed over a session, broken down by model
+#[tauri::command]
+pub async fn session_token_totals(
+    provider: String,
+    session_path: String,
+) -> Result<TokenTotals, String> {
+    let messages = crate::commands::multi_provider::load_normalized_messages(
+        provider,
+        session_path,
+        None,
+        None,
+    )
+    .await?;
+    Ok(build_token_totals(&messages))
+}
+
+/// Count `timestamps` into `buckets` evenly spaced intervals from the first to
+/// the last one (inclusive, so the latest lands in the final bucket).
+fn bucket_activity(timestamps: &[DateTime<Utc>], buckets: usize) -> Vec<usize> {
+    let mut counts = vec![0; buckets];
+    let (Some(first), Some(last)) = (timestamps.iter().min(), timestamps.iter().max()) else {
+        return counts;
+    };
+    if buckets == 0 {
+        return counts;
+    }
+
+    let span_ms = (*last - *first).num_milliseconds();
+    for ts in timestamps {
+        let offset_ms = (*ts - *first).num_milliseconds();
+        let index = if span_ms == 0 {
+            0
+        } else {
+            (i128::from(offset_ms) * buckets as i128 / i128::from(span_ms)) as usize
+        };
+        counts[index.min(buckets - 1)] += 1;
+    }
+    counts
+}
+
+/// Session counts for a provider over `buckets` intervals between its first and
+/// last activity, for a compact sparkline. Uses session metadata only
+/// (`last_modified`), never message contents.
+#[tauri::command]
+pub async fn provider_activity_sparkline(
+    provider: String,
+    buckets: usize,
+) -> Result<Vec<usize>, String> {
+    use crate::commands::multi_provider::{load_provider_sessions, scan_all_projects};
+
+    let projects = scan_all_projects(None, Some(vec![provider.clone()]), None, None, None).await?;
+    let mut timestamps = Vec::new();
+    for project in projects {
+        match load_provider_sessions(provider.clone(), project.path.clone(), None).await {
+            Ok(sessions) => timestamps.extend(
+                sessions
+                    .iter()
+                    .filter_map(|s| parse_timestamp_utc(&s.last_modified)),
+            ),
+            Err(e) => log::warn!("Skipping project {} in sparkline: {e}", project.path),
+        }
+    }
+
+    Ok(bucket_activity(&timestamps, buckets))
+}
+
+/// Sum of input, output and cache tokens across a session's messages
+fn session_total_tokens(messages: &[ClaudeMessage]) -> u64 {
+    messages
+        .iter()
+        .map(|message| {
+            let usage = extract_token_usage(message);
+            u64::from(usage.input_tokens.unwrap_or(0))
+                + u64::from(usage.output_tokens.unwrap_or(0))
+                + u64::from(usage.cache_creation_input_tokens.unwrap_or(0))
+                + u64::from(usage.cache_read_input_tokens.unwrap_or(0))
+        })
+        .sum()
+}
+
+/// Largest sessions across all (or selected) providers, biggest first.
+///
+/// `by` is `"messages"` or `"tokens"`. Message counts come from session
+/// metadata and are cheap; `"tokens"` loads every session's messages to total
+/// their usage, so it is much slower on large histories.
+#[tauri::command]
+pub async fn largest_sessions(
+    active_providers: Option<Vec<String>>,
+    by: String,
+    limit: Option<usize>,
+) -> Result<Vec<ClaudeSession>, String> {
+    use crate::commands::multi_provider::{
+        load_normalized_messages, load_provider_sessions, scan_all_projects,
+    };
+
+    let by_tokens = match by.as_str() {
+        "messages" => false,
+        "tokens" => true,
+        _ => {
+            return Err(format!(
+                "Invalid ranking '{by}': expected 'messages' or 'tokens'"
+            ))
+        }
+    };
+
+    let projects = scan_all_projects(None, active_providers, None, None, None).await?;
+    let mut ranked: Vec<(u64, ClaudeSession)> = Vec::new();
+    for project in projects {
+        let provider = project
+            .provider
+            .clone()
+            .unwrap_or_else(|| "claude".to_string());
+        let sessions =
+            match load_provider_sessions(provider.clone(), project.path.clone(), None).await {
+                Ok(sessions) => sessions,
+                Err(e) => {
+                    log::warn!("Skipping project {} in largest sessions: {e}", project.path);
+                    continue;
+                }
+            };
+
+        for session in sessions {
+            let size = if by_tokens {
+                match load_normalized_messages(
+                    provider.clone(),
+                    session.file_path.clone(),
+                    None,
+                    None,
+                )
+                .await
+                {
+                    Ok(messages) => session_total_tokens(&messages),
+                    Err(e) => {
+                        log::warn!(
+                            "Skipping session {} in largest sessions: {e}",
+                            session.file_path
+                        );
+                        continue;
+                    }
+                }
+            } else {
+                session.message_count as u64
+            };
+            ranked.push((size, session));
+        }
+    }
+
+    ranked.sort_by(|a, b| b.0.cmp(&a.0));
+    Ok(ranked
+        .into_iter()
+        .take(limit.unwrap_or(10))
+        .map(|(_, session)| session)
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2732,6 +3036,9 @@ mod tests {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            match_locations: None,
+            session_path: None,
+            project_path: None,
         };
 
         let usage = extract_token_usage(&msg);
@@ -2782,6 +3089,9 @@ mod tests {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            match_locations: None,
+            session_path: None,
+            project_path: None,
         };
 
         let usage = extract_token_usage(&msg);
@@ -2830,6 +3140,9 @@ mod tests {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            match_locations: None,
+            session_path: None,
+            project_path: None,
         };
 
         let usage = extract_token_usage(&msg);
@@ -2874,6 +3187,9 @@ mod tests {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            match_locations: None,
+            session_path: None,
+            project_path: None,
         };
 
         let usage = extract_token_usage(&msg);
@@ -2916,6 +3232,9 @@ mod tests {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            match_locations: None,
+            session_path: None,
+            project_path: None,
         };
 
         let usage = extract_token_usage(&msg);
@@ -3007,4 +3326,132 @@ mod tests {
         // 10:00~10:20(20분) + 14:00~14:30(30분) = 50분
         assert_eq!(calculate_session_active_minutes(&mut timestamps), 50);
     }
+
+    #[test]
+    fn test_build_token_totals_sums_per_model() {
+        use crate::test_utils::MessageBuilder;
+
+        let mut sonnet_with_cost = MessageBuilder::assistant()
+            .with_model("claude-sonnet-4")
+            .with_usage(100, 10)
+            .build();
+        sonnet_with_cost.cost_usd = Some(0.25);
+        if let Some(usage) = sonnet_with_cost.usage.as_mut() {
+            usage.cache_read_input_tokens = Some(1000);
+        }
+        let mut unknown_cost_only = MessageBuilder::assistant().build();
+        unknown_cost_only.model = None;
+        unknown_cost_only.cost_usd = Some(0.5);
+
+        let messages = vec![
+            MessageBuilder::user().build(),
+            sonnet_with_cost,
+            MessageBuilder::assistant()
+                .with_model("claude-opus-4")
+                .with_usage(50, 5)
+                .build(),
+            MessageBuilder::assistant()
+                .with_model("claude-sonnet-4")
+                .with_usage(200, 20)
+                .build(),
+            unknown_cost_only,
+        ];
+
+        let totals = build_token_totals(&messages);
+
+        assert_eq!(totals.input_tokens, 350);
+        assert_eq!(totals.output_tokens, 35);
+        assert_eq!(totals.cache_read_input_tokens, 1000);
+        assert_eq!(totals.total_tokens, 1385);
+        assert_eq!(totals.cost_usd, Some(0.75));
+        let models: Vec<_> = totals
+            .by_model
+            .iter()
+            .map(|m| (m.model.as_str(), m.total_tokens, m.cost_usd))
+            .collect();
+        assert_eq!(
+            models,
+            [
+                ("claude-sonnet-4", 1330, Some(0.25)),
+                ("claude-opus-4", 55, None),
+                ("unknown", 0, Some(0.5)),
+            ]
+        );
+        assert_eq!(build_token_totals(&[]), TokenTotals::default());
+    }
+
+    #[test]
+    fn test_build_token_timeline_is_cumulative_per_assistant_message() {
+        use crate::test_utils::MessageBuilder;
+
+        let messages = vec![
+            MessageBuilder::user()
+                .with_timestamp("2025-01-01T00:00:00Z")
+                .build(),
+            MessageBuilder::assistant()
+                .with_timestamp("2025-01-01T00:00:01Z")
+                .with_usage(100, 10)
+                .build(),
+            MessageBuilder::assistant()
+                .with_timestamp("2025-01-01T00:00:02Z")
+                .with_usage(200, 20)
+                .build(),
+            MessageBuilder::user()
+                .with_timestamp("2025-01-01T00:00:03Z")
+                .build(),
+            MessageBuilder::assistant()
+                .with_timestamp("2025-01-01T00:00:04Z")
+                .with_usage(300, 30)
+                .build(),
+        ];
+
+        let timeline = build_token_timeline(&messages);
+
+        assert_eq!(
+            timeline,
+            vec![
+                ("2025-01-01T00:00:01Z".to_string(), 100, 10),
+                ("2025-01-01T00:00:02Z".to_string(), 300, 30),
+                ("2025-01-01T00:00:04Z".to_string(), 600, 60),
+            ]
+        );
+        assert!(timeline
+            .windows(2)
+            .all(|w| w[1].1 > w[0].1 && w[1].2 > w[0].2));
+    }
+
+    #[test]
+    fn test_time_buckets_render_in_requested_timezone() {
+        let instant = parse_timestamp_utc("2024-01-01T15:30:00Z").unwrap();
+
+        // 15:30 UTC on Monday is 00:30 on Tuesday in Tokyo (UTC+9)
+        let tz = parse_stats_timezone(Some("Asia/Tokyo")).unwrap();
+        assert_eq!(time_buckets(instant, tz), (0, 2, "2024-01-02".to_string()));
+        assert_eq!(
+            time_buckets(instant, None),
+            (15, 1, "2024-01-01".to_string())
+        );
+
+        let err = parse_stats_timezone(Some("Mars/Olympus")).unwrap_err();
+        assert!(err.contains("Invalid timezone"));
+    }
+
+    #[test]
+    fn test_bucket_activity_spreads_counts_from_first_to_last() {
+        let timestamps: Vec<DateTime<Utc>> = [
+            "2024-01-01T00:00:00Z",
+            "2024-01-01T01:00:00Z",
+            "2024-01-01T05:00:00Z",
+            "2024-01-01T09:59:00Z",
+            "2024-01-01T10:00:00Z",
+        ]
+        .iter()
+        .filter_map(|ts| parse_timestamp_utc(ts))
+        .collect();
+
+        assert_eq!(bucket_activity(&timestamps, 5), vec![2, 0, 1, 0, 2]);
+        assert_eq!(bucket_activity(&timestamps[..1], 3), vec![1, 0, 0]);
+        assert_eq!(bucket_activity(&[], 3), vec![0, 0, 0]);
+        assert!(bucket_activity(&timestamps, 0).is_empty());
+    }
 }
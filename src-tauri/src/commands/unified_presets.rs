@@ -5,9 +5,9 @@
 //!
 //! Storage: ~/.claude-history-viewer/unified-presets/*.json
 
+use crate::utils::write_json_atomic;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::io::Write;
 use std::path::PathBuf;
 use uuid::Uuid;
 
@@ -141,35 +141,6 @@ fn validate_preset_input(input: &UnifiedPresetInput) -> Result<(), String> {
     Ok(())
 }
 
-/// Write file atomically using temp file + rename pattern
-fn atomic_write(path: &PathBuf, content: &str) -> Result<(), String> {
-    let temp_path = path.with_extension("json.tmp");
-
-    // Write to temp file
-    let mut file =
-        fs::File::create(&temp_path).map_err(|e| format!("Failed to create temp file: {e}"))?;
-
-    file.write_all(content.as_bytes()).map_err(|e| {
-        // Clean up temp file on write failure
-        let _ = fs::remove_file(&temp_path);
-        format!("Failed to write temp file: {e}")
-    })?;
-
-    // Flush and sync to ensure data is on disk
-    file.sync_all().map_err(|e| {
-        let _ = fs::remove_file(&temp_path);
-        format!("Failed to sync temp file: {e}")
-    })?;
-
-    // Drop the file handle before renaming
-    drop(file);
-
-    // Cross-platform atomic rename
-    super::fs_utils::atomic_rename(&temp_path, path)?;
-
-    Ok(())
-}
-
 /// Compute summary from settings and MCP servers JSON
 fn compute_summary(settings_json: &str, mcp_json: &str) -> UnifiedPresetSummary {
     // Parse settings for summary
@@ -358,10 +329,7 @@ pub async fn save_unified_preset(input: UnifiedPresetInput) -> Result<UnifiedPre
 
         // Write to file atomically
         let path = get_preset_path(&preset.id)?;
-        let json = serde_json::to_string_pretty(&preset)
-            .map_err(|e| format!("Failed to serialize preset: {e}"))?;
-
-        atomic_write(&path, &json)?;
+        write_json_atomic(&path, &preset)?;
 
         Ok(preset)
     })
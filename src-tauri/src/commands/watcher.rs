@@ -133,6 +133,10 @@ fn handle_file_event(app_handle: &AppHandle, event: &DebouncedEvent) {
         event_type: event_type.to_string(),
     };
 
+    // Cached searches and project scans may no longer match what's on disk
+    super::multi_provider::invalidate_search_cache();
+    super::multi_provider::invalidate_scan_cache();
+
     // Emit Tauri event to frontend
     if let Err(e) = app_handle.emit(event_type, &watch_event) {
         log::error!("Failed to emit file watch event: {e}");
@@ -1,5 +1,6 @@
 pub mod commands;
 pub mod models;
+pub mod pricing;
 pub mod providers;
 pub mod utils;
 
@@ -11,6 +12,7 @@ use crate::commands::{
         get_all_mcp_servers, get_all_settings, get_claude_json_config, get_mcp_servers,
         get_settings_by_scope, read_text_file, save_mcp_servers, save_settings, write_text_file,
     },
+    export::{export_session_html, export_session_jsonl, export_session_markdown, search_archive},
     feedback::{get_system_info, open_github_issues, send_feedback},
     mcp_presets::{delete_mcp_preset, get_mcp_preset, load_mcp_presets, save_mcp_preset},
     metadata::{
@@ -19,10 +21,15 @@ use crate::commands::{
         MetadataState,
     },
     multi_provider::{
-        detect_providers, load_provider_messages, load_provider_sessions, scan_all_projects,
-        search_all_providers,
+        clear_search_cache, detect_providers, get_message_context, get_message_schema_version,
+        load_provider_messages, load_provider_sessions, opencode_reconstruct_projects,
+        prune_old_sessions, resolve_session, scan_all_projects, scan_all_projects_with_warnings,
+        search_all_providers, search_all_providers_hits, search_all_providers_with_warnings,
+        session_first_user_message, session_primary_language, sessions_with_errors,
+        stream_provider_messages,
     },
     project::{get_claude_folder_path, get_git_log, scan_projects, validate_claude_folder},
+    search_history::{recent_searches, record_search},
     session::{
         get_recent_edits, get_session_message_count, load_project_sessions, load_session_messages,
         load_session_messages_paginated, rename_opencode_session_title, rename_session_native,
@@ -31,7 +38,9 @@ use crate::commands::{
     settings::{delete_preset, get_preset, load_presets, save_preset},
     stats::{
         get_global_stats_summary, get_project_stats_summary, get_project_token_stats,
-        get_session_comparison, get_session_token_stats,
+        get_session_comparison, get_session_token_stats, largest_sessions,
+        provider_activity_sparkline, session_token_timeline, session_token_totals,
+        set_model_pricing_file,
     },
     unified_presets::{
         delete_unified_preset, get_unified_preset, load_unified_presets, save_unified_preset,
@@ -70,6 +79,8 @@ pub fn run() {
             load_session_messages_paginated,
             get_session_message_count,
             search_messages,
+            record_search,
+            recent_searches,
             get_recent_edits,
             restore_file,
             get_session_token_stats,
@@ -77,6 +88,11 @@ pub fn run() {
             get_project_stats_summary,
             get_session_comparison,
             get_global_stats_summary,
+            session_token_timeline,
+            session_token_totals,
+            set_model_pricing_file,
+            provider_activity_sparkline,
+            largest_sessions,
             send_feedback,
             get_system_info,
             open_github_issues,
@@ -119,15 +135,33 @@ pub fn run() {
             rename_session_native,
             reset_session_native_name,
             rename_opencode_session_title,
+            // Session export commands
+            export_session_markdown,
+            export_session_html,
+            export_session_jsonl,
+            search_archive,
             // File watcher commands
             start_file_watcher,
             stop_file_watcher,
             // Multi-provider commands
             detect_providers,
             scan_all_projects,
+            scan_all_projects_with_warnings,
             load_provider_sessions,
             load_provider_messages,
-            search_all_providers
+            stream_provider_messages,
+            get_message_context,
+            opencode_reconstruct_projects,
+            resolve_session,
+            search_all_providers,
+            search_all_providers_hits,
+            search_all_providers_with_warnings,
+            prune_old_sessions,
+            get_message_schema_version,
+            session_first_user_message,
+            session_primary_language,
+            sessions_with_errors,
+            clear_search_cache
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
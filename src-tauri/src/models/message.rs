@@ -172,16 +172,106 @@ pub struct ClaudeMessage {
     /// Provider identifier (claude, codex, opencode)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub provider: Option<String>,
+    /// Where each search match fell (set on search results only)
+    #[serde(rename = "matchLocations", skip_serializing_if = "Option::is_none")]
+    pub match_locations: Option<Vec<MatchLocation>>,
+    /// Session file (or virtual path) holding the message, for
+    /// `load_provider_messages` (set on search results only)
+    #[serde(rename = "sessionPath", skip_serializing_if = "Option::is_none")]
+    pub session_path: Option<String>,
+    /// Path of the owning project as listed by `scan_all_projects` (set on
+    /// search results only)
+    #[serde(rename = "projectPath", skip_serializing_if = "Option::is_none")]
+    pub project_path: Option<String>,
 }
 
+/// Whether a search match fell inside a fenced code block or in prose text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchLocation {
+    Code,
+    Prose,
+}
+
+/// A search result with a short excerpt around the first match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub message: ClaudeMessage,
+    /// Message text around the first match, on one line
+    pub snippet: String,
+    /// `(start, end)` of each match within `snippet`, in UTF-16 code units
+    /// so they can be used directly as JavaScript string indices
+    pub match_ranges: Vec<(usize, usize)>,
+}
+
+/// Version of the normalized message shape produced by the loaders.
+///
+/// Bump whenever content shapes change so the frontend can adapt.
+pub const MESSAGE_SCHEMA_VERSION: u32 = 1;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessagePage {
+    /// Normalizer version that produced `messages` (see `MESSAGE_SCHEMA_VERSION`)
+    pub schema_version: u32,
     pub messages: Vec<ClaudeMessage>,
     pub total_count: usize,
     pub has_more: bool,
     pub next_offset: usize,
 }
 
+/// Paging state of a `MessageList` holding one page of a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageInfo {
+    pub total_count: usize,
+    pub has_more: bool,
+    pub next_offset: usize,
+}
+
+/// Envelope of a message load or search: `messages` are `ClaudeMessage`s
+/// or, for snippet searches, `SearchHit`s
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageList<T = ClaudeMessage> {
+    /// Normalizer version that produced `messages` (see `MESSAGE_SCHEMA_VERSION`)
+    pub schema_version: u32,
+    pub messages: Vec<T>,
+    /// Set when only one page of the session was loaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<PageInfo>,
+}
+
+impl<T> MessageList<T> {
+    /// Tag `messages` with the current `MESSAGE_SCHEMA_VERSION`
+    pub fn new(messages: Vec<T>) -> Self {
+        Self {
+            schema_version: MESSAGE_SCHEMA_VERSION,
+            messages,
+            page: None,
+        }
+    }
+
+    /// One page of a session, tagged like [`MessageList::new`]
+    pub fn paged(messages: Vec<T>, page: PageInfo) -> Self {
+        Self {
+            page: Some(page),
+            ..Self::new(messages)
+        }
+    }
+}
+
+/// Event sent over the channel of `stream_provider_messages`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", content = "data", rename_all = "camelCase")]
+pub enum MessageStreamEvent {
+    /// The next messages of the session, in order
+    Messages { messages: Vec<ClaudeMessage> },
+    /// Sent once after the last batch
+    Done {
+        /// Normalizer version that produced the batches
+        schema_version: u32,
+        total_count: usize,
+    },
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -349,6 +439,9 @@ mod tests {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            match_locations: None,
+            session_path: None,
+            project_path: None,
         };
 
         let serialized = serde_json::to_string(&message).unwrap();
@@ -394,6 +487,9 @@ mod tests {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            match_locations: None,
+            session_path: None,
+            project_path: None,
         };
 
         let serialized = serde_json::to_string(&message).unwrap();
@@ -410,6 +506,7 @@ mod tests {
     #[test]
     fn test_message_page_serialization() {
         let page = MessagePage {
+            schema_version: MESSAGE_SCHEMA_VERSION,
             messages: vec![],
             total_count: 100,
             has_more: true,
@@ -424,6 +521,45 @@ mod tests {
         assert_eq!(deserialized.next_offset, 20);
     }
 
+    #[test]
+    fn test_message_page_carries_current_schema_version() {
+        let page = MessagePage {
+            schema_version: MESSAGE_SCHEMA_VERSION,
+            messages: vec![],
+            total_count: 0,
+            has_more: false,
+            next_offset: 0,
+        };
+
+        let value = serde_json::to_value(&page).unwrap();
+        assert_eq!(value["schema_version"], json!(1));
+    }
+
+    #[test]
+    fn test_message_list_carries_current_schema_version() {
+        let list = MessageList::<ClaudeMessage>::new(vec![]);
+        assert_eq!(list.schema_version, MESSAGE_SCHEMA_VERSION);
+
+        let value = serde_json::to_value(&list).unwrap();
+        assert_eq!(value["schema_version"], json!(1));
+        assert_eq!(value["messages"], json!([]));
+        assert!(value.get("page").is_none());
+
+        let page = PageInfo {
+            total_count: 40,
+            has_more: true,
+            next_offset: 20,
+        };
+        let value =
+            serde_json::to_value(MessageList::<ClaudeMessage>::paged(vec![], page)).unwrap();
+        assert_eq!(
+            value["page"],
+            json!({ "total_count": 40, "has_more": true, "next_offset": 20 })
+        );
+        let deserialized: MessageList = serde_json::from_value(value).unwrap();
+        assert_eq!(deserialized.page, Some(page));
+    }
+
     #[test]
     fn test_content_array_parsing() {
         let json_str = r#"{
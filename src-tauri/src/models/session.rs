@@ -21,6 +21,9 @@ pub struct GitInfo {
     /// 예: "/Users/jack/my-project"
     #[serde(skip_serializing_if = "Option::is_none")]
     pub main_project_path: Option<String>,
+    /// 체크아웃된 브랜치 (detached HEAD이면 None)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +42,9 @@ pub struct ClaudeProject {
     /// Provider identifier (claude, codex, opencode)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub provider: Option<String>,
+    /// Whether `actual_path` still exists as a directory on disk
+    #[serde(default)]
+    pub path_exists: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,6 +63,10 @@ pub struct ClaudeSession {
     /// Provider identifier (claude, codex, opencode)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub provider: Option<String>,
+    /// Small provider-specific metadata bag (e.g. `OpenCode` model ids);
+    /// never holds message content
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -87,6 +97,7 @@ mod tests {
             has_errors: false,
             summary: Some("Test conversation".to_string()),
             provider: None,
+            data: None,
         };
 
         let serialized = serde_json::to_string(&session).unwrap();
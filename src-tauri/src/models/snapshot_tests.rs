@@ -48,6 +48,9 @@ mod claude_message_snapshots {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            match_locations: None,
+            session_path: None,
+            project_path: None,
         };
 
         assert_json_snapshot!("user_message", message);
@@ -96,6 +99,9 @@ mod claude_message_snapshots {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            match_locations: None,
+            session_path: None,
+            project_path: None,
         };
 
         assert_json_snapshot!("assistant_message", message);
@@ -154,6 +160,9 @@ mod claude_message_snapshots {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            match_locations: None,
+            session_path: None,
+            project_path: None,
         };
 
         assert_json_snapshot!("message_with_tool_use", message);
@@ -206,6 +215,7 @@ mod project_snapshots {
             last_modified: "2025-01-15T10:30:00Z".to_string(),
             git_info: None,
             provider: None,
+            path_exists: true,
         };
 
         assert_json_snapshot!("claude_project", project);
@@ -231,6 +241,7 @@ mod session_snapshots {
             has_errors: false,
             summary: Some("Test conversation summary".to_string()),
             provider: None,
+            data: None,
         };
 
         assert_json_snapshot!("claude_session", session);
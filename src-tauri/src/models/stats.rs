@@ -96,6 +96,33 @@ pub struct ModelStats {
     pub cache_read_tokens: u64,
 }
 
+/// Token and cost sums for one model within a session
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelTokenTotals {
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+    pub total_tokens: u64,
+    /// `None` when no message of this model reported a cost
+    pub cost_usd: Option<f64>,
+}
+
+/// Token and cost sums across a whole session, with a per-model breakdown
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenTotals {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+    pub total_tokens: u64,
+    /// `None` when no message reported a cost
+    pub cost_usd: Option<f64>,
+    /// Largest `total_tokens` first
+    pub by_model: Vec<ModelTokenTotals>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectRanking {
     pub project_name: String,
@@ -0,0 +1,188 @@
+//! Per-model token pricing for estimating `cost_usd` when a provider doesn't
+//! record one.
+//!
+//! The built-in table covers common Claude and GPT models and can be
+//! extended or overridden from a JSON file (see `load_overrides`).
+
+use crate::models::TokenUsage;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::RwLock;
+
+/// USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub input: f64,
+    pub output: f64,
+    #[serde(default)]
+    pub cache_write: f64,
+    #[serde(default)]
+    pub cache_read: f64,
+}
+
+impl ModelPricing {
+    const fn new(input: f64, output: f64, cache_write: f64, cache_read: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_write,
+            cache_read,
+        }
+    }
+}
+
+/// Built-in prices, keyed by model name prefix (see `lookup`)
+const DEFAULT_PRICING: &[(&str, ModelPricing)] = &[
+    ("claude-opus-4", ModelPricing::new(15.0, 75.0, 18.75, 1.5)),
+    ("claude-opus-4-1", ModelPricing::new(15.0, 75.0, 18.75, 1.5)),
+    ("claude-opus-4-5", ModelPricing::new(5.0, 25.0, 6.25, 0.5)),
+    ("claude-sonnet-4", ModelPricing::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-sonnet-4-5", ModelPricing::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-haiku-4-5", ModelPricing::new(1.0, 5.0, 1.25, 0.1)),
+    ("claude-3-7-sonnet", ModelPricing::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-3-5-sonnet", ModelPricing::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-3-5-haiku", ModelPricing::new(0.8, 4.0, 1.0, 0.08)),
+    ("gpt-4o", ModelPricing::new(2.5, 10.0, 0.0, 1.25)),
+    ("gpt-4o-mini", ModelPricing::new(0.15, 0.6, 0.0, 0.075)),
+    ("gpt-4.1", ModelPricing::new(2.0, 8.0, 0.0, 0.5)),
+    ("gpt-4.1-mini", ModelPricing::new(0.4, 1.6, 0.0, 0.1)),
+    ("gpt-5", ModelPricing::new(1.25, 10.0, 0.0, 0.125)),
+    ("gpt-5-mini", ModelPricing::new(0.25, 2.0, 0.0, 0.025)),
+    ("gpt-5-codex", ModelPricing::new(1.25, 10.0, 0.0, 0.125)),
+    ("o3", ModelPricing::new(2.0, 8.0, 0.0, 0.5)),
+    ("o3-mini", ModelPricing::new(1.1, 4.4, 0.0, 0.55)),
+    ("o4-mini", ModelPricing::new(1.1, 4.4, 0.0, 0.275)),
+];
+
+fn default_pricing() -> HashMap<String, ModelPricing> {
+    DEFAULT_PRICING
+        .iter()
+        .map(|(model, pricing)| ((*model).to_string(), *pricing))
+        .collect()
+}
+
+lazy_static! {
+    static ref PRICING: RwLock<HashMap<String, ModelPricing>> = RwLock::new(default_pricing());
+}
+
+/// Reset the table to the built-in prices, then apply the overrides in the
+/// JSON file at `path` (`{"model-name": {"input": 3.0, "output": 15.0,
+/// "cache_write": 3.75, "cache_read": 0.3}}`, cache rates optional). With no
+/// path, only the reset happens. Returns the number of overridden models.
+pub fn load_overrides(path: Option<&Path>) -> Result<usize, String> {
+    let mut table = default_pricing();
+    let mut overridden = 0;
+    if let Some(path) = path {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read pricing file {}: {e}", path.display()))?;
+        let overrides: HashMap<String, ModelPricing> = serde_json::from_str(&text)
+            .map_err(|e| format!("Invalid pricing file {}: {e}", path.display()))?;
+        overridden = overrides.len();
+        table.extend(overrides);
+    }
+
+    *PRICING
+        .write()
+        .map_err(|_| "Pricing table lock poisoned".to_string())? = table;
+    Ok(overridden)
+}
+
+/// Price for `model`: the longest table key that equals the model name or is
+/// followed in it by `-` (so `claude-sonnet-4` covers
+/// `claude-sonnet-4-20250514`). A `provider/` prefix such as `anthropic/` is
+/// ignored.
+fn lookup(table: &HashMap<String, ModelPricing>, model: &str) -> Option<ModelPricing> {
+    let model = model.rsplit('/').next().unwrap_or(model);
+    table
+        .iter()
+        .filter(|(key, _)| {
+            model == key.as_str()
+                || model
+                    .strip_prefix(key.as_str())
+                    .is_some_and(|rest| rest.starts_with('-'))
+        })
+        .max_by_key(|(key, _)| key.len())
+        .map(|(_, pricing)| *pricing)
+}
+
+/// Estimated cost in USD of `usage` on `model`, or `None` for unknown models
+pub fn estimate_cost(model: &str, usage: &TokenUsage) -> Option<f64> {
+    let table = PRICING.read().ok()?;
+    let pricing = lookup(&table, model)?;
+    let tokens = |count: Option<u32>| f64::from(count.unwrap_or(0));
+
+    Some(
+        (tokens(usage.input_tokens) * pricing.input
+            + tokens(usage.output_tokens) * pricing.output
+            + tokens(usage.cache_creation_input_tokens) * pricing.cache_write
+            + tokens(usage.cache_read_input_tokens) * pricing.cache_read)
+            / 1_000_000.0,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    fn usage(input: u32, output: u32, cache_write: u32, cache_read: u32) -> TokenUsage {
+        TokenUsage {
+            input_tokens: Some(input),
+            output_tokens: Some(output),
+            cache_creation_input_tokens: Some(cache_write),
+            cache_read_input_tokens: Some(cache_read),
+            service_tier: None,
+        }
+    }
+
+    fn assert_cost(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("model should be priced");
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn estimate_cost_applies_per_million_rates() {
+        let usage = usage(1_000_000, 200_000, 100_000, 1_000_000);
+
+        // 3.0 + 200k * 15/M + 100k * 3.75/M + 1M * 0.3/M
+        assert_cost(
+            estimate_cost("claude-sonnet-4-20250514", &usage),
+            3.0 + 3.0 + 0.375 + 0.3,
+        );
+        assert_cost(
+            estimate_cost("anthropic/claude-opus-4-5-20251101", &usage),
+            5.0 + 5.0 + 0.625 + 0.5,
+        );
+        assert_cost(estimate_cost("gpt-4o-mini", &usage), 0.15 + 0.12 + 0.075);
+        assert_eq!(estimate_cost("claude-sonnet-40", &usage), None);
+        assert_eq!(estimate_cost("unknown-model", &usage), None);
+    }
+
+    #[test]
+    #[serial]
+    fn load_overrides_replaces_and_adds_models() {
+        let tmp = tempfile::TempDir::new().expect("temp dir should be created");
+        let path = tmp.path().join("pricing.json");
+        std::fs::write(
+            &path,
+            r#"{ "claude-sonnet-4": { "input": 1.0, "output": 2.0 }, "local-llm": { "input": 0.5, "output": 0.5 } }"#,
+        )
+        .expect("pricing file should be written");
+        let usage = usage(1_000_000, 1_000_000, 1_000_000, 0);
+
+        assert_eq!(load_overrides(Some(&path)), Ok(2));
+        assert_cost(estimate_cost("claude-sonnet-4", &usage), 3.0);
+        assert_cost(estimate_cost("local-llm", &usage), 1.0);
+
+        assert_eq!(load_overrides(None), Ok(0));
+        assert_cost(estimate_cost("claude-sonnet-4", &usage), 3.0 + 15.0 + 3.75);
+        assert_eq!(estimate_cost("local-llm", &usage), None);
+        assert!(load_overrides(Some(&tmp.path().join("missing.json"))).is_err());
+    }
+}
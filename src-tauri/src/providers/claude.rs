@@ -2,7 +2,10 @@ use super::ProviderInfo;
 
 /// Detect Claude Code installation
 pub fn detect() -> Option<ProviderInfo> {
-    let home = dirs::home_dir()?;
+    let Some(home) = dirs::home_dir() else {
+        log::warn!("Claude Code: home directory could not be resolved");
+        return None;
+    };
     let claude_path = home.join(".claude");
     let projects_path = claude_path.join("projects");
 
@@ -11,6 +14,8 @@ pub fn detect() -> Option<ProviderInfo> {
         display_name: "Claude Code".to_string(),
         base_path: claude_path.to_string_lossy().to_string(),
         is_available: projects_path.exists() && projects_path.is_dir(),
+        // projects/{project}/{session}.jsonl
+        last_activity: super::activity_timestamp(super::latest_mtime(&projects_path, 2)),
     })
 }
 
@@ -1,10 +1,10 @@
-use super::ProviderInfo;
+use super::{Provider, ProviderId, ProviderInfo};
 use crate::models::{ClaudeMessage, ClaudeProject, ClaudeSession, TokenUsage};
-use crate::utils::{find_line_ranges, search_json_value_case_insensitive};
+use crate::utils::{find_line_ranges, SearchPattern, SearchScope};
 use chrono::{DateTime, Utc};
 use memmap2::Mmap;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
 use std::path::Path;
 use std::path::PathBuf;
@@ -22,27 +22,19 @@ pub fn detect() -> Option<ProviderInfo> {
         base_path: base_path.clone(),
         is_available: (sessions_path.exists() && sessions_path.is_dir())
             || (archived_sessions_path.exists() && archived_sessions_path.is_dir()),
+        // sessions/YYYY/MM/DD/rollout-*.jsonl; archived rollouts sit at the top
+        last_activity: super::activity_timestamp(
+            [&sessions_path, &archived_sessions_path]
+                .into_iter()
+                .filter_map(|dir| super::latest_mtime(dir, 4))
+                .max(),
+        ),
     })
 }
 
-/// Get the Codex base path
+/// Get the Codex base path (`$CODEX_HOME`, default `~/.codex`)
 pub fn get_base_path() -> Option<String> {
-    // Check $CODEX_HOME first
-    if let Ok(codex_home) = std::env::var("CODEX_HOME") {
-        let path = PathBuf::from(&codex_home);
-        if path.exists() {
-            return Some(codex_home);
-        }
-    }
-
-    // Default: ~/.codex
-    let home = dirs::home_dir()?;
-    let codex_path = home.join(".codex");
-    if codex_path.exists() {
-        Some(codex_path.to_string_lossy().to_string())
-    } else {
-        None
-    }
+    super::resolve_base_path("Codex", "CODEX_HOME", ".codex")
 }
 
 fn get_sessions_dir() -> Result<PathBuf, String> {
@@ -168,6 +160,7 @@ pub fn scan_projects() -> Result<Vec<ClaudeProject>, String> {
             ClaudeProject {
                 name,
                 path: format!("codex://{cwd}"),
+                path_exists: Path::new(&cwd).is_dir(),
                 actual_path: cwd,
                 session_count,
                 message_count,
@@ -232,6 +225,7 @@ pub fn load_sessions(
                     has_errors: false,
                     summary: info.summary,
                     provider: Some("codex".to_string()),
+                    data: None,
                 });
             }
         }
@@ -370,16 +364,52 @@ pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
     Ok(messages)
 }
 
+/// Path of the rollout file for session `session_id`, searching live and
+/// archived sessions. Rollout files are named `rollout-{timestamp}-{uuid}.jsonl`,
+/// so only ids that are UUIDs can match.
+pub fn find_session(session_id: &str) -> Option<String> {
+    let uuid = uuid::Uuid::parse_str(session_id).ok()?;
+    let suffix = format!("-{}.jsonl", uuid.hyphenated());
+
+    get_existing_session_dirs()
+        .ok()?
+        .into_iter()
+        .flat_map(|dir| {
+            WalkDir::new(dir)
+                .min_depth(1)
+                .into_iter()
+                .filter_map(Result::ok)
+        })
+        .filter(|e| e.file_type().is_file() && is_rollout_jsonl(e.path()))
+        .find(|e| e.file_name().to_string_lossy().ends_with(&suffix))
+        .map(|e| e.path().to_string_lossy().to_string())
+}
+
 /// Search Codex sessions for a query string
+///
+/// Matches only human-readable content text (not block types or call ids),
+/// returns at most `limit` matches, and skips duplicate messages. Each hit's
+/// `session_id` matches the `actual_session_id` (or, when the rollout has no
+/// `session_meta`, the `session_id`) of the session returned by `load_sessions`.
 pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
+    search_matching(&SearchPattern::plain(query), &SearchScope::default(), limit)
+}
+
+/// [`search`] with an explicit [`SearchPattern`], e.g. a regex, keeping only
+/// messages in `scope`
+pub fn search_matching(
+    pattern: &SearchPattern,
+    scope: &SearchScope,
+    limit: usize,
+) -> Result<Vec<ClaudeMessage>, String> {
     let session_dirs = get_existing_session_dirs()?;
 
     if session_dirs.is_empty() {
         return Ok(vec![]);
     }
 
-    let query_lower = query.to_lowercase();
     let mut results = Vec::new();
+    let mut seen: HashSet<(String, String)> = HashSet::new();
 
     for session_dir in session_dirs {
         for entry in WalkDir::new(session_dir)
@@ -389,18 +419,39 @@ pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
             .filter(|e| e.file_type().is_file())
             .filter(|e| is_rollout_jsonl(e.path()))
         {
-            let rollout_path = entry.path();
+            let rollout_path = entry.path().to_string_lossy().to_string();
+            // Read the session's cwd only once a message in it matches
+            let mut project_path: Option<Option<String>> = None;
 
-            if let Ok(messages) = load_messages(&rollout_path.to_string_lossy()) {
-                for msg in messages {
+            if let Ok(messages) = load_messages(&rollout_path) {
+                for mut msg in messages {
                     if results.len() >= limit {
                         return Ok(results);
                     }
 
-                    if let Some(content) = &msg.content {
-                        if search_json_value_case_insensitive(content, &query_lower) {
-                            results.push(msg);
-                        }
+                    let matches = scope.includes(&msg)
+                        && msg
+                            .content
+                            .as_ref()
+                            .is_some_and(|content| pattern.matches_content(content));
+                    if !matches {
+                        continue;
+                    }
+
+                    // Without session_meta there's no id; fall back to the
+                    // rollout path, which is the session's `session_id`.
+                    if msg.session_id.is_empty() || msg.session_id == "unknown" {
+                        msg.session_id.clone_from(&rollout_path);
+                    }
+                    if seen.insert((msg.session_id.clone(), msg.uuid.clone())) {
+                        msg.session_path = Some(rollout_path.clone());
+                        msg.project_path
+                            .clone_from(project_path.get_or_insert_with(|| {
+                                let info = extract_session_info(entry.path()).ok()?;
+                                let cwd = info.cwd.unwrap_or_else(|| "unknown".to_string());
+                                Some(format!("codex://{cwd}"))
+                            }));
+                        results.push(msg);
                     }
                 }
             }
@@ -1272,41 +1323,73 @@ fn build_codex_message(
         compact_metadata: None,
         microcompact_metadata: None,
         provider: Some("codex".to_string()),
+        match_locations: None,
+        session_path: None,
+        project_path: None,
+    }
+}
+
+/// Codex CLI entry in the provider [`registry`](super::registry)
+pub struct CodexProvider;
+
+impl Provider for CodexProvider {
+    fn id(&self) -> ProviderId {
+        ProviderId::Codex
+    }
+
+    fn detect(&self) -> Option<ProviderInfo> {
+        detect()
+    }
+
+    fn scan_projects(&self) -> Result<Vec<ClaudeProject>, String> {
+        scan_projects()
+    }
+
+    fn scan_roots(&self) -> Vec<PathBuf> {
+        get_existing_session_dirs().unwrap_or_default()
+    }
+
+    fn scan_depth(&self) -> usize {
+        // sessions/YYYY/MM/DD/rollout-*.jsonl: message counts and last
+        // activity come from the rollout contents, so the files are stamped
+        4
+    }
+
+    fn load_sessions(
+        &self,
+        project_path: &str,
+        exclude_sidechain: bool,
+    ) -> Result<Vec<ClaudeSession>, String> {
+        load_sessions(project_path, exclude_sidechain)
+    }
+
+    fn load_messages(&self, session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
+        load_messages(session_path)
+    }
+
+    fn search(
+        &self,
+        pattern: &SearchPattern,
+        scope: &SearchScope,
+        limit: usize,
+    ) -> Result<Vec<ClaudeMessage>, String> {
+        search_matching(pattern, scope, limit)
+    }
+
+    fn find_session(&self, id: &str) -> Option<String> {
+        find_session(id)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::EnvVarGuard;
     use serde_json::json;
     use serial_test::serial;
-    use std::ffi::OsString;
     use std::fs;
     use tempfile::TempDir;
 
-    struct EnvVarGuard {
-        key: &'static str,
-        original: Option<OsString>,
-    }
-
-    impl EnvVarGuard {
-        fn set(key: &'static str, value: &std::path::Path) -> Self {
-            let original = std::env::var_os(key);
-            std::env::set_var(key, value);
-            Self { key, original }
-        }
-    }
-
-    impl Drop for EnvVarGuard {
-        fn drop(&mut self) {
-            if let Some(value) = self.original.as_ref() {
-                std::env::set_var(self.key, value);
-            } else {
-                std::env::remove_var(self.key);
-            }
-        }
-    }
-
     #[test]
     fn map_exec_command_to_bash() {
         assert_eq!(map_codex_tool_name("exec_command"), "Bash");
@@ -2089,4 +2172,331 @@ mod tests {
         assert_eq!(messages.len(), 1);
         assert_eq!(messages[0].session_id, "archived-session");
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn multi_provider_commands_tag_every_result_with_provider() {
+        use crate::commands::multi_provider::{
+            load_provider_messages, load_provider_sessions, scan_all_projects, search_all_providers,
+        };
+
+        let tmp = TempDir::new().expect("temp dir should be created");
+        let codex_home = tmp.path().join("codex-home");
+        let sessions_dir = codex_home.join("sessions");
+        fs::create_dir_all(&sessions_dir).expect("sessions dir should be created");
+        let _guard = EnvVarGuard::set("CODEX_HOME", &codex_home);
+
+        let lines = [
+            json!({
+                "type": "session_meta",
+                "payload": { "id": "tagged-session", "cwd": "/tmp/tagged-project" }
+            }),
+            json!({
+                "type": "response_item",
+                "payload": {
+                    "id": "item-1",
+                    "type": "message",
+                    "role": "user",
+                    "created_at": "2026-02-21T10:00:00Z",
+                    "content": [{ "type": "input_text", "text": "tag me please" }]
+                }
+            }),
+        ];
+        let content = lines
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        fs::write(
+            sessions_dir.join("rollout-tagged.jsonl"),
+            format!("{content}\n"),
+        )
+        .expect("rollout should be written");
+
+        let codex_only = || Some(vec!["codex".to_string()]);
+        let projects = scan_all_projects(None, codex_only(), None, None, None)
+            .await
+            .expect("scan should succeed");
+        assert!(!projects.is_empty());
+        assert!(projects.iter().all(|p| p.provider.is_some()));
+
+        let sessions = load_provider_sessions("codex".to_string(), projects[0].path.clone(), None)
+            .await
+            .expect("sessions should load");
+        assert!(!sessions.is_empty());
+        assert!(sessions.iter().all(|s| s.provider.is_some()));
+
+        let messages = load_provider_messages(
+            "codex".to_string(),
+            sessions[0].file_path.clone(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("messages should load")
+        .messages;
+        assert!(!messages.is_empty());
+        assert!(messages.iter().all(|m| m.provider.is_some()));
+
+        let hits = search_all_providers(
+            None,
+            "tag me".to_string(),
+            codex_only(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("search should succeed")
+        .messages;
+        assert!(!hits.is_empty());
+        assert!(hits.iter().all(|m| m.provider.is_some()));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn search_all_providers_keeps_only_requested_roles() {
+        use crate::commands::multi_provider::search_all_providers;
+
+        let tmp = TempDir::new().expect("temp dir should be created");
+        let codex_home = tmp.path().join("codex-home");
+        let sessions_dir = codex_home.join("sessions");
+        fs::create_dir_all(&sessions_dir).expect("sessions dir should be created");
+        let _guard = EnvVarGuard::set("CODEX_HOME", &codex_home);
+
+        let message = |id: &str, role: &str, kind: &str, text: &str, at: &str| {
+            json!({
+                "type": "response_item",
+                "payload": {
+                    "id": id,
+                    "type": "message",
+                    "role": role,
+                    "created_at": at,
+                    "content": [{ "type": kind, "text": text }]
+                }
+            })
+        };
+        let lines = [
+            json!({
+                "type": "session_meta",
+                "payload": { "id": "roles-session", "cwd": "/tmp/roles-project" }
+            }),
+            message(
+                "item-1",
+                "assistant",
+                "output_text",
+                "The lexer feeds the parser",
+                "2026-02-21T10:00:00Z",
+            ),
+            message(
+                "item-2",
+                "user",
+                "input_text",
+                "Why is the parser slow?",
+                "2026-02-21T10:01:00Z",
+            ),
+            message(
+                "item-3",
+                "assistant",
+                "output_text",
+                "The parser backtracks",
+                "2026-02-21T10:02:00Z",
+            ),
+        ];
+        let content = lines
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        fs::write(
+            sessions_dir.join("rollout-roles.jsonl"),
+            format!("{content}\n"),
+        )
+        .expect("rollout should be written");
+
+        let codex_only = || Some(vec!["codex".to_string()]);
+        let all = search_all_providers(
+            None,
+            "parser".to_string(),
+            codex_only(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("search should succeed")
+        .messages;
+        assert_eq!(all.len(), 3);
+
+        // The limit applies after the role filter, so the one user prompt is
+        // found even though an assistant reply comes first in the session
+        let user_only = search_all_providers(
+            None,
+            "parser".to_string(),
+            codex_only(),
+            None,
+            Some(1),
+            None,
+            Some(vec!["user".to_string()]),
+            None,
+            None,
+        )
+        .await
+        .expect("search should succeed")
+        .messages;
+        assert_eq!(user_only.len(), 1);
+        assert_eq!(user_only[0].role.as_deref(), Some("user"));
+        assert!(user_only[0]
+            .content
+            .as_ref()
+            .is_some_and(|c| c.to_string().contains("Why is the parser slow?")));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn scan_sees_messages_appended_to_a_rollout() {
+        use crate::commands::multi_provider::scan_all_projects;
+
+        let tmp = TempDir::new().expect("temp dir should be created");
+        let codex_home = tmp.path().join("codex-home");
+        let day_dir = codex_home
+            .join("sessions")
+            .join("2026")
+            .join("02")
+            .join("19");
+        fs::create_dir_all(&day_dir).expect("sessions dir should be created");
+        let _guard = EnvVarGuard::set("CODEX_HOME", &codex_home);
+
+        let message = |id: &str, text: &str| {
+            json!({
+                "timestamp": "2026-02-19T12:00:01Z",
+                "type": "response_item",
+                "payload": {
+                    "id": id,
+                    "type": "message",
+                    "role": "user",
+                    "content": [{ "type": "input_text", "text": text }]
+                }
+            })
+        };
+        let rollout = day_dir.join("rollout-2026-02-19.jsonl");
+        let meta = json!({
+            "type": "session_meta",
+            "payload": { "id": "sess-1", "cwd": "/tmp/project" }
+        });
+        fs::write(
+            &rollout,
+            format!("{meta}\n{}\n", message("item-1", "first")),
+        )
+        .expect("rollout should be written");
+
+        let message_count = || async {
+            let projects =
+                scan_all_projects(None, Some(vec!["codex".to_string()]), None, None, None)
+                    .await
+                    .expect("scan should succeed");
+            projects.iter().map(|p| p.message_count).sum::<usize>()
+        };
+        assert_eq!(message_count().await, 1);
+
+        let stamp = fs::metadata(&rollout)
+            .and_then(|m| m.modified())
+            .expect("mtime should be readable");
+        let mut file = File::options()
+            .append(true)
+            .open(&rollout)
+            .expect("rollout should open");
+        std::io::Write::write_all(
+            &mut file,
+            format!("{}\n", message("item-2", "second")).as_bytes(),
+        )
+        .expect("line should be appended");
+        file.set_modified(stamp + std::time::Duration::from_secs(10))
+            .expect("mtime should be set");
+        drop(file);
+
+        assert_eq!(message_count().await, 2);
+    }
+
+    #[test]
+    #[serial]
+    fn search_matches_text_not_structural_json_values() {
+        let tmp = TempDir::new().expect("temp dir should be created");
+        let codex_home = tmp.path().join("codex-home");
+        let sessions_dir = codex_home.join("sessions");
+        fs::create_dir_all(&sessions_dir).expect("sessions dir should be created");
+        let _guard = EnvVarGuard::set("CODEX_HOME", &codex_home);
+
+        let lines = [
+            json!({
+                "type": "session_meta",
+                "payload": { "id": "search-session", "cwd": "/tmp/project" }
+            }),
+            json!({
+                "type": "response_item",
+                "payload": {
+                    "id": "item-1",
+                    "type": "function_call",
+                    "name": "exec_command",
+                    "call_id": "call_xyz",
+                    "arguments": "{\"cmd\":\"cargo test\"}"
+                }
+            }),
+            json!({
+                "type": "response_item",
+                "payload": {
+                    "id": "item-2",
+                    "type": "message",
+                    "role": "assistant",
+                    "created_at": "2026-02-21T10:00:00Z",
+                    "content": [{ "type": "output_text", "text": "All tests pass" }]
+                }
+            }),
+        ];
+        let content = lines
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        fs::write(
+            sessions_dir.join("rollout-search.jsonl"),
+            format!("{content}\n"),
+        )
+        .expect("fixture should be written");
+
+        assert!(search("tool_use", 10).expect("search").is_empty());
+        assert!(search("call_xyz", 10).expect("search").is_empty());
+
+        let hits = search("cargo test", 10).expect("search should succeed");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "search-session");
+
+        let hits = search("tests pass", 10).expect("search should succeed");
+        assert_eq!(hits.len(), 1);
+        assert!(search("tests pass", 0).expect("search").is_empty());
+
+        // exec_command is normalized to Bash, so a tool filter uses that name
+        let tool_scope = |tool: &str| SearchScope {
+            tool: Some(tool.to_string()),
+            ..SearchScope::default()
+        };
+        let cargo = SearchPattern::plain("cargo test");
+        let hits = search_matching(&cargo, &tool_scope("bash"), 10).expect("search");
+        assert_eq!(hits.len(), 1);
+        assert!(search_matching(&cargo, &tool_scope("Edit"), 10)
+            .expect("search")
+            .is_empty());
+        let replies = SearchPattern::plain("tests pass");
+        assert!(search_matching(&replies, &tool_scope("Bash"), 10)
+            .expect("search")
+            .is_empty());
+    }
 }
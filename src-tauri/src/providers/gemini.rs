@@ -0,0 +1,718 @@
+use super::{Provider, ProviderId, ProviderInfo};
+use crate::models::{ClaudeMessage, ClaudeProject, ClaudeSession, TokenUsage};
+use crate::utils::{is_safe_storage_id, SearchPattern, SearchScope};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Gemini CLI records each chat as one JSON file:
+//   {base}/tmp/{project_hash}/chats/session-*.json
+// holding `{"sessionId", "startTime", "lastUpdated", "messages": [...]}`.
+// Messages have `type` "user" or "gemini"; model turns may carry `thoughts`,
+// `toolCalls`, `tokens` and `model`. The project hash can't be reversed, so
+// the real project path is only known when a `.project_root` file exists.
+// Sessions are addressed as `gemini://{project_hash}/{session_id}`.
+
+/// Detect Gemini CLI installation
+pub fn detect() -> Option<ProviderInfo> {
+    let base_path = get_base_path()?;
+    let tmp_path = Path::new(&base_path).join("tmp");
+
+    Some(ProviderInfo {
+        id: "gemini".to_string(),
+        display_name: "Gemini CLI".to_string(),
+        base_path: base_path.clone(),
+        is_available: tmp_path.is_dir(),
+        // tmp/{project_hash}/chats/session-*.json
+        last_activity: super::activity_timestamp(super::latest_mtime(&tmp_path, 3)),
+    })
+}
+
+/// Get the Gemini CLI base path (`$GEMINI_HOME`, default `~/.gemini`)
+pub fn get_base_path() -> Option<String> {
+    super::resolve_base_path("Gemini CLI", "GEMINI_HOME", ".gemini")
+}
+
+fn get_tmp_dir() -> Result<PathBuf, String> {
+    let base_path = get_base_path().ok_or_else(|| "Gemini CLI not found".to_string())?;
+    Ok(Path::new(&base_path).join("tmp"))
+}
+
+/// List the chat files of one project directory
+fn list_session_files(chats_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(chats_dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|e| !e.file_type().map_or(true, |ft| ft.is_symlink()))
+        .map(|e| e.path())
+        .filter(|p| {
+            p.extension().is_some_and(|ext| ext == "json")
+                && p.file_name()
+                    .is_some_and(|n| n.to_string_lossy().starts_with("session-"))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+fn read_session_file(path: &Path) -> Option<Value> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// The chat's `sessionId`, or the file stem for chats without one
+fn session_id_of(val: &Value, path: &Path) -> String {
+    val.get("sessionId")
+        .and_then(Value::as_str)
+        .map(String::from)
+        .unwrap_or_else(|| {
+            path.file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string()
+        })
+}
+
+fn file_modified_rfc3339(path: &Path) -> Option<String> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(DateTime::<Utc>::from(modified).to_rfc3339())
+}
+
+/// Chat file behind a `gemini://{project_hash}/{session_id}` session path.
+/// A plain file path is accepted too, as long as it points into
+/// `{base}/tmp`.
+fn resolve_session_path(session_path: &str) -> Result<PathBuf, String> {
+    let Some(rest) = session_path.strip_prefix("gemini://") else {
+        return validate_session_path(session_path);
+    };
+    let (project_hash, session_id) = rest
+        .split_once('/')
+        .filter(|(hash, id)| is_safe_storage_id(hash) && is_safe_storage_id(id))
+        .ok_or_else(|| format!("Invalid Gemini session path: {session_path}"))?;
+
+    // File names embed the first 8 characters of the session id, so try
+    // those files before parsing the rest
+    let id_prefix: String = session_id.chars().take(8).collect();
+    let mut candidates = list_session_files(&get_tmp_dir()?.join(project_hash).join("chats"));
+    candidates.sort_by_key(|path| {
+        !path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().contains(&id_prefix))
+    });
+    candidates
+        .into_iter()
+        .find(|path| {
+            read_session_file(path).is_some_and(|val| session_id_of(&val, path) == session_id)
+        })
+        .ok_or_else(|| format!("Gemini session not found: {session_path}"))
+}
+
+/// Make sure a session path points into `{base}/tmp`
+fn validate_session_path(raw_session_path: &str) -> Result<PathBuf, String> {
+    let tmp_dir = get_tmp_dir()?
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve Gemini tmp directory: {e}"))?;
+    let session = Path::new(raw_session_path)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve session path: {e}"))?;
+
+    if !session.starts_with(&tmp_dir) {
+        return Err(format!(
+            "Session path is outside Gemini CLI storage: {raw_session_path}"
+        ));
+    }
+    Ok(session)
+}
+
+/// Scan Gemini CLI projects (one per project hash directory with chats)
+pub fn scan_projects() -> Result<Vec<ClaudeProject>, String> {
+    let tmp_dir = get_tmp_dir()?;
+    if !tmp_dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let mut projects = Vec::new();
+    for entry in fs::read_dir(&tmp_dir).map_err(|e| e.to_string())?.flatten() {
+        if entry.file_type().map_or(true, |ft| ft.is_symlink()) {
+            continue;
+        }
+        let project_hash = entry.file_name().to_string_lossy().to_string();
+        if !is_safe_storage_id(&project_hash) {
+            continue;
+        }
+
+        let session_files = list_session_files(&entry.path().join("chats"));
+        if session_files.is_empty() {
+            continue;
+        }
+
+        let actual_path = fs::read_to_string(entry.path().join(".project_root"))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default();
+        let name = Path::new(&actual_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| project_hash.chars().take(8).collect());
+
+        let last_modified = session_files
+            .iter()
+            .filter_map(|p| file_modified_rfc3339(p))
+            .max()
+            .unwrap_or_else(|| Utc::now().to_rfc3339());
+
+        projects.push(ClaudeProject {
+            name,
+            path: format!("gemini://{project_hash}"),
+            path_exists: Path::new(&actual_path).is_dir(),
+            actual_path,
+            session_count: session_files.len(),
+            message_count: 0,
+            last_modified,
+            git_info: None,
+            provider: Some("gemini".to_string()),
+        });
+    }
+
+    projects.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
+    Ok(projects)
+}
+
+/// Load sessions for a Gemini CLI project
+pub fn load_sessions(
+    project_path: &str,
+    _exclude_sidechain: bool,
+) -> Result<Vec<ClaudeSession>, String> {
+    let project_hash = project_path
+        .strip_prefix("gemini://")
+        .unwrap_or(project_path);
+    if !is_safe_storage_id(project_hash) {
+        return Err(format!("Invalid Gemini project path: {project_path}"));
+    }
+
+    let chats_dir = get_tmp_dir()?.join(project_hash).join("chats");
+    let mut sessions = Vec::new();
+
+    for path in list_session_files(&chats_dir) {
+        let Some(val) = read_session_file(&path) else {
+            continue;
+        };
+        let messages = val
+            .get("messages")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let session_id = session_id_of(&val, &path);
+        let last_modified = file_modified_rfc3339(&path).unwrap_or_default();
+        let first_message_time = val
+            .get("startTime")
+            .and_then(Value::as_str)
+            .map(String::from)
+            .unwrap_or_else(|| last_modified.clone());
+        let last_message_time = val
+            .get("lastUpdated")
+            .and_then(Value::as_str)
+            .map(String::from)
+            .unwrap_or_else(|| last_modified.clone());
+        let has_tool_use = messages.iter().any(|m| {
+            m.get("toolCalls")
+                .and_then(Value::as_array)
+                .is_some_and(|calls| !calls.is_empty())
+        });
+        let has_errors = messages
+            .iter()
+            .any(|m| m.get("type").and_then(Value::as_str) == Some("error"));
+        let summary = messages
+            .iter()
+            .find(|m| m.get("type").and_then(Value::as_str) == Some("user"))
+            .and_then(|m| message_text(m.get("content")?))
+            .filter(|text| !text.is_empty());
+
+        let virtual_path = format!("gemini://{project_hash}/{session_id}");
+        sessions.push(ClaudeSession {
+            session_id: virtual_path.clone(),
+            actual_session_id: session_id,
+            file_path: virtual_path,
+            project_name: String::new(),
+            message_count: messages.len(),
+            first_message_time,
+            last_message_time,
+            last_modified,
+            has_tool_use,
+            has_errors,
+            summary,
+            provider: Some("gemini".to_string()),
+            data: None,
+        });
+    }
+
+    sessions.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
+    Ok(sessions)
+}
+
+/// Load messages from a Gemini CLI chat
+pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
+    let path = resolve_session_path(session_path)?;
+    let val = read_session_file(&path)
+        .ok_or_else(|| format!("Failed to parse Gemini session: {session_path}"))?;
+
+    let session_id = session_id_of(&val, &path);
+    let fallback_time = val
+        .get("startTime")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+
+    let messages = val
+        .get("messages")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    Ok(messages
+        .iter()
+        .enumerate()
+        .filter_map(|(index, msg)| convert_message(msg, index, &session_id, fallback_time))
+        .collect())
+}
+
+/// Search Gemini CLI chats for a query string
+pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
+    search_matching(&SearchPattern::plain(query), &SearchScope::default(), limit)
+}
+
+/// [`search`] with an explicit [`SearchPattern`], e.g. a regex, keeping only
+/// messages in `scope`
+pub fn search_matching(
+    pattern: &SearchPattern,
+    scope: &SearchScope,
+    limit: usize,
+) -> Result<Vec<ClaudeMessage>, String> {
+    let mut results = Vec::new();
+
+    for project in scan_projects()? {
+        let sessions = match load_sessions(&project.path, false) {
+            Ok(sessions) => sessions,
+            Err(e) => {
+                log::warn!("Skipping Gemini project {} in search: {e}", project.name);
+                continue;
+            }
+        };
+        for session in sessions {
+            let Ok(messages) = load_messages(&session.file_path) else {
+                continue;
+            };
+            for mut msg in messages {
+                if results.len() >= limit {
+                    return Ok(results);
+                }
+                if scope.includes(&msg)
+                    && msg
+                        .content
+                        .as_ref()
+                        .is_some_and(|c| pattern.matches_content(c))
+                {
+                    msg.project_name = Some(project.name.clone());
+                    msg.session_path = Some(session.file_path.clone());
+                    msg.project_path = Some(project.path.clone());
+                    results.push(msg);
+                }
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+// ============================================================================
+// Internal helpers
+// ============================================================================
+
+/// Text of a message `content`, which is either a string or a list of parts
+fn message_text(content: &Value) -> Option<String> {
+    match content {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(parts) => Some(
+            parts
+                .iter()
+                .filter_map(|p| p.get("text").and_then(Value::as_str))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        _ => None,
+    }
+}
+
+/// Pull a displayable result out of a tool call: the function response
+/// output, then the rendered `resultDisplay`, then the raw result.
+fn tool_call_result(call: &Value) -> Value {
+    let output = call
+        .get("result")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .find_map(|part| {
+            let response = part.get("functionResponse")?.get("response")?;
+            response
+                .get("output")
+                .or_else(|| response.get("error"))
+                .cloned()
+        });
+
+    output
+        .or_else(|| call.get("resultDisplay").cloned())
+        .or_else(|| call.get("result").cloned())
+        .unwrap_or(Value::String(String::new()))
+}
+
+/// Map Gemini CLI's built-in tool names to the Claude names the renderers know
+fn map_gemini_tool_name(name: &str) -> &str {
+    match name {
+        "read_file" => "Read",
+        "write_file" => "Write",
+        "replace" => "Edit",
+        "run_shell_command" => "Bash",
+        "list_directory" => "LS",
+        "glob" => "Glob",
+        "search_file_content" => "Grep",
+        "web_fetch" => "WebFetch",
+        "google_web_search" => "WebSearch",
+        _ => name,
+    }
+}
+
+fn normalize_gemini_tool_input(tool_name: &str, input: Value) -> Value {
+    let Value::Object(mut input_obj) = input else {
+        return input;
+    };
+
+    let renames: &[(&str, &str)] = match tool_name {
+        "Read" | "Write" | "Edit" => &[("absolute_path", "file_path")],
+        "Grep" => &[("include", "glob")],
+        _ => &[],
+    };
+    for (from, to) in renames {
+        if !input_obj.contains_key(*to) {
+            if let Some(value) = input_obj.remove(*from) {
+                input_obj.insert((*to).to_string(), value);
+            }
+        }
+    }
+
+    Value::Object(input_obj)
+}
+
+fn convert_message(
+    msg: &Value,
+    index: usize,
+    session_id: &str,
+    fallback_time: &str,
+) -> Option<ClaudeMessage> {
+    let (message_type, role) = match msg.get("type").and_then(Value::as_str)? {
+        "user" => ("user", "user"),
+        "gemini" => ("assistant", "assistant"),
+        _ => return None,
+    };
+
+    let mut content_items = Vec::new();
+
+    for thought in msg
+        .get("thoughts")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let subject = thought.get("subject").and_then(Value::as_str).unwrap_or("");
+        let description = thought
+            .get("description")
+            .and_then(Value::as_str)
+            .unwrap_or("");
+        let text = match (subject.is_empty(), description.is_empty()) {
+            (false, false) => format!("**{subject}**\n{description}"),
+            (false, true) => subject.to_string(),
+            _ => description.to_string(),
+        };
+        if !text.is_empty() {
+            content_items.push(serde_json::json!({ "type": "thinking", "thinking": text }));
+        }
+    }
+
+    if let Some(text) = msg.get("content").and_then(message_text) {
+        if !text.is_empty() {
+            content_items.push(serde_json::json!({ "type": "text", "text": text }));
+        }
+    }
+
+    for (call_index, call) in msg
+        .get("toolCalls")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        let tool_id = call
+            .get("id")
+            .and_then(Value::as_str)
+            .map(String::from)
+            .unwrap_or_else(|| format!("gemini-tool-{index}-{call_index}"));
+        let name = map_gemini_tool_name(
+            call.get("name")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown"),
+        );
+        let input = normalize_gemini_tool_input(
+            name,
+            call.get("args")
+                .cloned()
+                .unwrap_or_else(|| serde_json::json!({})),
+        );
+        content_items.push(serde_json::json!({
+            "type": "tool_use",
+            "id": tool_id,
+            "name": name,
+            "input": input,
+        }));
+
+        if call.get("result").is_some() || call.get("resultDisplay").is_some() {
+            let mut tool_result = serde_json::json!({
+                "type": "tool_result",
+                "tool_use_id": tool_id,
+                "content": tool_call_result(call),
+            });
+            if call.get("status").and_then(Value::as_str) == Some("error") {
+                tool_result["is_error"] = Value::Bool(true);
+            }
+            content_items.push(tool_result);
+        }
+    }
+
+    if content_items.is_empty() {
+        return None;
+    }
+
+    // Reasoning (`thoughts`) tokens are billed as output, as in Claude usage
+    let usage = msg.get("tokens").map(|t| TokenUsage {
+        input_tokens: t.get("input").and_then(Value::as_u64).map(|v| v as u32),
+        output_tokens: t.get("output").and_then(Value::as_u64).map(|v| {
+            let thoughts = t.get("thoughts").and_then(Value::as_u64).unwrap_or(0);
+            (v + thoughts) as u32
+        }),
+        cache_creation_input_tokens: None,
+        cache_read_input_tokens: t.get("cached").and_then(Value::as_u64).map(|v| v as u32),
+        service_tier: None,
+    });
+
+    Some(ClaudeMessage {
+        uuid: msg
+            .get("id")
+            .and_then(Value::as_str)
+            .map(String::from)
+            .unwrap_or_else(|| format!("{session_id}-{index}")),
+        parent_uuid: None,
+        session_id: session_id.to_string(),
+        timestamp: msg
+            .get("timestamp")
+            .and_then(Value::as_str)
+            .unwrap_or(fallback_time)
+            .to_string(),
+        message_type: message_type.to_string(),
+        content: Some(Value::Array(content_items)),
+        project_name: None,
+        tool_use: None,
+        tool_use_result: None,
+        is_sidechain: None,
+        usage,
+        role: Some(role.to_string()),
+        model: msg.get("model").and_then(Value::as_str).map(String::from),
+        stop_reason: None,
+        cost_usd: None,
+        duration_ms: None,
+        message_id: None,
+        snapshot: None,
+        is_snapshot_update: None,
+        data: None,
+        tool_use_id: None,
+        parent_tool_use_id: None,
+        operation: None,
+        subtype: None,
+        level: None,
+        hook_count: None,
+        hook_infos: None,
+        stop_reason_system: None,
+        prevented_continuation: None,
+        compact_metadata: None,
+        microcompact_metadata: None,
+        provider: Some("gemini".to_string()),
+        match_locations: None,
+        session_path: None,
+        project_path: None,
+    })
+}
+
+/// Gemini CLI entry in the provider [`registry`](super::registry)
+pub struct GeminiProvider;
+
+impl Provider for GeminiProvider {
+    fn id(&self) -> ProviderId {
+        ProviderId::Gemini
+    }
+
+    fn detect(&self) -> Option<ProviderInfo> {
+        detect()
+    }
+
+    fn scan_projects(&self) -> Result<Vec<ClaudeProject>, String> {
+        scan_projects()
+    }
+
+    fn scan_roots(&self) -> Vec<PathBuf> {
+        get_tmp_dir()
+            .into_iter()
+            .filter(|dir| dir.is_dir())
+            .collect()
+    }
+
+    fn scan_depth(&self) -> usize {
+        // tmp/{project_hash}/chats
+        2
+    }
+
+    fn load_sessions(
+        &self,
+        project_path: &str,
+        exclude_sidechain: bool,
+    ) -> Result<Vec<ClaudeSession>, String> {
+        load_sessions(project_path, exclude_sidechain)
+    }
+
+    fn load_messages(&self, session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
+        load_messages(session_path)
+    }
+
+    fn search(
+        &self,
+        pattern: &SearchPattern,
+        scope: &SearchScope,
+        limit: usize,
+    ) -> Result<Vec<ClaudeMessage>, String> {
+        search_matching(pattern, scope, limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::EnvVarGuard;
+    use serde_json::json;
+    use serial_test::serial;
+    use tempfile::TempDir;
+
+    #[test]
+    #[serial]
+    fn loads_fixture_chat_with_text_and_function_calls() {
+        let tmp = TempDir::new().expect("temp dir should be created");
+        let gemini_home = tmp.path().join("gemini-home");
+        let project_dir = gemini_home.join("tmp").join("abc123");
+        let chats_dir = project_dir.join("chats");
+        fs::create_dir_all(&chats_dir).expect("chats dir should be created");
+        fs::write(project_dir.join(".project_root"), "/work/my-app\n")
+            .expect("project root should be written");
+        let _guard = EnvVarGuard::set("GEMINI_HOME", &gemini_home);
+
+        let session = json!({
+            "sessionId": "sess-1",
+            "startTime": "2025-07-01T10:00:00Z",
+            "lastUpdated": "2025-07-01T10:01:00Z",
+            "messages": [
+                { "id": "m1", "timestamp": "2025-07-01T10:00:00Z", "type": "user", "content": "List the files" },
+                {
+                    "id": "m2",
+                    "timestamp": "2025-07-01T10:00:05Z",
+                    "type": "gemini",
+                    "content": "Here they are.",
+                    "model": "gemini-2.5-pro",
+                    "tokens": { "input": 120, "output": 30, "cached": 10, "thoughts": 5 },
+                    "toolCalls": [{
+                        "id": "call-1",
+                        "name": "list_directory",
+                        "args": { "path": "." },
+                        "status": "success",
+                        "result": [{ "functionResponse": { "id": "call-1", "name": "list_directory", "response": { "output": "src\nCargo.toml" } } }]
+                    }]
+                },
+                { "id": "m3", "timestamp": "2025-07-01T10:00:06Z", "type": "info", "content": "ignored" }
+            ]
+        });
+        fs::write(
+            chats_dir.join("session-2025-07-01T10-00-sess1.json"),
+            session.to_string(),
+        )
+        .expect("session should be written");
+
+        let projects = scan_projects().expect("scan should succeed");
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].name, "my-app");
+        assert_eq!(projects[0].path, "gemini://abc123");
+
+        let sessions = load_sessions(&projects[0].path, false).expect("sessions should load");
+        assert_eq!(sessions.len(), 1);
+        assert!(sessions[0].has_tool_use);
+        assert_eq!(sessions[0].summary.as_deref(), Some("List the files"));
+        assert_eq!(sessions[0].session_id, "gemini://abc123/sess-1");
+        assert_eq!(sessions[0].file_path, "gemini://abc123/sess-1");
+        assert!(load_messages("gemini://abc123/../sess-1").is_err());
+        assert!(load_messages("gemini://abc123/sess-2").is_err());
+
+        let messages = load_messages(&sessions[0].file_path).expect("messages should load");
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].message_type, "user");
+        assert_eq!(messages[1].message_type, "assistant");
+        assert_eq!(messages[1].model.as_deref(), Some("gemini-2.5-pro"));
+        assert_eq!(
+            messages[1].usage.as_ref().and_then(|u| u.input_tokens),
+            Some(120)
+        );
+        assert_eq!(
+            messages[1].usage.as_ref().and_then(|u| u.output_tokens),
+            Some(35)
+        );
+
+        let content = messages[1].content.as_ref().unwrap();
+        assert_eq!(content[1]["type"], "tool_use");
+        assert_eq!(content[1]["name"], "LS");
+        assert_eq!(content[2]["type"], "tool_result");
+        assert_eq!(content[2]["tool_use_id"], "call-1");
+        assert_eq!(content[2]["content"], "src\nCargo.toml");
+
+        let hits = search("cargo.toml", 10).expect("search should succeed");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].uuid, "m2");
+    }
+
+    #[test]
+    fn maps_builtin_tool_names_and_input_keys() {
+        let msg = serde_json::json!({
+            "id": "m1",
+            "type": "gemini",
+            "timestamp": "2025-01-01T00:00:00Z",
+            "toolCalls": [
+                { "id": "c1", "name": "read_file", "args": { "absolute_path": "/repo/src/main.rs" } },
+                { "id": "c2", "name": "run_shell_command", "args": { "command": "cargo test" } },
+                { "id": "c3", "name": "save_memory", "args": { "fact": "x" } }
+            ]
+        });
+
+        let converted =
+            convert_message(&msg, 0, "s1", "2025-01-01T00:00:00Z").expect("message should convert");
+        let content = converted.content.expect("content");
+        assert_eq!(content[0]["name"], "Read");
+        assert_eq!(content[0]["input"]["file_path"], "/repo/src/main.rs");
+        assert!(content[0]["input"].get("absolute_path").is_none());
+        assert_eq!(content[1]["name"], "Bash");
+        assert_eq!(content[1]["input"]["command"], "cargo test");
+        assert_eq!(content[2]["name"], "save_memory");
+    }
+}
@@ -1,8 +1,19 @@
+use crate::models::{ClaudeMessage, ClaudeProject, ClaudeSession};
+use crate::utils::{SearchPattern, SearchScope};
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
 
 pub mod claude;
 pub mod codex;
+pub mod gemini;
 pub mod opencode;
+pub mod zed;
 
 /// Provider identifier
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -11,6 +22,8 @@ pub enum ProviderId {
     Claude,
     Codex,
     OpenCode,
+    Gemini,
+    Zed,
 }
 
 impl ProviderId {
@@ -19,6 +32,8 @@ impl ProviderId {
             Self::Claude => "claude",
             Self::Codex => "codex",
             Self::OpenCode => "opencode",
+            Self::Gemini => "gemini",
+            Self::Zed => "zed",
         }
     }
 
@@ -27,6 +42,8 @@ impl ProviderId {
             "claude" => Some(Self::Claude),
             "codex" => Some(Self::Codex),
             "opencode" => Some(Self::OpenCode),
+            "gemini" => Some(Self::Gemini),
+            "zed" => Some(Self::Zed),
             _ => None,
         }
     }
@@ -36,6 +53,8 @@ impl ProviderId {
             Self::Claude => "Claude Code",
             Self::Codex => "Codex CLI",
             Self::OpenCode => "OpenCode",
+            Self::Gemini => "Gemini CLI",
+            Self::Zed => "Zed",
         }
     }
 }
@@ -47,21 +66,378 @@ pub struct ProviderInfo {
     pub display_name: String,
     pub base_path: String,
     pub is_available: bool,
+    /// Most recent modification time in the provider's storage (RFC 3339),
+    /// so the UI can default to the provider used last
+    #[serde(default)]
+    pub last_activity: Option<String>,
+}
+
+/// A page of a session counted back from its newest message, as in
+/// `load_session_messages_paginated`: the newest `offset` messages are
+/// skipped and up to `limit` messages before them are returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageWindow {
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl MessageWindow {
+    /// The whole session
+    pub const ALL: Self = Self {
+        offset: 0,
+        limit: usize::MAX,
+    };
+
+    /// Indices of the window in a session of `total` messages, oldest first
+    pub fn range(self, total: usize) -> Range<usize> {
+        let end = total.saturating_sub(self.offset);
+        end.saturating_sub(self.limit)..end
+    }
+}
+
+/// Projects found by a scan, and problems that left some of them out
+/// without failing the whole scan
+#[derive(Debug, Clone, Default)]
+pub struct ProjectScan {
+    pub projects: Vec<ClaudeProject>,
+    pub warnings: Vec<String>,
+}
+
+/// Operations shared by the file-backed providers, so commands can dispatch
+/// through [`registry`] instead of matching on provider ids.
+///
+/// Claude Code isn't registered: its loaders are async commands that take a
+/// user-configurable base path, so callers handle it separately.
+pub trait Provider: Send + Sync {
+    fn id(&self) -> ProviderId;
+
+    fn detect(&self) -> Option<ProviderInfo>;
+
+    fn scan_projects(&self) -> Result<Vec<ClaudeProject>, String>;
+
+    /// `scan_projects`, also reporting projects it had to leave out (e.g. with
+    /// broken storage). The default reports none.
+    fn scan_projects_with_warnings(&self) -> Result<ProjectScan, String> {
+        Ok(ProjectScan {
+            projects: self.scan_projects()?,
+            warnings: Vec::new(),
+        })
+    }
+
+    /// Existing directories `scan_projects` reads. Scan results are cached
+    /// per provider and roots, and reused while the entries down to
+    /// `scan_depth` below them are unchanged.
+    fn scan_roots(&self) -> Vec<PathBuf>;
+
+    /// How many levels below `scan_roots` the scan lists. Creating or
+    /// removing a session changes a directory within that depth; writes
+    /// deeper inside a session file don't invalidate the cache.
+    fn scan_depth(&self) -> usize;
+
+    fn load_sessions(
+        &self,
+        project_path: &str,
+        exclude_sidechain: bool,
+    ) -> Result<Vec<ClaudeSession>, String>;
+
+    fn load_messages(&self, session_path: &str) -> Result<Vec<ClaudeMessage>, String>;
+
+    /// One `window` of a session's messages, oldest first, and the total
+    /// message count. The default loads the whole session and slices it;
+    /// providers that can read part of a session override it.
+    fn load_messages_page(
+        &self,
+        session_path: &str,
+        window: MessageWindow,
+    ) -> Result<(Vec<ClaudeMessage>, usize), String> {
+        let mut messages = self.load_messages(session_path)?;
+        let total = messages.len();
+        let page = messages.drain(window.range(total)).collect();
+        Ok((page, total))
+    }
+
+    /// Hand the session's messages to `on_batch`, oldest first, in batches of
+    /// at most `batch_size`, stopping at the first error. The default loads
+    /// the whole session first; providers that can read part of a session
+    /// override it.
+    fn load_message_batches(
+        &self,
+        session_path: &str,
+        batch_size: usize,
+        on_batch: &mut dyn FnMut(Vec<ClaudeMessage>) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let mut messages = self.load_messages(session_path)?.into_iter().peekable();
+        while messages.peek().is_some() {
+            on_batch(messages.by_ref().take(batch_size.max(1)).collect())?;
+        }
+        Ok(())
+    }
+
+    /// Messages matching `pattern` within `scope`, at most `limit` of them
+    fn search(
+        &self,
+        pattern: &SearchPattern,
+        scope: &SearchScope,
+        limit: usize,
+    ) -> Result<Vec<ClaudeMessage>, String>;
+
+    /// Session path for a raw session id, if this provider owns it
+    fn find_session(&self, _id: &str) -> Option<String> {
+        None
+    }
+
+    /// Text of the session's first user prompt. The default loads the whole
+    /// session; providers that can stop early override it.
+    fn first_user_message(&self, session_path: &str) -> Result<Option<String>, String> {
+        Ok(self
+            .load_messages(session_path)?
+            .iter()
+            .filter(|m| m.message_type == "user")
+            .find_map(|m| m.content.as_ref().and_then(user_prompt_text)))
+    }
+}
+
+lazy_static! {
+    static ref REGISTRY: Vec<Box<dyn Provider>> = vec![
+        Box::new(codex::CodexProvider),
+        Box::new(opencode::OpenCodeProvider),
+        Box::new(gemini::GeminiProvider),
+        Box::new(zed::ZedProvider),
+    ];
+}
+
+/// All file-backed providers, in display order
+pub fn registry() -> &'static [Box<dyn Provider>] {
+    REGISTRY.as_slice()
+}
+
+/// The registered provider with this id (e.g. `"codex"`)
+pub fn find(id: &str) -> Option<&'static dyn Provider> {
+    registry()
+        .iter()
+        .find(|provider| provider.id().as_str() == id)
+        .map(AsRef::as_ref)
+}
+
+/// Ids of every provider, Claude Code first
+pub fn all_provider_ids() -> Vec<String> {
+    std::iter::once(ProviderId::Claude)
+        .chain(registry().iter().map(|provider| provider.id()))
+        .map(|id| id.as_str().to_string())
+        .collect()
+}
+
+/// Prompt text of a user message's content: a plain string or the joined
+/// `text` items of a content array. System-injected text (starting with a
+/// tag, e.g. `<command-name>` or `<environment_context>`) doesn't count.
+pub(crate) fn user_prompt_text(content: &Value) -> Option<String> {
+    let text = match content {
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items
+            .iter()
+            .filter(|item| item.get("type").and_then(Value::as_str) == Some("text"))
+            .filter_map(|item| item.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => return None,
+    };
+    let trimmed = text.trim();
+    (!trimmed.is_empty() && !trimmed.starts_with('<')).then_some(text)
 }
 
 /// Detect all available providers on the system
 pub fn detect_providers() -> Vec<ProviderInfo> {
-    let mut providers = Vec::new();
+    claude::detect()
+        .into_iter()
+        .chain(registry().iter().filter_map(|provider| provider.detect()))
+        .collect()
+}
 
-    if let Some(info) = claude::detect() {
-        providers.push(info);
+/// Latest modification time of `root` and of entries at most `depth` levels
+/// below it. Only directory listings and metadata are read, so this stays
+/// cheap enough for `detect`; symlinks aren't followed.
+pub(crate) fn latest_mtime(root: &Path, depth: usize) -> Option<SystemTime> {
+    WalkDir::new(root)
+        .max_depth(depth)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.metadata().ok()?.modified().ok())
+        .max()
+}
+
+/// Format a `latest_mtime` result for `ProviderInfo::last_activity`
+pub(crate) fn activity_timestamp(mtime: Option<SystemTime>) -> Option<String> {
+    mtime.map(|t| DateTime::<Utc>::from(t).to_rfc3339())
+}
+
+/// Resolve a provider's data home: `$env_var` when it names an existing
+/// directory, otherwise `~/{home_relative}` if that exists.
+///
+/// The env override doesn't depend on the home directory, so it keeps working
+/// where `dirs::home_dir()` can't resolve one (headless/CI environments).
+pub(crate) fn resolve_base_path(
+    provider: &str,
+    env_var: &str,
+    home_relative: &str,
+) -> Option<String> {
+    resolve_base_path_from(
+        provider,
+        env_var,
+        std::env::var(env_var).ok(),
+        dirs::home_dir(),
+        home_relative,
+    )
+}
+
+fn resolve_base_path_from(
+    provider: &str,
+    env_var: &str,
+    env_value: Option<String>,
+    home: Option<PathBuf>,
+    home_relative: &str,
+) -> Option<String> {
+    if let Some(value) = env_value {
+        if Path::new(&value).exists() {
+            return Some(value);
+        }
+        log::debug!("{provider}: ${env_var} is set but {value} does not exist");
     }
-    if let Some(info) = codex::detect() {
-        providers.push(info);
+
+    let Some(home) = home else {
+        log::warn!(
+            "{provider}: home directory could not be resolved; set ${env_var} to locate its data"
+        );
+        return None;
+    };
+
+    let path = home.join(home_relative);
+    if path.exists() {
+        Some(path.to_string_lossy().to_string())
+    } else {
+        log::debug!("{provider}: not installed ({} not found)", path.display());
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::EnvVarGuard;
+    use serial_test::serial;
+    use tempfile::TempDir;
+
+    #[test]
+    fn message_window_counts_back_from_newest() {
+        let window = |offset, limit| MessageWindow { offset, limit };
+        assert_eq!(window(0, 3).range(10), 7..10);
+        assert_eq!(window(3, 3).range(10), 4..7);
+        assert_eq!(window(8, 5).range(10), 0..2);
+        assert_eq!(window(12, 5).range(10), 0..0);
+        assert_eq!(MessageWindow::ALL.range(10), 0..10);
     }
-    if let Some(info) = opencode::detect() {
-        providers.push(info);
+
+    #[test]
+    fn registry_ids_round_trip_through_find() {
+        assert_eq!(
+            all_provider_ids(),
+            vec!["claude", "codex", "opencode", "gemini", "zed"]
+        );
+        for provider in registry() {
+            let id = provider.id().as_str();
+            assert_eq!(find(id).map(|found| found.id().as_str()), Some(id));
+        }
+        assert!(find("claude").is_none());
+        assert!(find("cursor").is_none());
     }
 
-    providers
+    #[test]
+    fn resolve_base_path_honors_env_override_without_home_dir() {
+        let tmp = TempDir::new().expect("temp dir should be created");
+        let override_dir = tmp.path().to_string_lossy().to_string();
+
+        assert_eq!(
+            resolve_base_path_from(
+                "Codex",
+                "CODEX_HOME",
+                Some(override_dir.clone()),
+                None,
+                ".codex"
+            ),
+            Some(override_dir)
+        );
+        assert_eq!(
+            resolve_base_path_from("Codex", "CODEX_HOME", None, None, ".codex"),
+            None
+        );
+    }
+
+    #[test]
+    fn resolve_base_path_falls_back_to_home_dir() {
+        let tmp = TempDir::new().expect("temp dir should be created");
+        std::fs::create_dir_all(tmp.path().join(".gemini")).expect("dir should be created");
+        let missing_override = tmp.path().join("missing").to_string_lossy().to_string();
+
+        let resolved = resolve_base_path_from(
+            "Gemini CLI",
+            "GEMINI_HOME",
+            Some(missing_override),
+            Some(tmp.path().to_path_buf()),
+            ".gemini",
+        );
+        assert_eq!(
+            resolved,
+            Some(tmp.path().join(".gemini").to_string_lossy().to_string())
+        );
+        assert_eq!(
+            resolve_base_path_from(
+                "Gemini CLI",
+                "GEMINI_HOME",
+                None,
+                Some(tmp.path().to_path_buf()),
+                ".gemini-other"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn detect_reports_later_last_activity_for_recently_touched_provider() {
+        let tmp = TempDir::new().expect("temp dir should be created");
+        let codex_home = tmp.path().join("codex-home");
+        let codex_day = codex_home
+            .join("sessions")
+            .join("2025")
+            .join("07")
+            .join("01");
+        std::fs::create_dir_all(&codex_day).expect("codex sessions dir should be created");
+        std::fs::write(codex_day.join("rollout-2025-07-01T10-00-00-a.jsonl"), "{}")
+            .expect("rollout should be written");
+        let gemini_home = tmp.path().join("gemini-home");
+        let chats_dir = gemini_home.join("tmp").join("abc123").join("chats");
+        std::fs::create_dir_all(&chats_dir).expect("chats dir should be created");
+        let chat = chats_dir.join("session-2025-07-01T10-00-sess1.json");
+        std::fs::write(&chat, "{}").expect("chat should be written");
+        // Everything was just created; push the Gemini chat an hour ahead
+        let later = std::time::SystemTime::from(Utc::now() + chrono::Duration::hours(1));
+        std::fs::File::options()
+            .write(true)
+            .open(&chat)
+            .and_then(|f| f.set_modified(later))
+            .expect("mtime should be set");
+        let _codex_guard = EnvVarGuard::set("CODEX_HOME", &codex_home);
+        let _gemini_guard = EnvVarGuard::set("GEMINI_HOME", &gemini_home);
+
+        let activity = |info: Option<ProviderInfo>| {
+            info.and_then(|i| i.last_activity)
+                .and_then(|ts| crate::utils::parse_rfc3339_utc(&ts))
+                .expect("last_activity should be a timestamp")
+        };
+        let codex = activity(crate::providers::codex::detect());
+        let gemini = activity(crate::providers::gemini::detect());
+        assert!(
+            gemini > codex,
+            "gemini {gemini} should be after codex {codex}"
+        );
+    }
 }
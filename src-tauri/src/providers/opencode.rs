@@ -1,13 +1,38 @@
-use super::ProviderInfo;
+use super::{MessageWindow, ProjectScan, Provider, ProviderId, ProviderInfo};
 use crate::models::{ClaudeMessage, ClaudeProject, ClaudeSession, TokenUsage};
-use crate::utils::{is_safe_storage_id, search_json_value_case_insensitive};
+use crate::utils::{
+    git_info_for_path, is_safe_storage_id, parse_rfc3339_utc, uri_to_path, SearchPattern,
+    SearchScope,
+};
 use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
 use serde_json::Value;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+/// Epoch values below this are taken as seconds, at or above as milliseconds.
+/// 10^12 ms is September 2001, while 10^12 s is tens of thousands of years out,
+/// so the two ranges never overlap for real timestamps.
+const EPOCH_SECONDS_THRESHOLD: u64 = 1_000_000_000_000;
+
+/// Normalize an epoch timestamp in seconds or milliseconds to milliseconds
+/// (see `EPOCH_SECONDS_THRESHOLD`)
+fn epoch_to_millis(value: u64) -> u64 {
+    if value < EPOCH_SECONDS_THRESHOLD {
+        value.saturating_mul(1000)
+    } else {
+        value
+    }
+}
 
-/// Convert epoch milliseconds to RFC 3339 string
-fn epoch_ms_to_rfc3339(ms: u64) -> String {
+/// Convert an epoch timestamp to an RFC 3339 string, accepting either seconds
+/// or milliseconds (see `EPOCH_SECONDS_THRESHOLD`)
+fn epoch_to_rfc3339(value: u64) -> String {
+    let ms = epoch_to_millis(value);
     #[allow(clippy::cast_possible_wrap)]
     let secs = (ms / 1000) as i64;
     let nsecs = ((ms % 1000) * 1_000_000) as u32;
@@ -27,41 +52,117 @@ pub fn detect() -> Option<ProviderInfo> {
         display_name: "OpenCode".to_string(),
         base_path: base_path.clone(),
         is_available: storage_path.exists() && storage_path.is_dir(),
+        // New sessions land in session/{project}/, new messages add files to
+        // message/{session}/
+        last_activity: super::activity_timestamp(
+            get_base_paths()
+                .iter()
+                .map(|base| Path::new(base).join("storage"))
+                .flat_map(|storage| {
+                    [
+                        super::latest_mtime(&storage.join("session"), 2),
+                        super::latest_mtime(&storage.join("message"), 1),
+                    ]
+                })
+                .flatten()
+                .max(),
+        ),
     })
 }
 
-/// Get the `OpenCode` base path
+/// Get the `OpenCode` base path (the first of [`get_base_paths`])
 pub fn get_base_path() -> Option<String> {
-    // Check $OPENCODE_HOME first
+    get_base_paths().into_iter().next()
+}
+
+/// Get every existing `OpenCode` data home, in priority order:
+/// `$OPENCODE_HOME`, `$XDG_DATA_HOME/opencode`, then `~/.local/share/opencode`.
+/// Candidates resolving to the same directory are listed once.
+pub fn get_base_paths() -> Vec<String> {
+    let mut candidates = Vec::new();
     if let Ok(home) = std::env::var("OPENCODE_HOME") {
-        let path = PathBuf::from(&home);
-        if path.exists() {
-            return Some(home);
-        }
+        candidates.push(PathBuf::from(home));
     }
-
-    // XDG data directory
     if let Ok(xdg_data) = std::env::var("XDG_DATA_HOME") {
-        let path = PathBuf::from(&xdg_data).join("opencode");
-        if path.exists() {
-            return Some(path.to_string_lossy().to_string());
-        }
+        candidates.push(PathBuf::from(xdg_data).join("opencode"));
     }
-
-    // Default: ~/.local/share/opencode
-    let home = dirs::home_dir()?;
-    let opencode_path = home.join(".local").join("share").join("opencode");
-    if opencode_path.exists() {
-        Some(opencode_path.to_string_lossy().to_string())
+    if let Some(home) = dirs::home_dir() {
+        candidates.push(home.join(".local").join("share").join("opencode"));
     } else {
-        None
+        log::warn!(
+            "OpenCode: home directory could not be resolved; \
+             set $OPENCODE_HOME or $XDG_DATA_HOME to locate its data"
+        );
+    }
+
+    let mut seen = Vec::new();
+    let mut paths = Vec::new();
+    for candidate in candidates {
+        if !candidate.exists() {
+            continue;
+        }
+        let canonical = fs::canonicalize(&candidate).unwrap_or_else(|_| candidate.clone());
+        if seen.contains(&canonical) {
+            continue;
+        }
+        seen.push(canonical);
+        paths.push(candidate.to_string_lossy().to_string());
     }
+    paths
+}
+
+/// Find the first data home whose `storage/` contains `storage_relative`,
+/// falling back to the primary home so callers can report what's missing.
+pub fn find_base_path_containing(storage_relative: &Path) -> Option<String> {
+    let bases = get_base_paths();
+    bases
+        .iter()
+        .find(|base| {
+            Path::new(base.as_str())
+                .join("storage")
+                .join(storage_relative)
+                .exists()
+        })
+        .or_else(|| bases.first())
+        .cloned()
 }
 
-/// Scan `OpenCode` projects
+/// Scan `OpenCode` projects across all data homes. A project id found in
+/// more than one home is listed once, from the higher-priority home.
 pub fn scan_projects() -> Result<Vec<ClaudeProject>, String> {
-    let base_path = get_base_path().ok_or_else(|| "OpenCode not found".to_string())?;
-    let storage_path = Path::new(&base_path).join("storage");
+    scan_projects_with_warnings().map(|scan| scan.projects)
+}
+
+/// [`scan_projects`], also reporting projects whose session directory is
+/// missing, which `scan_all_projects` would otherwise drop as empty
+pub fn scan_projects_with_warnings() -> Result<ProjectScan, String> {
+    let base_paths = get_base_paths();
+    if base_paths.is_empty() {
+        return Err("OpenCode not found".to_string());
+    }
+
+    let mut scan = ProjectScan::default();
+    for base_path in &base_paths {
+        for project in scan_projects_in(base_path, &mut scan.warnings)? {
+            if !scan.projects.iter().any(|p| p.path == project.path) {
+                scan.projects.push(project);
+            }
+        }
+    }
+
+    scan.projects
+        .sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
+    Ok(scan)
+}
+
+/// Scan the projects of a single `OpenCode` data home. A project whose
+/// session directory is missing has no sessions and is reported in
+/// `warnings`.
+fn scan_projects_in(
+    base_path: &str,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<ClaudeProject>, String> {
+    let storage_path = Path::new(base_path).join("storage");
     let projects_dir = storage_path.join("project");
 
     if !projects_dir.exists() {
@@ -86,7 +187,7 @@ pub fn scan_projects() -> Result<Vec<ClaudeProject>, String> {
             Err(_) => continue,
         };
 
-        let val: Value = match serde_json::from_str(&content) {
+        let val: Value = match parse_storage_json(&content) {
             Ok(v) => v,
             Err(_) => continue,
         };
@@ -97,12 +198,14 @@ pub fn scan_projects() -> Result<Vec<ClaudeProject>, String> {
             .unwrap_or("")
             .to_string();
 
-        // Real field is "worktree", not "path"
+        // Real field is "worktree"; older files use "path". Either may be a
+        // file:// URI rather than a plain path.
         let project_path = val
             .get("worktree")
+            .or_else(|| val.get("path"))
             .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
+            .map(uri_to_path)
+            .unwrap_or_default();
 
         // No "name" field — derive from last segment of "worktree"
         let project_name = Path::new(&project_path)
@@ -115,24 +218,13 @@ pub fn scan_projects() -> Result<Vec<ClaudeProject>, String> {
             continue;
         }
 
-        // Count sessions
         let sessions_dir = storage_path.join("session").join(&project_id);
-        let session_count = if sessions_dir.exists() {
-            fs::read_dir(&sessions_dir)
-                .map(|entries| {
-                    entries
-                        .flatten()
-                        .filter(|e| {
-                            if e.file_type().map_or(true, |ft| ft.is_symlink()) {
-                                return false;
-                            }
-                            e.path().extension().and_then(|ext| ext.to_str()) == Some("json")
-                        })
-                        .count()
-                })
-                .unwrap_or(0)
-        } else {
-            0
+        let (session_count, message_count) = match count_sessions(&storage_path, &sessions_dir) {
+            Ok(counts) => counts,
+            Err(e) => {
+                warnings.push(e);
+                (0, 0)
+            }
         };
 
         let last_modified =
@@ -141,9 +233,58 @@ pub fn scan_projects() -> Result<Vec<ClaudeProject>, String> {
         projects.push(ClaudeProject {
             name: project_name,
             path: format!("opencode://{project_id}"),
+            path_exists: Path::new(&project_path).is_dir(),
+            git_info: git_info_for_path(&project_path),
             actual_path: project_path,
             session_count,
-            message_count: 0,
+            message_count,
+            last_modified,
+            provider: Some("opencode".to_string()),
+        });
+    }
+
+    Ok(projects)
+}
+
+/// Synthesize projects for session directories whose `project/{id}.json` is
+/// missing from every data home, so their sessions stay reachable. The
+/// project's path and name are unknown; the id stands in for the name.
+pub fn reconstruct_projects() -> Result<Vec<ClaudeProject>, String> {
+    let base_paths = get_base_paths();
+    if base_paths.is_empty() {
+        return Err("OpenCode not found".to_string());
+    }
+
+    let known_ids: HashSet<String> = base_paths
+        .iter()
+        .flat_map(|base| list_json_file_stems(&Path::new(base).join("storage").join("project")))
+        .collect();
+
+    let mut projects = Vec::new();
+    for (project_id, sessions_dir) in session_project_dirs() {
+        if known_ids.contains(&project_id) {
+            continue;
+        }
+        // session_project_dirs yields `{storage}/session/{project_id}`
+        let Some(storage_path) = sessions_dir.parent().and_then(Path::parent) else {
+            continue;
+        };
+        let Ok((session_count, message_count)) = count_sessions(storage_path, &sessions_dir) else {
+            continue;
+        };
+        if session_count == 0 {
+            continue;
+        }
+        let last_modified =
+            get_latest_session_time(&sessions_dir).unwrap_or_else(|| Utc::now().to_rfc3339());
+
+        projects.push(ClaudeProject {
+            name: project_id.clone(),
+            path: format!("opencode://{project_id}"),
+            actual_path: String::new(),
+            path_exists: false,
+            session_count,
+            message_count,
             last_modified,
             git_info: None,
             provider: Some("opencode".to_string()),
@@ -159,9 +300,6 @@ pub fn load_sessions(
     project_path: &str,
     _exclude_sidechain: bool,
 ) -> Result<Vec<ClaudeSession>, String> {
-    let base_path = get_base_path().ok_or_else(|| "OpenCode not found".to_string())?;
-    let storage_path = Path::new(&base_path).join("storage");
-
     let project_id = project_path
         .strip_prefix("opencode://")
         .unwrap_or(project_path);
@@ -169,9 +307,18 @@ pub fn load_sessions(
         return Err(format!("Invalid OpenCode project path: {project_path}"));
     }
 
+    let base_path = find_base_path_containing(&Path::new("session").join(project_id))
+        .ok_or_else(|| "OpenCode not found".to_string())?;
+    let storage_path = Path::new(&base_path).join("storage");
+
+    // A missing directory means broken storage; an existing empty one is a
+    // project that genuinely has no sessions.
     let sessions_dir = storage_path.join("session").join(project_id);
-    if !sessions_dir.exists() {
-        return Ok(vec![]);
+    if !sessions_dir.is_dir() {
+        return Err(format!(
+            "OpenCode session directory missing for project {project_id}: {}",
+            sessions_dir.display()
+        ));
     }
 
     let mut sessions = Vec::new();
@@ -193,7 +340,7 @@ pub fn load_sessions(
             Err(_) => continue,
         };
 
-        let val: Value = match serde_json::from_str(&content) {
+        let val: Value = match parse_storage_json(&content) {
             Ok(v) => v,
             Err(_) => continue,
         };
@@ -210,37 +357,20 @@ pub fn load_sessions(
         let created_at = time_obj
             .and_then(|t| t.get("created"))
             .and_then(Value::as_u64)
-            .map(epoch_ms_to_rfc3339)
+            .map(epoch_to_rfc3339)
             .unwrap_or_default();
         let updated_at = time_obj
             .and_then(|t| t.get("updated"))
             .and_then(Value::as_u64)
-            .map(epoch_ms_to_rfc3339)
+            .map(epoch_to_rfc3339)
             .unwrap_or_else(|| created_at.clone());
 
         if session_id.is_empty() || !is_safe_storage_id(&session_id) {
             continue;
         }
 
-        // Count messages
         let messages_dir = storage_path.join("message").join(&session_id);
-        let message_count = if messages_dir.exists() {
-            fs::read_dir(&messages_dir)
-                .map(|entries| {
-                    entries
-                        .flatten()
-                        .filter(|e| {
-                            if e.file_type().map_or(true, |ft| ft.is_symlink()) {
-                                return false;
-                            }
-                            e.path().extension().and_then(|ext| ext.to_str()) == Some("json")
-                        })
-                        .count()
-                })
-                .unwrap_or(0)
-        } else {
-            0
-        };
+        let message_scan = scan_session_messages(&messages_dir, &storage_path.join("part"));
 
         sessions.push(ClaudeSession {
             session_id: format!("opencode://{session_id}"),
@@ -250,14 +380,15 @@ pub fn load_sessions(
                 path.file_stem().unwrap_or_default().to_string_lossy()
             ),
             project_name: String::new(),
-            message_count,
+            message_count: message_scan.count,
             first_message_time: created_at.clone(),
             last_message_time: updated_at.clone(),
             last_modified: updated_at,
-            has_tool_use: false,
-            has_errors: false,
+            has_tool_use: message_scan.has_tool_use,
+            has_errors: message_scan.has_errors,
             summary: title,
             provider: Some("opencode".to_string()),
+            data: session_metadata(&val, message_scan.model_ids),
         });
     }
 
@@ -265,12 +396,9 @@ pub fn load_sessions(
     Ok(sessions)
 }
 
-/// Load messages for an `OpenCode` session
-pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
-    let base_path = get_base_path().ok_or_else(|| "OpenCode not found".to_string())?;
-    let storage_path = Path::new(&base_path).join("storage");
-
-    // Extract session info from virtual path "opencode://{project_id}/{session_id}"
+/// Split a virtual session path `opencode://{project_id}/{session_id}` into
+/// its validated ids
+fn parse_session_path(session_path: &str) -> Result<(&str, &str), String> {
     let path_part = session_path
         .strip_prefix("opencode://")
         .unwrap_or(session_path);
@@ -286,14 +414,146 @@ pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
     if !is_safe_storage_id(session_id) {
         return Err(format!("Invalid session_id in path: {session_path}"));
     }
+    Ok((project_id, session_id))
+}
+
+/// Virtual path (`opencode://{project_id}/{session_id}`) of the session with
+/// this id, searching every data home; `None` if it isn't found or the id is
+/// not a valid storage id
+pub fn find_session(session_id: &str) -> Option<String> {
+    if !is_safe_storage_id(session_id) {
+        return None;
+    }
+    session_project_dirs()
+        .into_iter()
+        .find(|(_, sessions_dir)| sessions_dir.join(format!("{session_id}.json")).is_file())
+        .map(|(project_id, _)| format!("opencode://{project_id}/{session_id}"))
+}
 
-    // Read message files
+/// Text of the earliest user message in a session, or `None` if no user
+/// message has text. Only message metadata and that message's parts are
+/// read, not the whole session.
+pub fn first_user_message(session_path: &str) -> Result<Option<String>, String> {
+    let (_, session_id) = parse_session_path(session_path)?;
+    let base_path = find_base_path_containing(&Path::new("message").join(session_id))
+        .ok_or_else(|| "OpenCode not found".to_string())?;
+    let storage_path = Path::new(&base_path).join("storage");
     let messages_dir = storage_path.join("message").join(session_id);
-    if !messages_dir.exists() {
-        return Ok(vec![]);
+
+    let mut msg_files: Vec<PathBuf> = fs::read_dir(&messages_dir)
+        .map_err(|e| format!("Failed to read OpenCode messages for {session_id}: {e}"))?
+        .flatten()
+        .filter(|e| !e.file_type().map_or(true, |ft| ft.is_symlink()))
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("json"))
+        .collect();
+    msg_files.sort();
+
+    let mut user_messages: Vec<(Option<u64>, String)> = msg_files
+        .iter()
+        .filter_map(|path| parse_storage_json(&fs::read_to_string(path).ok()?).ok())
+        .filter(|val| val.get("role").and_then(Value::as_str) == Some("user"))
+        .filter_map(|val| {
+            let id = val.get("id").and_then(Value::as_str)?;
+            let created_ms = val
+                .get("time")
+                .and_then(|t| t.get("created"))
+                .and_then(Value::as_u64)
+                .map(epoch_to_millis);
+            Some((created_ms, id.to_string()))
+        })
+        .filter(|(_, id)| is_safe_storage_id(id))
+        .collect();
+    user_messages.sort_by_key(|(created_ms, _)| created_ms.unwrap_or(u64::MAX));
+
+    for (_, msg_id) in user_messages {
+        let parts_dir = storage_path.join("part").join(&msg_id);
+        let Ok(parts) = read_message_parts(&parts_dir) else {
+            continue;
+        };
+        let text = parts
+            .iter()
+            .filter(|part| part.get("type").and_then(Value::as_str) == Some("text"))
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n");
+        if !text.trim().is_empty() {
+            return Ok(Some(text));
+        }
+    }
+    Ok(None)
+}
+
+/// Load messages for an `OpenCode` session
+pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
+    load_messages_page(session_path, MessageWindow::ALL).map(|(messages, _)| messages)
+}
+
+/// One [`MessageWindow`] of a session and the session's total message count.
+/// Every message file's metadata is read to order the session, but parts
+/// are only read for the messages in the window.
+pub fn load_messages_page(
+    session_path: &str,
+    window: MessageWindow,
+) -> Result<(Vec<ClaudeMessage>, usize), String> {
+    let (storage_path, session_id, mut ordered) = read_ordered_metadata(session_path)?;
+    let total = ordered.len();
+    let range = window.range(total);
+    let next_created = ordered
+        .get(range.end)
+        .and_then(|(created_ms, _)| *created_ms);
+    let window_metadata: Vec<_> = ordered.drain(range).collect();
+    let messages = build_messages(&storage_path, session_id, window_metadata, next_created)?;
+    Ok((messages, total))
+}
+
+/// Hand the session's messages to `on_batch`, oldest first, in batches of at
+/// most `batch_size`. The metadata is read and ordered once up front, so a
+/// message written mid-stream can't shift later batches; parts are read per
+/// batch.
+pub fn load_message_batches(
+    session_path: &str,
+    batch_size: usize,
+    on_batch: &mut dyn FnMut(Vec<ClaudeMessage>) -> Result<(), String>,
+) -> Result<(), String> {
+    let (storage_path, session_id, ordered) = read_ordered_metadata(session_path)?;
+    let batch_size = batch_size.max(1);
+    let mut ordered = ordered.into_iter().peekable();
+    while ordered.peek().is_some() {
+        let batch: Vec<_> = ordered.by_ref().take(batch_size).collect();
+        let next_created = ordered.peek().and_then(|(created_ms, _)| *created_ms);
+        on_batch(build_messages(
+            &storage_path,
+            session_id,
+            batch,
+            next_created,
+        )?)?;
     }
+    Ok(())
+}
+
+/// Message metadata paired with its creation time (epoch ms)
+type TimedMetadata = Vec<(Option<u64>, Value)>;
+
+/// The storage dir, session id and every message's metadata of a session,
+/// in creation order
+fn read_ordered_metadata(session_path: &str) -> Result<(PathBuf, &str, TimedMetadata), String> {
+    // Extract session info from virtual path "opencode://{project_id}/{session_id}"
+    let (_, session_id) = parse_session_path(session_path)?;
+
+    let base_path = find_base_path_containing(&Path::new("message").join(session_id))
+        .ok_or_else(|| "OpenCode not found".to_string())?;
+    let storage_path = Path::new(&base_path).join("storage");
 
-    let mut messages = Vec::new();
+    // Read message files. A missing directory is reported as an error so it
+    // can't be mistaken for a session that genuinely has no messages.
+    let messages_dir = storage_path.join("message").join(session_id);
+    if !messages_dir.is_dir() {
+        return Err(format!(
+            "OpenCode message directory missing for session {session_id}: {}",
+            messages_dir.display()
+        ));
+    }
 
     // Collect and sort message files
     let mut msg_files: Vec<PathBuf> = fs::read_dir(&messages_dir)
@@ -309,152 +569,243 @@ pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
         .collect();
     msg_files.sort();
 
-    for msg_path in &msg_files {
-        let content = match fs::read_to_string(msg_path) {
-            Ok(c) => c,
-            Err(_) => continue,
-        };
+    let metadata: TimedMetadata = msg_files
+        .iter()
+        .filter_map(|msg_path| parse_storage_json(&fs::read_to_string(msg_path).ok()?).ok())
+        .filter(|val| {
+            val.get("id")
+                .and_then(Value::as_str)
+                .is_some_and(|id| !id.is_empty() && is_safe_storage_id(id))
+        })
+        .map(|val| {
+            // Timestamp is epoch ms under val["time"]["created"]
+            let created_ms = val
+                .get("time")
+                .and_then(|t| t.get("created"))
+                .and_then(Value::as_u64)
+                .map(epoch_to_millis);
+            (created_ms, val)
+        })
+        .collect();
 
-        let val: Value = match serde_json::from_str(&content) {
-            Ok(v) => v,
-            Err(_) => continue,
-        };
+    Ok((storage_path, session_id, order_by_creation_time(metadata)))
+}
 
-        let msg_id = val
-            .get("id")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-        let role = val.get("role").and_then(|v| v.as_str()).unwrap_or("user");
+/// Build consecutive messages from their ordered metadata. `next_created` is
+/// the creation time of the message after the last one, if any.
+fn build_messages(
+    storage_path: &Path,
+    session_id: &str,
+    metadata: TimedMetadata,
+    next_created: Option<u64>,
+) -> Result<Vec<ClaudeMessage>, String> {
+    let mut messages = Vec::with_capacity(metadata.len());
+    for (created_ms, val) in metadata {
+        let message = build_message(storage_path, session_id, created_ms, &val)?;
+        messages.push((created_ms, message));
+    }
+    fill_durations_from_next_message(&mut messages, next_created);
+    Ok(messages.into_iter().map(|(_, message)| message).collect())
+}
 
-        // Timestamp is epoch ms under val["time"]["created"]
-        let created_at = val
-            .get("time")
-            .and_then(|t| t.get("created"))
-            .and_then(Value::as_u64)
-            .map(epoch_ms_to_rfc3339)
-            .unwrap_or_default();
+/// Convert one message's metadata and its parts into a [`ClaudeMessage`]
+fn build_message(
+    storage_path: &Path,
+    session_id: &str,
+    created_ms: Option<u64>,
+    val: &Value,
+) -> Result<ClaudeMessage, String> {
+    let msg_id = val
+        .get("id")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+    let role = val.get("role").and_then(|v| v.as_str()).unwrap_or("user");
+    let created_at = created_ms.map(epoch_to_rfc3339).unwrap_or_default();
+
+    // Real field is "modelID", not "model"
+    let model = val
+        .get("modelID")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+
+    // parentID maps to parent_uuid
+    let parent_uuid = val
+        .get("parentID")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+
+    // Extract usage from val["tokens"] with fields "input" and "output"
+    let usage = val.get("tokens").map(|t| TokenUsage {
+        input_tokens: token_count(t.get("input")),
+        output_tokens: token_count(t.get("output")),
+        cache_creation_input_tokens: None,
+        cache_read_input_tokens: None,
+        service_tier: None,
+    });
+
+    // Extract cost from val["cost"]
+    let cost_usd = val.get("cost").and_then(Value::as_f64);
+
+    // Read parts for this message
+    let parts_dir = storage_path.join("part").join(&msg_id);
+    let part_values = if parts_dir.exists() {
+        read_message_parts(&parts_dir)?
+    } else {
+        Vec::new()
+    };
 
-        // Real field is "modelID", not "model"
-        let model = val
-            .get("modelID")
-            .and_then(|v| v.as_str())
-            .map(String::from);
+    let parts = process_parts(&part_values);
+    let duration_ms = if role == "assistant" {
+        parts_duration_ms(&part_values)
+    } else {
+        None
+    };
 
-        // parentID maps to parent_uuid
-        let parent_uuid = val
-            .get("parentID")
-            .and_then(|v| v.as_str())
-            .map(String::from);
-
-        // Extract usage from val["tokens"] with fields "input" and "output"
-        let usage = val.get("tokens").map(|t| TokenUsage {
-            input_tokens: t.get("input").and_then(Value::as_u64).map(|v| v as u32),
-            output_tokens: t.get("output").and_then(Value::as_u64).map(|v| v as u32),
-            cache_creation_input_tokens: None,
-            cache_read_input_tokens: None,
-            service_tier: None,
-        });
+    // Use message-level usage/cost if present, otherwise fall back to parts-derived,
+    // and estimate the cost from the model's pricing when neither has one
+    let final_usage = usage.or(parts.usage);
+    let final_cost = cost_usd
+        .or(parts.cost_usd)
+        .or_else(|| crate::pricing::estimate_cost(model.as_deref()?, final_usage.as_ref()?));
+
+    let message_type = match role {
+        "assistant" => "assistant",
+        "system" => "system",
+        _ => "user",
+    };
 
-        // Extract cost from val["cost"]
-        let cost_usd = val.get("cost").and_then(Value::as_f64);
+    Ok(ClaudeMessage {
+        uuid: msg_id,
+        parent_uuid,
+        session_id: session_id.to_string(),
+        timestamp: created_at,
+        message_type: message_type.to_string(),
+        content: parts.content,
+        project_name: None,
+        tool_use: None,
+        tool_use_result: None,
+        is_sidechain: None,
+        usage: final_usage,
+        role: Some(role.to_string()),
+        model,
+        stop_reason: None,
+        cost_usd: final_cost,
+        duration_ms,
+        message_id: None,
+        snapshot: None,
+        is_snapshot_update: None,
+        data: None,
+        tool_use_id: None,
+        parent_tool_use_id: None,
+        operation: None,
+        subtype: None,
+        level: None,
+        hook_count: None,
+        hook_infos: None,
+        stop_reason_system: None,
+        prevented_continuation: None,
+        compact_metadata: parts.compact_metadata,
+        microcompact_metadata: None,
+        provider: Some("opencode".to_string()),
+        match_locations: None,
+        session_path: None,
+        project_path: None,
+    })
+}
+
+/// Order messages by creation time. Message ids aren't guaranteed to sort
+/// chronologically, so filename order is only the tie-breaker: the sort is
+/// stable, and a message without a timestamp takes the time of the message
+/// before it so it stays where the filenames put it. Each message keeps its
+/// own (possibly missing) creation time.
+fn order_by_creation_time<T>(messages: Vec<(Option<u64>, T)>) -> Vec<(Option<u64>, T)> {
+    let mut last_seen = 0;
+    let mut keyed: Vec<(u64, Option<u64>, T)> = messages
+        .into_iter()
+        .map(|(created_ms, message)| {
+            let key = created_ms.unwrap_or(last_seen);
+            last_seen = key;
+            (key, created_ms, message)
+        })
+        .collect();
+    keyed.sort_by_key(|(key, _, _)| *key);
+    keyed
+        .into_iter()
+        .map(|(_, created_ms, message)| (created_ms, message))
+        .collect()
+}
+
+/// How long an assistant message took, from the earliest to the latest
+/// timestamp on its parts. Text and reasoning parts carry `time.start` /
+/// `time.end`, tool parts carry them under `state.time`. `None` unless the
+/// span is positive.
+fn parts_duration_ms(parts: &[Value]) -> Option<u64> {
+    let times: Vec<u64> = parts
+        .iter()
+        .flat_map(|part| {
+            [
+                part.get("time"),
+                part.get("state").and_then(|s| s.get("time")),
+            ]
+        })
+        .flatten()
+        .flat_map(|time| [time.get("start"), time.get("end")])
+        .flatten()
+        .filter_map(Value::as_u64)
+        .map(epoch_to_millis)
+        .collect();
+    let first = times.iter().min()?;
+    let last = times.iter().max()?;
+    Some(last - first).filter(|&duration| duration > 0)
+}
 
-        if msg_id.is_empty() {
+/// Fallback for assistant messages whose parts carry no usable timestamps:
+/// the gap between the message's creation and the next message's. This
+/// overstates the response time by however long the user took to reply, so
+/// it is only used when the parts say nothing. `after` is the creation time
+/// of the message following the last one, if any.
+fn fill_durations_from_next_message(
+    messages: &mut [(Option<u64>, ClaudeMessage)],
+    after: Option<u64>,
+) {
+    for i in 0..messages.len() {
+        let next_created = messages
+            .get(i + 1)
+            .map_or(after, |(created_ms, _)| *created_ms);
+        let (created_ms, message) = &mut messages[i];
+        if message.duration_ms.is_some() || message.message_type != "assistant" {
             continue;
         }
-        if !is_safe_storage_id(&msg_id) {
+        let (Some(created), Some(next_created)) = (*created_ms, next_created) else {
             continue;
-        }
-
-        // Read parts for this message
-        let parts_dir = storage_path.join("part").join(&msg_id);
-        let part_values = if parts_dir.exists() {
-            read_message_parts(&parts_dir)?
-        } else {
-            Vec::new()
         };
-
-        let (content_value, parts_usage, parts_cost) = process_parts(&part_values);
-
-        // Use message-level usage/cost if present, otherwise fall back to parts-derived
-        let final_usage = usage.or(parts_usage);
-        let final_cost = cost_usd.or(parts_cost);
-
-        let message_type = match role {
-            "assistant" => "assistant",
-            "system" => "system",
-            _ => "user",
-        };
-
-        messages.push(ClaudeMessage {
-            uuid: msg_id,
-            parent_uuid,
-            session_id: session_id.to_string(),
-            timestamp: created_at,
-            message_type: message_type.to_string(),
-            content: content_value,
-            project_name: None,
-            tool_use: None,
-            tool_use_result: None,
-            is_sidechain: None,
-            usage: final_usage,
-            role: Some(role.to_string()),
-            model,
-            stop_reason: None,
-            cost_usd: final_cost,
-            duration_ms: None,
-            message_id: None,
-            snapshot: None,
-            is_snapshot_update: None,
-            data: None,
-            tool_use_id: None,
-            parent_tool_use_id: None,
-            operation: None,
-            subtype: None,
-            level: None,
-            hook_count: None,
-            hook_infos: None,
-            stop_reason_system: None,
-            prevented_continuation: None,
-            compact_metadata: None,
-            microcompact_metadata: None,
-            provider: Some("opencode".to_string()),
-        });
+        if next_created > created {
+            message.duration_ms = Some(next_created - created);
+        }
     }
-
-    Ok(messages)
 }
 
 /// Search `OpenCode` sessions for a query string
 pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
-    let base_path = get_base_path().ok_or_else(|| "OpenCode not found".to_string())?;
-    let storage_path = Path::new(&base_path).join("storage");
-    let session_root = storage_path.join("session");
+    search_matching(&SearchPattern::plain(query), &SearchScope::default(), limit)
+}
 
-    if !session_root.exists() {
-        return Ok(vec![]);
+/// [`search`] with an explicit [`SearchPattern`], e.g. a regex, keeping only
+/// messages in `scope`
+pub fn search_matching(
+    pattern: &SearchPattern,
+    scope: &SearchScope,
+    limit: usize,
+) -> Result<Vec<ClaudeMessage>, String> {
+    if get_base_paths().is_empty() {
+        return Err("OpenCode not found".to_string());
     }
 
-    let query_lower = query.to_lowercase();
     let mut results = Vec::new();
 
-    for project_entry in fs::read_dir(&session_root)
-        .map_err(|e| e.to_string())?
-        .flatten()
-    {
-        if project_entry.file_type().map_or(true, |ft| ft.is_symlink()) {
-            continue;
-        }
-        let project_id = project_entry.file_name().to_string_lossy().to_string();
-        if !is_safe_storage_id(&project_id) {
-            continue;
-        }
-
-        for session_entry in fs::read_dir(project_entry.path())
-            .into_iter()
-            .flatten()
-            .flatten()
-        {
+    for (project_id, project_dir) in session_project_dirs() {
+        for session_entry in fs::read_dir(&project_dir).into_iter().flatten().flatten() {
             if session_entry.file_type().map_or(true, |ft| ft.is_symlink()) {
                 continue;
             }
@@ -471,15 +822,22 @@ pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
             let virtual_path = format!("opencode://{project_id}/{session_id}");
 
             if let Ok(messages) = load_messages(&virtual_path) {
-                for msg in messages {
+                for mut msg in messages {
                     if results.len() >= limit {
                         return Ok(results);
                     }
 
-                    if let Some(content) = &msg.content {
-                        if search_json_value_case_insensitive(content, &query_lower) {
-                            results.push(msg);
-                        }
+                    if !scope.includes(&msg) {
+                        continue;
+                    }
+                    if msg
+                        .content
+                        .as_ref()
+                        .is_some_and(|content| pattern.matches_content(content))
+                    {
+                        msg.session_path = Some(virtual_path.clone());
+                        msg.project_path = Some(format!("opencode://{project_id}"));
+                        results.push(msg);
                     }
                 }
             }
@@ -489,68 +847,444 @@ pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
     Ok(results)
 }
 
-// ============================================================================
-// Internal helpers
-// ============================================================================
+/// List `OpenCode` session paths whose `last_modified` is before `cutoff`.
+/// Sessions without a parseable timestamp are never listed.
+pub fn sessions_modified_before(cutoff: DateTime<Utc>) -> Result<Vec<String>, String> {
+    if get_base_paths().is_empty() {
+        return Err("OpenCode not found".to_string());
+    }
 
-fn get_latest_session_time(sessions_dir: &Path) -> Option<String> {
-    if !sessions_dir.exists() {
-        return None;
+    let mut stale = Vec::new();
+    for (project_id, _) in session_project_dirs() {
+        let sessions = match load_sessions(&format!("opencode://{project_id}"), false) {
+            Ok(sessions) => sessions,
+            Err(e) => {
+                log::warn!("Skipping OpenCode project while pruning: {e}");
+                continue;
+            }
+        };
+        stale.extend(
+            sessions
+                .into_iter()
+                .filter(|s| parse_rfc3339_utc(&s.last_modified).is_some_and(|ts| ts < cutoff))
+                .map(|s| s.file_path),
+        );
     }
 
-    let mut latest: Option<String> = None;
+    stale.sort();
+    Ok(stale)
+}
 
-    for entry in fs::read_dir(sessions_dir).ok()?.flatten() {
-        if entry.file_type().map_or(true, |ft| ft.is_symlink()) {
-            continue;
-        }
-        let path = entry.path();
-        if path.extension().and_then(|e| e.to_str()) != Some("json") {
-            continue;
-        }
+/// Move an `OpenCode` session (session file, messages and their parts) under
+/// `trash_dir`, keeping the `storage/...` layout so it can be restored by hand.
+pub fn trash_session(session_path: &str, trash_dir: &Path) -> Result<(), String> {
+    let (project_id, session_id) = session_path
+        .strip_prefix("opencode://")
+        .and_then(|rest| rest.split_once('/'))
+        .ok_or_else(|| format!("Invalid OpenCode session path: {session_path}"))?;
+    if !is_safe_storage_id(project_id) || !is_safe_storage_id(session_id) {
+        return Err(format!("Invalid OpenCode session path: {session_path}"));
+    }
 
-        if let Ok(content) = fs::read_to_string(&path) {
-            if let Ok(val) = serde_json::from_str::<Value>(&content) {
-                // Timestamps are epoch ms under val["time"]["updated"] or val["time"]["created"]
-                let time_obj = val.get("time");
-                let updated = time_obj
-                    .and_then(|t| t.get("updated").or_else(|| t.get("created")))
-                    .and_then(Value::as_u64)
-                    .map(epoch_ms_to_rfc3339);
+    let base_path = find_base_path_containing(&Path::new("session").join(project_id))
+        .ok_or_else(|| "OpenCode not found".to_string())?;
+    let storage_path = Path::new(&base_path).join("storage");
 
-                if let Some(t) = updated {
-                    if latest.is_none() || t > *latest.as_ref().unwrap() {
-                        latest = Some(t);
+    let session_file = storage_path
+        .join("session")
+        .join(project_id)
+        .join(format!("{session_id}.json"));
+    if !session_file.is_file() {
+        return Err(format!("OpenCode session not found: {session_path}"));
+    }
+
+    let messages_dir = storage_path.join("message").join(session_id);
+    let mut to_move: Vec<PathBuf> = Vec::new();
+    if messages_dir.is_dir() {
+        for entry in fs::read_dir(&messages_dir)
+            .map_err(|e| e.to_string())?
+            .flatten()
+        {
+            let msg_id = entry
+                .path()
+                .file_stem()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let parts_dir = storage_path.join("part").join(&msg_id);
+            if is_safe_storage_id(&msg_id) && parts_dir.is_dir() {
+                to_move.push(parts_dir);
+            }
+        }
+        to_move.push(messages_dir);
+    }
+    // Session file last, so the session stays listed until everything else
+    // has moved
+    to_move.push(session_file);
+
+    let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for src in to_move {
+        let result = src
+            .strip_prefix(&base_path)
+            .map_err(|e| format!("Failed to resolve OpenCode path: {e}"))
+            .and_then(|relative| {
+                let dest = trash_dir.join(relative);
+                if let Some(parent) = dest.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| format!("Failed to create trash folder: {e}"))?;
+                }
+                move_path(&src, &dest)
+                    .map_err(|e| format!("Failed to move {} to trash: {e}", src.display()))?;
+                Ok(dest)
+            });
+        match result {
+            Ok(dest) => moved.push((src, dest)),
+            Err(e) => {
+                // Put back what already moved so the session stays complete
+                for (src, dest) in moved.iter().rev() {
+                    if let Err(undo) = move_path(dest, src) {
+                        log::warn!("Failed to restore {}: {undo}", src.display());
                     }
                 }
+                return Err(e);
             }
         }
     }
 
-    latest
+    Ok(())
 }
 
-fn read_message_parts(parts_dir: &Path) -> Result<Vec<Value>, String> {
-    let mut parts: Vec<(String, Value)> = Vec::new();
+/// Rename `src` to `dest`, copying and then removing it when they are on
+/// different filesystems (e.g. the trash under `~` and an XDG data home on
+/// another mount)
+fn move_path(src: &Path, dest: &Path) -> std::io::Result<()> {
+    match fs::rename(src, dest) {
+        Err(e) if is_cross_device(&e) => copy_then_remove(src, dest),
+        result => result,
+    }
+}
 
-    for entry in fs::read_dir(parts_dir)
-        .map_err(|e| e.to_string())?
-        .flatten()
-    {
-        if entry.file_type().map_or(true, |ft| ft.is_symlink()) {
-            continue;
-        }
-        let path = entry.path();
-        if path.extension().and_then(|e| e.to_str()) != Some("json") {
-            continue;
-        }
+/// Whether an I/O error is `EXDEV` (`ERROR_NOT_SAME_DEVICE` on Windows)
+fn is_cross_device(err: &std::io::Error) -> bool {
+    const CROSS_DEVICE: i32 = if cfg!(windows) { 17 } else { 18 };
+    err.raw_os_error() == Some(CROSS_DEVICE)
+}
+
+/// Copy a file or directory tree to `dest`, then remove `src`. A failed copy
+/// removes the partial `dest` and leaves `src` untouched.
+fn copy_then_remove(src: &Path, dest: &Path) -> std::io::Result<()> {
+    let copied = if src.is_dir() {
+        WalkDir::new(src)
+            .into_iter()
+            .try_for_each(|entry| -> std::io::Result<()> {
+                let entry = entry?;
+                let target = dest.join(entry.path().strip_prefix(src).unwrap_or(entry.path()));
+                if entry.file_type().is_dir() {
+                    fs::create_dir_all(&target)
+                } else {
+                    fs::copy(entry.path(), &target).map(|_| ())
+                }
+            })
+    } else {
+        fs::copy(src, dest).map(|_| ())
+    };
+    if let Err(e) = copied {
+        let _ = if dest.is_dir() {
+            fs::remove_dir_all(dest)
+        } else {
+            fs::remove_file(dest)
+        };
+        return Err(e);
+    }
+
+    if src.is_dir() {
+        fs::remove_dir_all(src)
+    } else {
+        fs::remove_file(src)
+    }
+}
+
+// ============================================================================
+// Internal helpers
+// ============================================================================
+
+/// List `(project_id, session dir)` pairs across all data homes. A project id
+/// present in several homes maps to the first one, matching `load_sessions`.
+fn session_project_dirs() -> Vec<(String, PathBuf)> {
+    let mut dirs: Vec<(String, PathBuf)> = Vec::new();
+    for base_path in get_base_paths() {
+        let session_root = Path::new(&base_path).join("storage").join("session");
+        let Ok(entries) = fs::read_dir(&session_root) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if entry.file_type().map_or(true, |ft| ft.is_symlink()) {
+                continue;
+            }
+            let project_id = entry.file_name().to_string_lossy().to_string();
+            if !is_safe_storage_id(&project_id) || dirs.iter().any(|(id, _)| *id == project_id) {
+                continue;
+            }
+            dirs.push((project_id, entry.path()));
+        }
+    }
+    dirs
+}
+
+/// Count a project's sessions, and their messages by file name only (session
+/// file stems are session ids) so the scan never parses message contents.
+/// A missing session directory is an error, as in `load_sessions`, so it
+/// can't be mistaken for a project without sessions.
+fn count_sessions(storage_path: &Path, sessions_dir: &Path) -> Result<(usize, usize), String> {
+    if !sessions_dir.is_dir() {
+        let project_id = sessions_dir
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        return Err(format!(
+            "OpenCode session directory missing for project {project_id}: {}",
+            sessions_dir.display()
+        ));
+    }
+    let session_ids = list_json_file_stems(sessions_dir);
+    let message_count = session_ids
+        .iter()
+        .filter(|id| is_safe_storage_id(id))
+        .map(|id| list_json_file_stems(&storage_path.join("message").join(id)).len())
+        .sum();
+    Ok((session_ids.len(), message_count))
+}
+
+/// Stems of the non-symlink `.json` files directly inside `dir` (empty when
+/// the directory is missing or unreadable)
+fn list_json_file_stems(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|e| !e.file_type().map_or(true, |ft| ft.is_symlink()))
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|ext| ext.to_str()) == Some("json"))
+        .filter_map(|p| Some(p.file_stem()?.to_string_lossy().to_string()))
+        .collect()
+}
+
+/// What `scan_session_messages` learned from a session's message files
+#[derive(Debug, Default)]
+struct SessionMessageScan {
+    count: usize,
+    has_tool_use: bool,
+    has_errors: bool,
+    model_ids: BTreeSet<String>,
+}
+
+/// Tool flags of one message's parts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct PartFlags {
+    has_tool_use: bool,
+    has_tool_error: bool,
+}
+
+/// Number of part files and the newest modification time among them; a part
+/// directory whose signature is unchanged doesn't need re-reading
+type PartDirSignature = (usize, Option<SystemTime>);
+
+lazy_static! {
+    /// Per-message part flags keyed by part directory, so re-listing a
+    /// project's sessions only re-reads parts that changed
+    static ref PART_FLAGS_CACHE: Mutex<HashMap<PathBuf, (PartDirSignature, PartFlags)>> =
+        Mutex::new(HashMap::new());
+}
+
+fn part_dir_signature(parts_dir: &Path) -> Option<PartDirSignature> {
+    let entries = fs::read_dir(parts_dir).ok()?;
+    let mut count = 0;
+    let mut newest: Option<SystemTime> = None;
+    for entry in entries.flatten() {
+        if entry.file_type().map_or(true, |ft| ft.is_symlink()) {
+            continue;
+        }
+        if entry.path().extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        count += 1;
+        let modified = entry.metadata().and_then(|m| m.modified()).ok();
+        newest = newest.max(modified);
+    }
+    Some((count, newest))
+}
+
+fn part_flags(parts: &[Value]) -> PartFlags {
+    let mut flags = PartFlags::default();
+    for part in parts {
+        if part.get("type").and_then(Value::as_str) != Some("tool") {
+            continue;
+        }
+        flags.has_tool_use = true;
+        if part
+            .get("state")
+            .and_then(|s| s.get("status"))
+            .and_then(Value::as_str)
+            == Some("error")
+        {
+            flags.has_tool_error = true;
+        }
+    }
+    flags
+}
+
+/// Tool flags for a message's part directory, served from `PART_FLAGS_CACHE`
+/// while the directory's signature is unchanged
+fn scan_message_parts(parts_dir: &Path) -> PartFlags {
+    let Some(signature) = part_dir_signature(parts_dir) else {
+        return PartFlags::default();
+    };
+
+    if let Ok(cache) = PART_FLAGS_CACHE.lock() {
+        if let Some((cached_signature, flags)) = cache.get(parts_dir) {
+            if *cached_signature == signature {
+                return *flags;
+            }
+        }
+    }
+
+    let flags = read_message_parts(parts_dir)
+        .map(|parts| part_flags(&parts))
+        .unwrap_or_default();
+    if let Ok(mut cache) = PART_FLAGS_CACHE.lock() {
+        cache.insert(parts_dir.to_path_buf(), (signature, flags));
+    }
+    flags
+}
+
+/// Count a session's message files, check whether any of them recorded an
+/// error (a non-null `error` field, set on failed or aborted assistant turns,
+/// or a tool part in the error state), note whether any tool ran, and collect
+/// the model ids used by assistant turns.
+fn scan_session_messages(messages_dir: &Path, parts_root: &Path) -> SessionMessageScan {
+    let mut scan = SessionMessageScan::default();
+    let Ok(entries) = fs::read_dir(messages_dir) else {
+        return scan;
+    };
+
+    for entry in entries.flatten() {
+        if entry.file_type().map_or(true, |ft| ft.is_symlink()) {
+            continue;
+        }
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        scan.count += 1;
+
+        let Some(val) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| parse_storage_json(&content).ok())
+        else {
+            continue;
+        };
+        if val.get("error").is_some_and(|e| !e.is_null()) {
+            scan.has_errors = true;
+        }
+        if let Some(model_id) = val.get("modelID").and_then(Value::as_str) {
+            scan.model_ids.insert(model_id.to_string());
+        }
+
+        let Some(msg_id) = val
+            .get("id")
+            .and_then(Value::as_str)
+            .filter(|id| is_safe_storage_id(id))
+        else {
+            continue;
+        };
+        let flags = scan_message_parts(&parts_root.join(msg_id));
+        scan.has_tool_use |= flags.has_tool_use;
+        scan.has_errors |= flags.has_tool_error;
+    }
+    scan
+}
+
+/// Provider extras for `ClaudeSession::data`: model ids plus a few session
+/// fields, or `None` when there's nothing to report
+fn session_metadata(session: &Value, model_ids: BTreeSet<String>) -> Option<Value> {
+    let mut data = serde_json::Map::new();
+    if !model_ids.is_empty() {
+        data.insert("model_ids".to_string(), serde_json::json!(model_ids));
+    }
+    for (source, target) in [("version", "version"), ("parentID", "parent_id")] {
+        if let Some(value) = session.get(source).and_then(Value::as_str) {
+            data.insert(target.to_string(), Value::String(value.to_string()));
+        }
+    }
+    if let Some(url) = session
+        .get("share")
+        .and_then(|s| s.get("url"))
+        .and_then(Value::as_str)
+    {
+        data.insert("share_url".to_string(), Value::String(url.to_string()));
+    }
+
+    (!data.is_empty()).then_some(Value::Object(data))
+}
+
+fn get_latest_session_time(sessions_dir: &Path) -> Option<String> {
+    if !sessions_dir.exists() {
+        return None;
+    }
+
+    let mut latest: Option<String> = None;
+
+    for entry in fs::read_dir(sessions_dir).ok()?.flatten() {
+        if entry.file_type().map_or(true, |ft| ft.is_symlink()) {
+            continue;
+        }
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+
+        if let Ok(content) = fs::read_to_string(&path) {
+            if let Ok(val) = parse_storage_json(&content) {
+                // Timestamps are epoch ms under val["time"]["updated"] or val["time"]["created"]
+                let time_obj = val.get("time");
+                let updated = time_obj
+                    .and_then(|t| t.get("updated").or_else(|| t.get("created")))
+                    .and_then(Value::as_u64)
+                    .map(epoch_to_rfc3339);
+
+                if let Some(t) = updated {
+                    if latest.is_none() || t > *latest.as_ref().unwrap() {
+                        latest = Some(t);
+                    }
+                }
+            }
+        }
+    }
+
+    latest
+}
+
+fn read_message_parts(parts_dir: &Path) -> Result<Vec<Value>, String> {
+    let mut parts: Vec<(String, Value)> = Vec::new();
+
+    for entry in fs::read_dir(parts_dir)
+        .map_err(|e| e.to_string())?
+        .flatten()
+    {
+        if entry.file_type().map_or(true, |ft| ft.is_symlink()) {
+            continue;
+        }
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
 
         let content = match fs::read_to_string(&path) {
             Ok(c) => c,
             Err(_) => continue,
         };
 
-        let val: Value = match serde_json::from_str(&content) {
+        let val: Value = match parse_storage_json(&content) {
             Ok(v) => v,
             Err(_) => continue,
         };
@@ -569,6 +1303,22 @@ fn read_message_parts(parts_dir: &Path) -> Result<Vec<Value>, String> {
     Ok(parts.into_iter().map(|(_, v)| v).collect())
 }
 
+/// Parse an `OpenCode` storage file, tolerating a UTF-8 BOM and surrounding whitespace
+fn parse_storage_json(content: &str) -> serde_json::Result<Value> {
+    let trimmed = content.trim_start_matches('\u{feff}').trim();
+    serde_json::from_str(trimmed)
+}
+
+/// Read a token count stored either as a number or as a numeric string.
+/// Negative or oversized counts are dropped rather than wrapped.
+fn token_count(value: Option<&Value>) -> Option<u32> {
+    let value = value?;
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|s| s.trim().parse::<u64>().ok()))
+        .and_then(|v| u32::try_from(v).ok())
+}
+
 /// Sum two `Option<u32>` values, treating None as absent (not zero)
 fn sum_opt(a: Option<u32>, b: Option<u32>) -> Option<u32> {
     match (a, b) {
@@ -580,10 +1330,55 @@ fn sum_opt(a: Option<u32>, b: Option<u32>) -> Option<u32> {
 
 // is_safe_storage_id is imported from crate::utils
 
-fn process_parts(parts: &[Value]) -> (Option<Value>, Option<TokenUsage>, Option<f64>) {
+/// What a message's parts contribute besides the message file itself
+#[derive(Debug, Default)]
+struct ProcessedParts {
+    content: Option<Value>,
+    /// Token usage summed over `step-finish` and `compaction` parts
+    usage: Option<TokenUsage>,
+    cost_usd: Option<f64>,
+    /// Set when the message contains a `compaction` part
+    compact_metadata: Option<Value>,
+}
+
+/// Token usage from a part's `tokens` object: "input", "output", and a
+/// "cache" object containing "read" and "write"
+fn part_token_usage(tokens: &Value) -> TokenUsage {
+    let cache_obj = tokens.get("cache");
+    TokenUsage {
+        input_tokens: token_count(tokens.get("input")),
+        output_tokens: token_count(tokens.get("output")),
+        cache_creation_input_tokens: token_count(cache_obj.and_then(|c| c.get("write"))),
+        cache_read_input_tokens: token_count(cache_obj.and_then(|c| c.get("read"))),
+        service_tier: None,
+    }
+}
+
+/// Add `new_usage` to a running total
+fn accumulate_usage(total: &mut Option<TokenUsage>, new_usage: TokenUsage) {
+    *total = Some(match total.take() {
+        Some(prev) => TokenUsage {
+            input_tokens: sum_opt(prev.input_tokens, new_usage.input_tokens),
+            output_tokens: sum_opt(prev.output_tokens, new_usage.output_tokens),
+            cache_creation_input_tokens: sum_opt(
+                prev.cache_creation_input_tokens,
+                new_usage.cache_creation_input_tokens,
+            ),
+            cache_read_input_tokens: sum_opt(
+                prev.cache_read_input_tokens,
+                new_usage.cache_read_input_tokens,
+            ),
+            service_tier: None,
+        },
+        None => new_usage,
+    });
+}
+
+fn process_parts(parts: &[Value]) -> ProcessedParts {
     let mut content_items: Vec<Value> = Vec::new();
     let mut usage: Option<TokenUsage> = None;
     let mut cost_usd: Option<f64> = None;
+    let mut compact_metadata: Option<Value> = None;
 
     for part in parts {
         let part_type = part.get("type").and_then(|t| t.as_str()).unwrap_or("");
@@ -654,48 +1449,37 @@ fn process_parts(parts: &[Value]) -> (Option<Value>, Option<TokenUsage>, Option<
                     .or_else(|| part.get("reasoning"))
                     .and_then(|v| v.as_str())
                     .unwrap_or("");
-                if !text.is_empty() {
-                    content_items.push(serde_json::json!({
+                let signature = part
+                    .get("signature")
+                    .or_else(|| part.get("metadata").and_then(|m| m.get("signature")))
+                    .and_then(|v| v.as_str())
+                    .filter(|s| !s.is_empty());
+                let redacted = part
+                    .get("redacted")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false);
+                if !text.is_empty() && !redacted {
+                    let mut thinking = serde_json::json!({
                         "type": "thinking",
                         "thinking": text
+                    });
+                    if let Some(signature) = signature {
+                        thinking["signature"] = Value::String(signature.to_string());
+                    }
+                    content_items.push(thinking);
+                } else if redacted || signature.is_some() {
+                    // Encrypted reasoning: keep a marker so the UI can show
+                    // that reasoning happened even though it can't be read
+                    content_items.push(serde_json::json!({
+                        "type": "thinking",
+                        "redacted": true
                     }));
                 }
             }
             "step-finish" => {
-                // Real field is "tokens" with "input", "output", "reasoning",
-                // and "cache" object containing "read" and "write"
+                // Real field is "tokens"; accumulate across multiple step-finish parts
                 if let Some(t) = part.get("tokens") {
-                    let cache_obj = t.get("cache");
-                    let new_usage = TokenUsage {
-                        input_tokens: t.get("input").and_then(Value::as_u64).map(|v| v as u32),
-                        output_tokens: t.get("output").and_then(Value::as_u64).map(|v| v as u32),
-                        cache_creation_input_tokens: cache_obj
-                            .and_then(|c| c.get("write"))
-                            .and_then(Value::as_u64)
-                            .map(|v| v as u32),
-                        cache_read_input_tokens: cache_obj
-                            .and_then(|c| c.get("read"))
-                            .and_then(Value::as_u64)
-                            .map(|v| v as u32),
-                        service_tier: None,
-                    };
-                    // Accumulate tokens across multiple step-finish parts
-                    usage = match usage {
-                        Some(prev) => Some(TokenUsage {
-                            input_tokens: sum_opt(prev.input_tokens, new_usage.input_tokens),
-                            output_tokens: sum_opt(prev.output_tokens, new_usage.output_tokens),
-                            cache_creation_input_tokens: sum_opt(
-                                prev.cache_creation_input_tokens,
-                                new_usage.cache_creation_input_tokens,
-                            ),
-                            cache_read_input_tokens: sum_opt(
-                                prev.cache_read_input_tokens,
-                                new_usage.cache_read_input_tokens,
-                            ),
-                            service_tier: None,
-                        }),
-                        None => Some(new_usage),
-                    };
+                    accumulate_usage(&mut usage, part_token_usage(t));
                 }
                 // "cost" is at the top level of step-finish parts
                 let part_cost = part.get("cost").and_then(Value::as_f64);
@@ -712,6 +1496,32 @@ fn process_parts(parts: &[Value]) -> (Option<Value>, Option<TokenUsage>, Option<
                     "type": "text",
                     "text": format!("[Summary] {text}")
                 }));
+
+                let mut metadata = serde_json::json!({});
+                if let Some(auto) = part.get("auto").and_then(Value::as_bool) {
+                    metadata["trigger"] = Value::from(if auto { "auto" } else { "manual" });
+                }
+                // The compaction step itself may be billed like a step-finish
+                if let Some(t) = part.get("tokens") {
+                    let compaction_usage = part_token_usage(t);
+                    let total: u64 = [
+                        compaction_usage.input_tokens,
+                        compaction_usage.output_tokens,
+                        compaction_usage.cache_creation_input_tokens,
+                        compaction_usage.cache_read_input_tokens,
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .map(u64::from)
+                    .sum();
+                    metadata["tokens"] = Value::from(total);
+                    accumulate_usage(&mut usage, compaction_usage);
+                }
+                if let Some(c) = part.get("cost").and_then(Value::as_f64) {
+                    metadata["cost"] = serde_json::json!(c);
+                    cost_usd = Some(cost_usd.unwrap_or(0.0) + c);
+                }
+                compact_metadata = Some(metadata);
             }
             "patch" => {
                 // Show modified file list from patch parts
@@ -757,7 +1567,12 @@ fn process_parts(parts: &[Value]) -> (Option<Value>, Option<TokenUsage>, Option<
         Some(Value::Array(content_items))
     };
 
-    (content, usage, cost_usd)
+    ProcessedParts {
+        content,
+        usage,
+        cost_usd,
+        compact_metadata,
+    }
 }
 
 fn normalize_opencode_tool_name(name: &str) -> &str {
@@ -818,40 +1633,137 @@ fn normalize_opencode_tool_input(tool_name: &str, input: Value) -> Value {
 
 fn extract_tool_result_from_state(part: &Value, status: &str) -> Option<(Value, bool)> {
     let state = part.get("state")?;
+    // Output is usually a string but may be structured JSON; older parts use
+    // "result". Non-string values are embedded as-is.
+    let output = state.get("output").or_else(|| state.get("result"));
     match status {
         "completed" => {
-            let output = state
-                .get("output")
-                .cloned()
-                .unwrap_or(Value::String(String::new()));
+            let output = output.cloned().unwrap_or(Value::String(String::new()));
             Some((output, false))
         }
         "error" | "cancelled" => {
             let error = state
                 .get("error")
                 .and_then(Value::as_str)
-                .map(str::to_string)
                 .filter(|s| !s.is_empty())
-                .or_else(|| {
-                    state.get("output").map(|v| {
-                        if let Some(s) = v.as_str() {
-                            s.to_string()
-                        } else {
-                            v.to_string()
-                        }
-                    })
-                })
-                .unwrap_or_else(|| format!("Tool execution failed: {status}"));
-            Some((Value::String(error), true))
+                .map(|s| Value::String(s.to_string()))
+                .or_else(|| output.cloned())
+                .unwrap_or_else(|| Value::String(format!("Tool execution failed: {status}")));
+            Some((error, true))
         }
         _ => None,
     }
 }
 
+/// `OpenCode` entry in the provider [`registry`](super::registry)
+pub struct OpenCodeProvider;
+
+impl Provider for OpenCodeProvider {
+    fn id(&self) -> ProviderId {
+        ProviderId::OpenCode
+    }
+
+    fn detect(&self) -> Option<ProviderInfo> {
+        detect()
+    }
+
+    fn scan_projects(&self) -> Result<Vec<ClaudeProject>, String> {
+        scan_projects()
+    }
+
+    fn scan_projects_with_warnings(&self) -> Result<ProjectScan, String> {
+        scan_projects_with_warnings()
+    }
+
+    fn scan_roots(&self) -> Vec<PathBuf> {
+        // Parts don't affect project or message counts
+        get_base_paths()
+            .iter()
+            .map(|base| Path::new(base).join("storage"))
+            .flat_map(|storage| ["project", "session", "message"].map(|dir| storage.join(dir)))
+            .filter(|dir| dir.is_dir())
+            .collect()
+    }
+
+    fn scan_depth(&self) -> usize {
+        // project/*.json, session/{project_id}, message/{session_id}
+        1
+    }
+
+    fn load_sessions(
+        &self,
+        project_path: &str,
+        exclude_sidechain: bool,
+    ) -> Result<Vec<ClaudeSession>, String> {
+        load_sessions(project_path, exclude_sidechain)
+    }
+
+    fn load_messages(&self, session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
+        load_messages(session_path)
+    }
+
+    fn load_messages_page(
+        &self,
+        session_path: &str,
+        window: MessageWindow,
+    ) -> Result<(Vec<ClaudeMessage>, usize), String> {
+        load_messages_page(session_path, window)
+    }
+
+    fn load_message_batches(
+        &self,
+        session_path: &str,
+        batch_size: usize,
+        on_batch: &mut dyn FnMut(Vec<ClaudeMessage>) -> Result<(), String>,
+    ) -> Result<(), String> {
+        load_message_batches(session_path, batch_size, on_batch)
+    }
+
+    fn search(
+        &self,
+        pattern: &SearchPattern,
+        scope: &SearchScope,
+        limit: usize,
+    ) -> Result<Vec<ClaudeMessage>, String> {
+        search_matching(pattern, scope, limit)
+    }
+
+    fn find_session(&self, id: &str) -> Option<String> {
+        find_session(id)
+    }
+
+    fn first_user_message(&self, session_path: &str) -> Result<Option<String>, String> {
+        first_user_message(session_path)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::EnvVarGuard;
     use serde_json::json;
+    use serial_test::serial;
+    use tempfile::TempDir;
+
+    /// Create an `OpenCode` home with an empty `storage` dir and point
+    /// `OPENCODE_HOME` at it for the lifetime of the returned guard.
+    fn setup_opencode_home() -> (TempDir, PathBuf, EnvVarGuard) {
+        let tmp = TempDir::new().expect("temp dir should be created");
+        let home = tmp.path().join("opencode-home");
+        let storage = home.join("storage");
+        fs::create_dir_all(&storage).expect("storage dir should be created");
+        let guard = EnvVarGuard::set("OPENCODE_HOME", &home);
+        (tmp, storage, guard)
+    }
+
+    #[test]
+    fn epoch_to_rfc3339_accepts_seconds_and_milliseconds() {
+        assert_eq!(epoch_to_rfc3339(1_718_000_000), "2024-06-10T06:13:20+00:00");
+        assert_eq!(
+            epoch_to_rfc3339(1_740_000_000_123),
+            "2025-02-19T21:20:00.123+00:00"
+        );
+    }
 
     #[test]
     fn normalizes_lowercase_tool_names() {
@@ -912,4 +1824,1307 @@ mod tests {
         assert_eq!(result.as_str(), Some("failure"));
         assert!(is_error);
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn sessions_with_errors_returns_only_errored_sessions() {
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        fs::create_dir_all(storage.join("project")).expect("project dir should be created");
+        fs::write(
+            storage.join("project").join("proj.json"),
+            r#"{"id": "proj", "worktree": "/work/proj"}"#,
+        )
+        .expect("project file should be written");
+
+        let session_dir = storage.join("session").join("proj");
+        fs::create_dir_all(&session_dir).expect("session dir should be created");
+        for (session_id, error) in [
+            ("ses_bad", serde_json::json!({ "name": "APIError" })),
+            ("ses_ok", Value::Null),
+        ] {
+            fs::write(
+                session_dir.join(format!("{session_id}.json")),
+                serde_json::json!({ "id": session_id, "time": { "created": 1_704_067_200_000_u64 } })
+                    .to_string(),
+            )
+            .expect("session file should be written");
+            let message_dir = storage.join("message").join(session_id);
+            fs::create_dir_all(&message_dir).expect("message dir should be created");
+            fs::write(
+                message_dir.join("msg_1.json"),
+                serde_json::json!({ "id": "msg_1", "role": "assistant", "error": error })
+                    .to_string(),
+            )
+            .expect("message file should be written");
+        }
+
+        let sessions = crate::commands::multi_provider::sessions_with_errors(
+            None,
+            Some(vec!["opencode".to_string()]),
+            None,
+        )
+        .await
+        .expect("listing should succeed");
+
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].actual_session_id, "ses_bad");
+        assert!(sessions[0].has_errors);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn paginated_load_reads_a_window_counted_from_the_newest_message() {
+        use crate::commands::multi_provider::load_provider_messages;
+
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        let message_dir = storage.join("message").join("ses_1");
+        fs::create_dir_all(&message_dir).expect("message dir should be created");
+        for (id, role, created) in [
+            ("msg_c", "user", 1_704_067_204_000_u64),
+            ("msg_a", "user", 1_704_067_200_000),
+            ("msg_b", "assistant", 1_704_067_201_000),
+        ] {
+            fs::write(
+                message_dir.join(format!("{id}.json")),
+                json!({ "id": id, "role": role, "time": { "created": created } }).to_string(),
+            )
+            .expect("message file should be written");
+            let part_dir = storage.join("part").join(id);
+            fs::create_dir_all(&part_dir).expect("part dir should be created");
+            fs::write(
+                part_dir.join("prt_1.json"),
+                json!({ "type": "text", "text": format!("text of {id}") }).to_string(),
+            )
+            .expect("part file should be written");
+        }
+
+        let (page, total) = load_messages_page(
+            "opencode://proj/ses_1",
+            MessageWindow {
+                offset: 1,
+                limit: 1,
+            },
+        )
+        .expect("page should load");
+        assert_eq!(total, 3);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].uuid, "msg_b");
+        // The next message lies outside the window but still bounds the duration
+        assert_eq!(page[0].duration_ms, Some(3_000));
+
+        let newest = load_provider_messages(
+            "opencode".to_string(),
+            "opencode://proj/ses_1".to_string(),
+            None,
+            None,
+            Some(0),
+            Some(2),
+        )
+        .await
+        .expect("page should load");
+        let uuids: Vec<_> = newest.messages.iter().map(|m| m.uuid.as_str()).collect();
+        assert_eq!(uuids, vec!["msg_b", "msg_c"]);
+        let info = newest.page.expect("a paged load carries its page");
+        assert_eq!(info.total_count, 3);
+        assert!(info.has_more);
+        assert_eq!(info.next_offset, 2);
+
+        let oldest = load_provider_messages(
+            "opencode".to_string(),
+            "opencode://proj/ses_1".to_string(),
+            None,
+            None,
+            Some(2),
+            Some(2),
+        )
+        .await
+        .expect("page should load");
+        assert_eq!(oldest.messages.len(), 1);
+        assert_eq!(oldest.messages[0].uuid, "msg_a");
+        assert!(!oldest.page.expect("a paged load carries its page").has_more);
+    }
+
+    #[test]
+    #[serial]
+    fn message_batches_survive_a_message_written_mid_stream() {
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        let message_dir = storage.join("message").join("ses_1");
+        fs::create_dir_all(&message_dir).expect("message dir should be created");
+        let write_message = |id: &str, created: u64| {
+            fs::write(
+                message_dir.join(format!("{id}.json")),
+                json!({ "id": id, "role": "user", "time": { "created": created } }).to_string(),
+            )
+            .expect("message file should be written");
+        };
+        for (i, id) in ["msg_a", "msg_b", "msg_c", "msg_d"].into_iter().enumerate() {
+            write_message(id, 1_704_067_200_000 + i as u64 * 1_000);
+        }
+
+        let mut batches = Vec::new();
+        load_message_batches("opencode://proj/ses_1", 2, &mut |batch| {
+            if batches.is_empty() {
+                write_message("msg_e", 1_704_067_210_000);
+            }
+            batches.push(batch.into_iter().map(|m| m.uuid).collect::<Vec<_>>());
+            Ok(())
+        })
+        .expect("batches should load");
+
+        // The session is ordered once, so the new message can't shift the
+        // second batch and drop one of the originals
+        assert_eq!(
+            batches,
+            vec![vec!["msg_a", "msg_b"], vec!["msg_c", "msg_d"]]
+        );
+        assert_eq!(
+            load_messages("opencode://proj/ses_1").map(|m| m.len()),
+            Ok(5)
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn search_hits_carry_navigable_session_and_project_paths() {
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        fs::create_dir_all(storage.join("project")).expect("project dir should be created");
+        fs::write(
+            storage.join("project").join("proj.json"),
+            r#"{"id": "proj", "worktree": "/work/proj"}"#,
+        )
+        .expect("project file should be written");
+        let session_dir = storage.join("session").join("proj");
+        fs::create_dir_all(&session_dir).expect("session dir should be created");
+        fs::write(
+            session_dir.join("ses_1.json"),
+            json!({ "id": "ses_1", "time": { "created": 1_704_067_200_000_u64 } }).to_string(),
+        )
+        .expect("session file should be written");
+        let message_dir = storage.join("message").join("ses_1");
+        fs::create_dir_all(&message_dir).expect("message dir should be created");
+        fs::write(
+            message_dir.join("msg_1.json"),
+            json!({ "id": "msg_1", "role": "user", "time": { "created": 1_704_067_200_000_u64 } })
+                .to_string(),
+        )
+        .expect("message file should be written");
+        let part_dir = storage.join("part").join("msg_1");
+        fs::create_dir_all(&part_dir).expect("part dir should be created");
+        fs::write(
+            part_dir.join("prt_1.json"),
+            json!({ "type": "text", "text": "Rename the crate" }).to_string(),
+        )
+        .expect("part file should be written");
+
+        let hits = search("rename", 10).expect("search should succeed");
+        assert_eq!(hits.len(), 1);
+
+        let projects = scan_projects().expect("scan should succeed");
+        assert_eq!(
+            hits[0].project_path.as_deref(),
+            Some(projects[0].path.as_str())
+        );
+        let sessions = load_sessions(&projects[0].path, false).expect("sessions should load");
+        let session_path = hits[0]
+            .session_path
+            .as_deref()
+            .expect("hit should carry its session path");
+        assert_eq!(session_path, sessions[0].file_path);
+        let messages = load_messages(session_path).expect("hit session should load");
+        assert!(messages.iter().any(|m| m.uuid == hits[0].uuid));
+    }
+
+    #[test]
+    #[serial]
+    fn first_user_message_reads_earliest_user_prompt() {
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        let message_dir = storage.join("message").join("ses_1");
+        fs::create_dir_all(&message_dir).expect("message dir should be created");
+        for (id, role, created, text) in [
+            ("msg_a", "user", 1_704_067_202_000_u64, "follow-up"),
+            ("msg_b", "assistant", 1_704_067_201_000, "answer"),
+            ("msg_c", "user", 1_704_067_200_000, "opening prompt"),
+        ] {
+            fs::write(
+                message_dir.join(format!("{id}.json")),
+                json!({ "id": id, "role": role, "time": { "created": created } }).to_string(),
+            )
+            .expect("message file should be written");
+            let part_dir = storage.join("part").join(id);
+            fs::create_dir_all(&part_dir).expect("part dir should be created");
+            fs::write(
+                part_dir.join("prt_1.json"),
+                json!({ "type": "text", "text": text }).to_string(),
+            )
+            .expect("part file should be written");
+        }
+        let assistant_only = storage.join("message").join("ses_2");
+        fs::create_dir_all(&assistant_only).expect("message dir should be created");
+        fs::write(
+            assistant_only.join("msg_x.json"),
+            json!({ "id": "msg_x", "role": "assistant" }).to_string(),
+        )
+        .expect("message file should be written");
+
+        assert_eq!(
+            first_user_message("opencode://proj/ses_1")
+                .unwrap()
+                .as_deref(),
+            Some("opening prompt")
+        );
+        assert_eq!(first_user_message("opencode://proj/ses_2").unwrap(), None);
+    }
+
+    #[test]
+    #[serial]
+    fn load_messages_orders_by_created_time_not_filename() {
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        let message_dir = storage.join("message").join("ses_1");
+        fs::create_dir_all(&message_dir).expect("message dir should be created");
+        // Filenames sort a, m, z but creation times run z, m, a
+        for (id, role, created) in [
+            ("msg_z", "user", 1_704_067_200_000_u64),
+            ("msg_m", "assistant", 1_704_067_201_000),
+            ("msg_a", "user", 1_704_067_202_000),
+        ] {
+            fs::write(
+                message_dir.join(format!("{id}.json")),
+                json!({ "id": id, "role": role, "time": { "created": created } }).to_string(),
+            )
+            .expect("message file should be written");
+            let part_dir = storage.join("part").join(id);
+            fs::create_dir_all(&part_dir).expect("part dir should be created");
+            fs::write(
+                part_dir.join("prt_1.json"),
+                json!({ "type": "text", "text": id }).to_string(),
+            )
+            .expect("part file should be written");
+        }
+
+        let messages = load_messages("opencode://proj/ses_1").expect("messages should load");
+        let ids: Vec<_> = messages.iter().map(|m| m.uuid.as_str()).collect();
+        assert_eq!(ids, ["msg_z", "msg_m", "msg_a"]);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn resolve_session_finds_opencode_session_by_id() {
+        use crate::commands::multi_provider::resolve_session;
+
+        let (tmp, storage, _guard) = setup_opencode_home();
+        let session_dir = storage.join("session").join("proj");
+        fs::create_dir_all(&session_dir).expect("session dir should be created");
+        fs::write(
+            session_dir.join("ses_abc.json"),
+            json!({ "id": "ses_abc" }).to_string(),
+        )
+        .expect("session file should be written");
+        let claude_path = Some(tmp.path().join("no-claude").to_string_lossy().to_string());
+
+        let found = resolve_session("ses_abc".to_string(), claude_path.clone())
+            .await
+            .expect("lookup should succeed");
+        assert_eq!(
+            found,
+            Some((
+                "opencode".to_string(),
+                "opencode://proj/ses_abc".to_string()
+            ))
+        );
+        for missing in ["ses_missing", "../proj", ""] {
+            let found = resolve_session(missing.to_string(), claude_path.clone())
+                .await
+                .expect("lookup should succeed");
+            assert_eq!(found, None, "{missing:?} should not resolve");
+        }
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn export_session_jsonl_round_trips_through_claude_loader() {
+        use crate::commands::export::export_session_jsonl;
+        use crate::commands::multi_provider::load_provider_messages;
+
+        let (tmp, storage, _guard) = setup_opencode_home();
+        let message_dir = storage.join("message").join("ses_1");
+        fs::create_dir_all(&message_dir).expect("message dir should be created");
+        for (id, role, parent, created) in [
+            ("msg_1", "user", None, 1_704_067_200_000_u64),
+            ("msg_2", "assistant", Some("msg_1"), 1_704_067_201_000),
+        ] {
+            let mut message = json!({ "id": id, "role": role, "time": { "created": created } });
+            if let Some(parent) = parent {
+                message["parentID"] = json!(parent);
+                message["modelID"] = json!("claude-sonnet-4");
+                message["tokens"] = json!({ "input": 40, "output": 8 });
+            }
+            fs::write(message_dir.join(format!("{id}.json")), message.to_string())
+                .expect("message file should be written");
+        }
+        for (id, part) in [
+            (
+                "msg_1",
+                json!({ "type": "text", "text": "Rename the crate" }),
+            ),
+            (
+                "msg_2",
+                json!({
+                    "type": "tool",
+                    "tool": "bash",
+                    "callID": "call_1",
+                    "state": { "status": "completed", "input": { "command": "ls" }, "output": "src" }
+                }),
+            ),
+        ] {
+            let part_dir = storage.join("part").join(id);
+            fs::create_dir_all(&part_dir).expect("part dir should be created");
+            fs::write(part_dir.join("prt_1.json"), part.to_string())
+                .expect("part file should be written");
+        }
+
+        let jsonl =
+            export_session_jsonl("opencode".to_string(), "opencode://proj/ses_1".to_string())
+                .await
+                .expect("export should succeed");
+        let lines: Vec<Value> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("each line should be JSON"))
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["parentUuid"], Value::Null);
+        assert_eq!(lines[1]["parentUuid"], "msg_1");
+        assert_eq!(lines[1]["sessionId"], "ses_1");
+        assert_eq!(lines[1]["provider"], "opencode");
+        assert_eq!(lines[1]["message"]["role"], "assistant");
+        assert_eq!(lines[1]["message"]["model"], "claude-sonnet-4");
+
+        let exported = tmp.path().join("exported.jsonl");
+        fs::write(&exported, &jsonl).expect("export should be written");
+        let original = load_messages("opencode://proj/ses_1").expect("messages should load");
+        let reloaded = load_provider_messages(
+            "claude".to_string(),
+            exported.to_string_lossy().to_string(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("Claude loader should parse the export")
+        .messages;
+
+        assert_eq!(reloaded.len(), original.len());
+        for (before, after) in original.iter().zip(&reloaded) {
+            assert_eq!(after.uuid, before.uuid);
+            assert_eq!(after.parent_uuid, before.parent_uuid);
+            assert_eq!(after.session_id, before.session_id);
+            assert_eq!(after.timestamp, before.timestamp);
+            assert_eq!(after.message_type, before.message_type);
+            assert_eq!(after.content, before.content);
+            assert_eq!(after.model, before.model);
+        }
+        assert_eq!(
+            reloaded[1].usage.as_ref().and_then(|u| u.input_tokens),
+            Some(40)
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn export_session_markdown_renders_messages_in_order() {
+        use crate::commands::export::export_session_markdown;
+
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        let message_dir = storage.join("message").join("ses_1");
+        fs::create_dir_all(&message_dir).expect("message dir should be created");
+        let parts = [
+            (
+                "msg_1",
+                "user",
+                vec![json!({ "type": "text", "text": "Why is the build red?" })],
+            ),
+            (
+                "msg_2",
+                "assistant",
+                vec![
+                    json!({ "type": "reasoning", "text": "Check the CI log first" }),
+                    json!({
+                        "type": "tool",
+                        "tool": "bash",
+                        "callID": "call_1",
+                        "state": {
+                            "status": "completed",
+                            "input": { "command": "cargo test" },
+                            "output": "test result: FAILED"
+                        }
+                    }),
+                    json!({ "type": "text", "text": "One test fails." }),
+                ],
+            ),
+        ];
+        for (index, (id, role, message_parts)) in parts.iter().enumerate() {
+            fs::write(
+                message_dir.join(format!("{id}.json")),
+                json!({
+                    "id": id,
+                    "role": role,
+                    "time": { "created": 1_704_067_200_000_u64 + index as u64 * 1000 }
+                })
+                .to_string(),
+            )
+            .expect("message file should be written");
+            let part_dir = storage.join("part").join(id);
+            fs::create_dir_all(&part_dir).expect("part dir should be created");
+            for (part_index, part) in message_parts.iter().enumerate() {
+                fs::write(
+                    part_dir.join(format!("prt_{part_index}.json")),
+                    part.to_string(),
+                )
+                .expect("part file should be written");
+            }
+        }
+
+        let markdown =
+            export_session_markdown("opencode".to_string(), "opencode://proj/ses_1".to_string())
+                .await
+                .expect("export should succeed");
+
+        let user = markdown
+            .find("## User · 2024-01-01T00:00:00")
+            .expect("user heading should be present");
+        let assistant = markdown
+            .find("## Assistant · 2024-01-01T00:00:01")
+            .expect("assistant heading should be present");
+        assert!(user < assistant);
+        assert!(markdown.contains("<summary>Thinking</summary>\n\nCheck the CI log first"));
+        assert!(markdown
+            .contains("**Tool: `Bash`**\n\n```json\n{\n  \"command\": \"cargo test\"\n}\n```"));
+        assert!(markdown.contains("**Result:**\n\n```\ntest result: FAILED\n```"));
+        assert!(markdown.ends_with("One test fails.\n"));
+    }
+
+    #[test]
+    #[serial]
+    fn load_messages_estimates_cost_only_when_missing() {
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        let message_dir = storage.join("message").join("ses_1");
+        fs::create_dir_all(&message_dir).expect("message dir should be created");
+        for (id, cost) in [("msg_1", None), ("msg_2", Some(0.42))] {
+            let mut message = json!({
+                "id": id,
+                "role": "assistant",
+                "modelID": "claude-sonnet-4-20250514",
+                "tokens": { "input": 1_000_000, "output": 100_000 },
+            });
+            if let Some(cost) = cost {
+                message["cost"] = json!(cost);
+            }
+            fs::write(message_dir.join(format!("{id}.json")), message.to_string())
+                .expect("message file should be written");
+        }
+
+        let messages = load_messages("opencode://proj/ses_1").expect("messages should load");
+        let estimated = messages[0].cost_usd.expect("cost should be estimated");
+        assert!((estimated - 4.5).abs() < 1e-9, "got {estimated}");
+        assert_eq!(messages[1].cost_usd, Some(0.42));
+    }
+
+    #[test]
+    #[serial]
+    fn load_messages_derives_assistant_duration_from_part_times() {
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        let message_dir = storage.join("message").join("ses_1");
+        fs::create_dir_all(&message_dir).expect("message dir should be created");
+        for (id, role, created) in [
+            ("msg_1", "user", 1_704_067_200_000_u64),
+            ("msg_2", "assistant", 1_704_067_201_000),
+            ("msg_3", "assistant", 1_704_067_210_000),
+            ("msg_4", "user", 1_704_067_215_000),
+        ] {
+            fs::write(
+                message_dir.join(format!("{id}.json")),
+                json!({ "id": id, "role": role, "time": { "created": created } }).to_string(),
+            )
+            .expect("message file should be written");
+        }
+
+        let part_dir = storage.join("part").join("msg_2");
+        fs::create_dir_all(&part_dir).expect("part dir should be created");
+        fs::write(
+            part_dir.join("prt_1.json"),
+            json!({
+                "type": "text",
+                "text": "Reading the file",
+                "time": { "start": 1_704_067_201_200_u64, "end": 1_704_067_201_500_u64 }
+            })
+            .to_string(),
+        )
+        .expect("part file should be written");
+        fs::write(
+            part_dir.join("prt_2.json"),
+            json!({
+                "type": "tool",
+                "tool": "read",
+                "callID": "call_1",
+                "state": {
+                    "status": "completed",
+                    "output": "ok",
+                    "time": { "start": 1_704_067_201_500_u64, "end": 1_704_067_203_700_u64 }
+                }
+            })
+            .to_string(),
+        )
+        .expect("part file should be written");
+
+        let messages = load_messages("opencode://proj/ses_1").expect("messages should load");
+        let durations: Vec<_> = messages.iter().map(|m| m.duration_ms).collect();
+        // msg_2 spans its parts; msg_3 has none, so it falls back to the gap
+        // until msg_4; user messages get no duration
+        assert_eq!(durations, [None, Some(2_500), Some(5_000), None]);
+    }
+
+    #[test]
+    #[serial]
+    fn load_sessions_flags_tool_use_and_tool_errors_from_parts() {
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        let session_dir = storage.join("session").join("proj");
+        fs::create_dir_all(&session_dir).expect("session dir should be created");
+        for (session_id, tool_status) in [
+            ("ses_chat", None),
+            ("ses_tools", Some("completed")),
+            ("ses_failed", Some("error")),
+        ] {
+            fs::write(
+                session_dir.join(format!("{session_id}.json")),
+                json!({ "id": session_id }).to_string(),
+            )
+            .expect("session file should be written");
+            let message_dir = storage.join("message").join(session_id);
+            fs::create_dir_all(&message_dir).expect("message dir should be created");
+            let msg_id = format!("msg_{session_id}");
+            fs::write(
+                message_dir.join(format!("{msg_id}.json")),
+                json!({ "id": msg_id, "role": "assistant" }).to_string(),
+            )
+            .expect("message file should be written");
+
+            let part_dir = storage.join("part").join(&msg_id);
+            fs::create_dir_all(&part_dir).expect("part dir should be created");
+            let part = match tool_status {
+                Some(status) => {
+                    json!({ "type": "tool", "tool": "bash", "state": { "status": status } })
+                }
+                None => json!({ "type": "text", "text": "hello" }),
+            };
+            fs::write(part_dir.join("prt_1.json"), part.to_string())
+                .expect("part file should be written");
+        }
+
+        let flags = |sessions: &[ClaudeSession], id: &str| {
+            let session = sessions
+                .iter()
+                .find(|s| s.actual_session_id == id)
+                .expect("session should be listed");
+            (session.has_tool_use, session.has_errors)
+        };
+
+        let sessions = load_sessions("opencode://proj", false).expect("sessions should load");
+        assert_eq!(flags(&sessions, "ses_chat"), (false, false));
+        assert_eq!(flags(&sessions, "ses_tools"), (true, false));
+        assert_eq!(flags(&sessions, "ses_failed"), (true, true));
+
+        // A new part invalidates the cached flags for that message
+        fs::write(
+            storage.join("part").join("msg_ses_chat").join("prt_2.json"),
+            json!({ "type": "tool", "tool": "read", "state": { "status": "completed" } })
+                .to_string(),
+        )
+        .expect("part file should be written");
+        let sessions = load_sessions("opencode://proj", false).expect("sessions should load");
+        assert_eq!(flags(&sessions, "ses_chat"), (true, false));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn largest_sessions_ranks_by_message_count() {
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        fs::create_dir_all(storage.join("project")).expect("project dir should be created");
+        fs::write(
+            storage.join("project").join("proj.json"),
+            r#"{"id": "proj", "worktree": "/work/proj"}"#,
+        )
+        .expect("project file should be written");
+
+        let session_dir = storage.join("session").join("proj");
+        fs::create_dir_all(&session_dir).expect("session dir should be created");
+        for (session_id, messages) in [("ses_small", 1), ("ses_big", 4), ("ses_mid", 2)] {
+            fs::write(
+                session_dir.join(format!("{session_id}.json")),
+                json!({ "id": session_id, "time": { "created": 1_704_067_200_000_u64 } })
+                    .to_string(),
+            )
+            .expect("session file should be written");
+            let message_dir = storage.join("message").join(session_id);
+            fs::create_dir_all(&message_dir).expect("message dir should be created");
+            for i in 0..messages {
+                fs::write(message_dir.join(format!("msg_{i}.json")), "{}")
+                    .expect("message file should be written");
+            }
+        }
+
+        let largest = crate::commands::stats::largest_sessions(
+            Some(vec!["opencode".to_string()]),
+            "messages".to_string(),
+            Some(2),
+        )
+        .await
+        .expect("ranking should succeed");
+
+        let ids: Vec<_> = largest
+            .iter()
+            .map(|s| s.actual_session_id.as_str())
+            .collect();
+        assert_eq!(ids, ["ses_big", "ses_mid"]);
+    }
+
+    #[test]
+    #[serial]
+    fn scan_projects_accepts_file_uri_project_path() {
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        fs::create_dir_all(storage.join("project")).expect("project dir should be created");
+        fs::write(
+            storage.join("project").join("proj.json"),
+            r#"{"id": "proj", "path": "file:///work/my%20app"}"#,
+        )
+        .expect("project file should be written");
+        let session_dir = storage.join("session").join("proj");
+        fs::create_dir_all(&session_dir).expect("session dir should be created");
+        fs::write(session_dir.join("ses_1.json"), r#"{"id": "ses_1"}"#)
+            .expect("session file should be written");
+
+        let projects = scan_projects().expect("scan should succeed");
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].actual_path, "/work/my app");
+        assert_eq!(projects[0].name, "my app");
+    }
+
+    #[test]
+    #[serial]
+    fn scan_projects_sums_message_files_across_sessions() {
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        fs::create_dir_all(storage.join("project")).expect("project dir should be created");
+        fs::write(
+            storage.join("project").join("proj.json"),
+            r#"{"id": "proj", "worktree": "/work/proj"}"#,
+        )
+        .expect("project file should be written");
+
+        let session_dir = storage.join("session").join("proj");
+        fs::create_dir_all(&session_dir).expect("session dir should be created");
+        for (session_id, messages) in [("ses_a", 3), ("ses_b", 2), ("ses_empty", 0)] {
+            fs::write(
+                session_dir.join(format!("{session_id}.json")),
+                json!({ "id": session_id }).to_string(),
+            )
+            .expect("session file should be written");
+            let message_dir = storage.join("message").join(session_id);
+            fs::create_dir_all(&message_dir).expect("message dir should be created");
+            for i in 0..messages {
+                fs::write(message_dir.join(format!("msg_{i}.json")), "{}")
+                    .expect("message file should be written");
+            }
+        }
+        // Non-JSON files are not messages
+        fs::write(storage.join("message").join("ses_a").join("notes.txt"), "x")
+            .expect("stray file should be written");
+
+        let projects = scan_projects().expect("scan should succeed");
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].session_count, 3);
+        assert_eq!(projects[0].message_count, 5);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn scan_all_projects_flags_and_filters_missing_worktrees() {
+        let (tmp, storage, _guard) = setup_opencode_home();
+        let live_worktree = tmp.path().join("live");
+        fs::create_dir_all(&live_worktree).expect("worktree dir should be created");
+        let dead_worktree = tmp.path().join("deleted");
+
+        fs::create_dir_all(storage.join("project")).expect("project dir should be created");
+        for (project_id, worktree) in [("live", &live_worktree), ("dead", &dead_worktree)] {
+            fs::write(
+                storage.join("project").join(format!("{project_id}.json")),
+                json!({ "id": project_id, "worktree": worktree }).to_string(),
+            )
+            .expect("project file should be written");
+            let session_dir = storage.join("session").join(project_id);
+            fs::create_dir_all(&session_dir).expect("session dir should be created");
+            fs::write(
+                session_dir.join(format!("ses_{project_id}.json")),
+                json!({ "id": format!("ses_{project_id}") }).to_string(),
+            )
+            .expect("session file should be written");
+        }
+
+        let opencode_only = || Some(vec!["opencode".to_string()]);
+        let mut all = crate::commands::multi_provider::scan_all_projects(
+            None,
+            opencode_only(),
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("scan should succeed");
+        all.sort_by(|a, b| a.path.cmp(&b.path));
+        let flags: Vec<_> = all
+            .iter()
+            .map(|p| (p.path.as_str(), p.path_exists))
+            .collect();
+        assert_eq!(
+            flags,
+            vec![("opencode://dead", false), ("opencode://live", true)]
+        );
+
+        let existing = crate::commands::multi_provider::scan_all_projects(
+            None,
+            opencode_only(),
+            Some(true),
+            None,
+            None,
+        )
+        .await
+        .expect("scan should succeed");
+        assert_eq!(existing.len(), 1);
+        assert_eq!(existing[0].path, "opencode://live");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn orphaned_session_dir_is_reconstructed_as_project() {
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        fs::create_dir_all(storage.join("project")).expect("project dir should be created");
+        fs::write(
+            storage.join("project").join("known.json"),
+            json!({ "id": "known", "worktree": "/work/known" }).to_string(),
+        )
+        .expect("project file should be written");
+        for (project_id, session_id) in [("known", "ses_k"), ("lost", "ses_l")] {
+            let session_dir = storage.join("session").join(project_id);
+            fs::create_dir_all(&session_dir).expect("session dir should be created");
+            fs::write(
+                session_dir.join(format!("{session_id}.json")),
+                json!({ "id": session_id }).to_string(),
+            )
+            .expect("session file should be written");
+        }
+        let message_dir = storage.join("message").join("ses_l");
+        fs::create_dir_all(&message_dir).expect("message dir should be created");
+        fs::write(message_dir.join("msg_1.json"), "{}").expect("message file should be written");
+
+        let orphans = reconstruct_projects().expect("reconstruction should succeed");
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].path, "opencode://lost");
+        assert_eq!(orphans[0].name, "lost");
+        assert!(orphans[0].actual_path.is_empty());
+        assert_eq!(orphans[0].session_count, 1);
+        assert_eq!(orphans[0].message_count, 1);
+
+        let opencode_only = || Some(vec!["opencode".to_string()]);
+        let default_scan = crate::commands::multi_provider::scan_all_projects(
+            None,
+            opencode_only(),
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("scan should succeed");
+        assert_eq!(default_scan.len(), 1);
+
+        let with_orphans = crate::commands::multi_provider::scan_all_projects(
+            None,
+            opencode_only(),
+            None,
+            Some(true),
+            None,
+        )
+        .await
+        .expect("scan should succeed");
+        let mut paths: Vec<_> = with_orphans.iter().map(|p| p.path.as_str()).collect();
+        paths.sort_unstable();
+        assert_eq!(paths, vec!["opencode://known", "opencode://lost"]);
+
+        // The orphan's path is unknown, so only_existing can't rule it out;
+        // the known project's worktree is gone
+        let existing_with_orphans = crate::commands::multi_provider::scan_all_projects(
+            None,
+            opencode_only(),
+            Some(true),
+            Some(true),
+            None,
+        )
+        .await
+        .expect("scan should succeed");
+        let paths: Vec<_> = existing_with_orphans
+            .iter()
+            .map(|p| p.path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["opencode://lost"]);
+
+        let sessions = load_sessions("opencode://lost", false).expect("sessions should load");
+        assert_eq!(sessions.len(), 1);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn scan_is_cached_until_storage_changes() {
+        use crate::commands::multi_provider::scan_all_projects;
+
+        let (tmp, storage, _guard) = setup_opencode_home();
+        let project_file = storage.join("project").join("proj.json");
+        let write_project = |worktree: &str| {
+            fs::write(
+                &project_file,
+                json!({ "id": "proj", "worktree": tmp.path().join(worktree) }).to_string(),
+            )
+            .expect("project file should be written");
+        };
+        fs::create_dir_all(storage.join("project")).expect("project dir should be created");
+        write_project("alpha");
+        let session_dir = storage.join("session").join("proj");
+        fs::create_dir_all(&session_dir).expect("session dir should be created");
+        fs::write(
+            session_dir.join("ses_1.json"),
+            json!({ "id": "ses_1" }).to_string(),
+        )
+        .expect("session file should be written");
+
+        let opencode_only = || Some(vec!["opencode".to_string()]);
+        let names = |projects: Vec<ClaudeProject>| -> Vec<String> {
+            projects.into_iter().map(|p| p.name).collect()
+        };
+        let scan =
+            |force_refresh| scan_all_projects(None, opencode_only(), None, None, force_refresh);
+        assert_eq!(
+            names(scan(None).await.expect("scan should succeed")),
+            vec!["alpha"]
+        );
+
+        // Same newest mtime: the cached scan is served
+        let stamp = fs::metadata(&project_file)
+            .and_then(|m| m.modified())
+            .expect("mtime should be readable");
+        let set_mtime = |time| {
+            fs::File::options()
+                .write(true)
+                .open(&project_file)
+                .and_then(|f| f.set_modified(time))
+                .expect("mtime should be set");
+        };
+        write_project("beta");
+        set_mtime(stamp);
+        assert_eq!(
+            names(scan(None).await.expect("scan should succeed")),
+            vec!["alpha"]
+        );
+        assert_eq!(
+            names(scan(Some(true)).await.expect("scan should succeed")),
+            vec!["beta"]
+        );
+
+        // A newer mtime invalidates it
+        write_project("gamma");
+        set_mtime(stamp + std::time::Duration::from_secs(10));
+        assert_eq!(
+            names(scan(None).await.expect("scan should succeed")),
+            vec!["gamma"]
+        );
+
+        // Another data home is scanned on its own
+        let (_other_tmp, other_storage, _other_guard) = setup_opencode_home();
+        fs::create_dir_all(other_storage.join("project")).expect("project dir should be created");
+        assert!(scan(None).await.expect("scan should succeed").is_empty());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn scan_all_projects_reports_broken_storage_as_warning() {
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        // A file where the project directory should be makes the scan fail
+        fs::write(storage.join("project"), "not a directory")
+            .expect("project placeholder should be written");
+
+        let result = crate::commands::multi_provider::scan_all_projects_with_warnings(
+            None,
+            Some(vec!["opencode".to_string()]),
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("scan should still succeed overall");
+
+        assert!(result.projects.is_empty());
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(result.warnings[0].provider, "opencode");
+        assert!(!result.warnings[0].error.is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn load_sessions_exposes_provider_extras_in_data() {
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        let session_dir = storage.join("session").join("proj");
+        fs::create_dir_all(&session_dir).expect("session dir should be created");
+        fs::write(
+            session_dir.join("ses_1.json"),
+            json!({
+                "id": "ses_1",
+                "version": "0.5.1",
+                "parentID": "ses_0",
+                "time": { "created": 1_704_067_200_000_u64 }
+            })
+            .to_string(),
+        )
+        .expect("session file should be written");
+        let message_dir = storage.join("message").join("ses_1");
+        fs::create_dir_all(&message_dir).expect("message dir should be created");
+        for (id, model) in [
+            ("msg_1", "claude-sonnet-4"),
+            ("msg_2", "gpt-5"),
+            ("msg_3", "gpt-5"),
+        ] {
+            fs::write(
+                message_dir.join(format!("{id}.json")),
+                json!({ "id": id, "role": "assistant", "modelID": model }).to_string(),
+            )
+            .expect("message file should be written");
+        }
+
+        let sessions = load_sessions("opencode://proj", false).expect("sessions should load");
+        assert_eq!(sessions.len(), 1);
+
+        let round_tripped: ClaudeSession =
+            serde_json::from_value(serde_json::to_value(&sessions[0]).unwrap()).unwrap();
+        assert_eq!(
+            round_tripped.data,
+            Some(json!({
+                "model_ids": ["claude-sonnet-4", "gpt-5"],
+                "version": "0.5.1",
+                "parent_id": "ses_0"
+            }))
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn sessions_modified_before_lists_only_stale_sessions() {
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        let project_dir = storage.join("session").join("proj");
+        fs::create_dir_all(&project_dir).expect("session dir should be created");
+        // 2024-01-01T00:00:00Z and 2024-06-01T00:00:00Z in epoch ms
+        for (id, updated) in [
+            ("ses_old", 1_704_067_200_000_u64),
+            ("ses_new", 1_717_200_000_000),
+        ] {
+            fs::write(
+                project_dir.join(format!("{id}.json")),
+                serde_json::json!({ "id": id, "time": { "created": updated, "updated": updated } })
+                    .to_string(),
+            )
+            .expect("session file should be written");
+        }
+
+        let cutoff = parse_rfc3339_utc("2024-03-01T00:00:00Z").unwrap();
+        let stale = sessions_modified_before(cutoff).expect("listing should succeed");
+        assert_eq!(stale, vec!["opencode://proj/ses_old".to_string()]);
+        assert!(project_dir.join("ses_old.json").exists());
+    }
+
+    #[test]
+    #[serial]
+    fn trash_session_moves_session_messages_and_parts() {
+        let (tmp, storage, _guard) = setup_opencode_home();
+        let session_dir = storage.join("session").join("proj");
+        let message_dir = storage.join("message").join("ses_1");
+        let part_dir = storage.join("part").join("msg_1");
+        for dir in [&session_dir, &message_dir, &part_dir] {
+            fs::create_dir_all(dir).expect("storage dir should be created");
+        }
+        fs::write(session_dir.join("ses_1.json"), r#"{"id":"ses_1"}"#)
+            .expect("session file should be written");
+        fs::write(message_dir.join("msg_1.json"), r#"{"id":"msg_1"}"#)
+            .expect("message file should be written");
+        fs::write(part_dir.join("prt_1.json"), r#"{"type":"text"}"#)
+            .expect("part file should be written");
+
+        let trash = tmp.path().join("trash");
+        trash_session("opencode://proj/ses_1", &trash).expect("session should be trashed");
+
+        for relative in [
+            "storage/session/proj/ses_1.json",
+            "storage/message/ses_1/msg_1.json",
+            "storage/part/msg_1/prt_1.json",
+        ] {
+            assert!(
+                trash.join(relative).is_file(),
+                "{relative} should be in trash"
+            );
+        }
+        assert!(!session_dir.join("ses_1.json").exists());
+        assert!(!message_dir.exists());
+        assert!(!part_dir.exists());
+        assert!(load_sessions("opencode://proj", false)
+            .expect("sessions should load")
+            .is_empty());
+    }
+
+    #[test]
+    fn copy_then_remove_moves_a_directory_tree() {
+        let tmp = TempDir::new().expect("temp dir should be created");
+        let src = tmp.path().join("src");
+        fs::create_dir_all(src.join("nested")).expect("source dir should be created");
+        fs::write(src.join("a.json"), "a").expect("file should be written");
+        fs::write(src.join("nested").join("b.json"), "b").expect("file should be written");
+
+        let dest = tmp.path().join("dest");
+        copy_then_remove(&src, &dest).expect("tree should move");
+        assert!(!src.exists());
+        assert_eq!(fs::read_to_string(dest.join("a.json")).unwrap(), "a");
+        assert_eq!(
+            fs::read_to_string(dest.join("nested").join("b.json")).unwrap(),
+            "b"
+        );
+
+        // A failed copy keeps the source and drops the partial destination
+        let missing = tmp.path().join("missing.json");
+        let target = tmp.path().join("target.json");
+        assert!(copy_then_remove(&missing, &target).is_err());
+        assert!(!target.exists());
+    }
+
+    #[test]
+    #[serial]
+    fn get_base_paths_finds_env_and_xdg_homes() {
+        let (tmp, storage, _guard) = setup_opencode_home();
+        let xdg_storage = tmp.path().join("xdg").join("opencode").join("storage");
+        let _xdg_guard = EnvVarGuard::set("XDG_DATA_HOME", &tmp.path().join("xdg"));
+
+        for (storage, id) in [(&storage, "proj_env"), (&xdg_storage, "proj_xdg")] {
+            let project_dir = storage.join("project");
+            fs::create_dir_all(&project_dir).expect("project dir should be created");
+            fs::write(
+                project_dir.join(format!("{id}.json")),
+                serde_json::json!({ "id": id, "worktree": format!("/work/{id}") }).to_string(),
+            )
+            .expect("project file should be written");
+        }
+
+        let bases = get_base_paths();
+        assert!(bases.len() >= 2, "both homes should be found: {bases:?}");
+        assert!(bases[0].ends_with("opencode-home"));
+        assert!(bases[1].ends_with("opencode"));
+
+        let mut ids: Vec<String> = scan_projects()
+            .expect("scan should succeed")
+            .into_iter()
+            .map(|p| p.path)
+            .filter(|p| p.contains("proj_"))
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["opencode://proj_env", "opencode://proj_xdg"]);
+    }
+
+    #[test]
+    #[serial]
+    fn scan_projects_parses_bom_prefixed_project_json() {
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        let project_dir = storage.join("project");
+        fs::create_dir_all(&project_dir).expect("project dir should be created");
+        fs::write(
+            project_dir.join("proj_bom.json"),
+            "\u{feff}\n  {\"id\": \"proj_bom\", \"worktree\": \"/work/bom-project\"}\n",
+        )
+        .expect("project file should be written");
+
+        let projects = scan_projects().expect("scan should succeed");
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].path, "opencode://proj_bom");
+        assert_eq!(projects[0].name, "bom-project");
+    }
+
+    #[test]
+    fn process_parts_embeds_object_valued_tool_result() {
+        let parts = vec![json!({
+            "type": "tool",
+            "tool": "read",
+            "callID": "call_1",
+            "state": {
+                "status": "completed",
+                "input": { "filePath": "/tmp/a.txt" },
+                "result": { "lines": 3, "truncated": false }
+            }
+        })];
+
+        let content = process_parts(&parts)
+            .content
+            .expect("content should be produced");
+        let tool_result = content
+            .as_array()
+            .and_then(|items| items.iter().find(|i| i["type"] == "tool_result"))
+            .expect("tool_result should be present");
+        assert_eq!(
+            tool_result["content"],
+            json!({ "lines": 3, "truncated": false })
+        );
+    }
+
+    #[test]
+    fn process_parts_marks_redacted_reasoning() {
+        let parts = vec![
+            json!({ "type": "reasoning", "text": "", "redacted": true }),
+            json!({ "type": "reasoning", "text": "", "metadata": { "signature": "sig_abc" } }),
+            json!({ "type": "reasoning", "text": "" }),
+        ];
+
+        let content = process_parts(&parts)
+            .content
+            .expect("content should be produced");
+        assert_eq!(
+            content,
+            json!([
+                { "type": "thinking", "redacted": true },
+                { "type": "thinking", "redacted": true }
+            ])
+        );
+    }
+
+    #[test]
+    fn process_parts_accepts_string_encoded_token_counts() {
+        let parts = vec![serde_json::json!({
+            "type": "step-finish",
+            "tokens": {
+                "input": "100",
+                "output": 25,
+                "cache": { "read": "7", "write": "not-a-number" }
+            }
+        })];
+
+        let usage = process_parts(&parts)
+            .usage
+            .expect("usage should be extracted");
+        assert_eq!(usage.input_tokens, Some(100));
+        assert_eq!(usage.output_tokens, Some(25));
+        assert_eq!(usage.cache_read_input_tokens, Some(7));
+        assert_eq!(usage.cache_creation_input_tokens, None);
+    }
+
+    #[test]
+    fn token_count_drops_negative_and_oversized_values() {
+        assert_eq!(token_count(Some(&json!(42))), Some(42));
+        assert_eq!(token_count(Some(&json!(-5))), None);
+        assert_eq!(token_count(Some(&json!("-5"))), None);
+        assert_eq!(token_count(Some(&json!(5_000_000_000_u64))), None);
+        assert_eq!(token_count(Some(&json!("5000000000"))), None);
+    }
+
+    #[test]
+    fn process_parts_adds_compaction_usage_to_totals() {
+        let parts = vec![
+            json!({
+                "type": "step-finish",
+                "tokens": { "input": 100, "output": 20, "cache": { "read": 5 } },
+                "cost": 0.01
+            }),
+            json!({
+                "type": "compaction",
+                "auto": true,
+                "text": "Earlier work summarized",
+                "tokens": { "input": 40, "output": 10, "cache": { "read": 0, "write": 2 } },
+                "cost": 0.005
+            }),
+        ];
+
+        let processed = process_parts(&parts);
+        let usage = processed.usage.expect("usage should be extracted");
+        assert_eq!(usage.input_tokens, Some(140));
+        assert_eq!(usage.output_tokens, Some(30));
+        assert_eq!(usage.cache_read_input_tokens, Some(5));
+        assert_eq!(usage.cache_creation_input_tokens, Some(2));
+        let cost = processed.cost_usd.expect("cost should be summed");
+        assert!((cost - 0.015).abs() < 1e-9);
+        assert_eq!(
+            processed.compact_metadata,
+            Some(json!({ "trigger": "auto", "tokens": 52, "cost": 0.005 }))
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn load_messages_distinguishes_missing_from_empty_message_dir() {
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        fs::create_dir_all(storage.join("message").join("ses_empty"))
+            .expect("message dir should be created");
+
+        let empty = load_messages("opencode://proj/ses_empty").expect("empty dir is valid");
+        assert!(empty.is_empty());
+
+        let missing = load_messages("opencode://proj/ses_missing")
+            .expect_err("missing dir should be reported");
+        assert!(missing.contains("message directory missing"));
+    }
+
+    #[test]
+    #[serial]
+    fn load_sessions_distinguishes_missing_from_empty_session_dir() {
+        let (_tmp, storage, _guard) = setup_opencode_home();
+        fs::create_dir_all(storage.join("session").join("proj_empty"))
+            .expect("session dir should be created");
+
+        let empty = load_sessions("opencode://proj_empty", false).expect("empty dir is valid");
+        assert!(empty.is_empty());
+
+        let missing = load_sessions("opencode://proj_missing", false)
+            .expect_err("missing dir should be reported");
+        assert!(missing.contains("session directory missing"));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn scan_warns_about_projects_missing_their_session_dir() {
+        let (tmp, storage, _guard) = setup_opencode_home();
+        fs::create_dir_all(storage.join("project")).expect("project dir should be created");
+        for id in ["proj_empty", "proj_missing"] {
+            fs::write(
+                storage.join("project").join(format!("{id}.json")),
+                json!({ "id": id, "worktree": tmp.path().join(id) }).to_string(),
+            )
+            .expect("project file should be written");
+        }
+        fs::create_dir_all(storage.join("session").join("proj_empty"))
+            .expect("session dir should be created");
+
+        let scan = scan_projects_with_warnings().expect("scan should succeed");
+        assert!(scan.projects.iter().all(|p| p.session_count == 0));
+        assert_eq!(scan.warnings.len(), 1);
+        assert!(scan.warnings[0].contains("session directory missing for project proj_missing"));
+
+        let result = crate::commands::multi_provider::scan_all_projects_with_warnings(
+            None,
+            Some(vec!["opencode".to_string()]),
+            None,
+            None,
+            Some(true),
+        )
+        .await
+        .expect("scan should succeed");
+        // Both projects are hidden as empty, but only the missing one warns
+        assert!(result.projects.is_empty());
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(result.warnings[0].provider, "opencode");
+        assert!(result.warnings[0].error.contains("proj_missing"));
+    }
 }
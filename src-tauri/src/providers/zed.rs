@@ -0,0 +1,984 @@
+use super::{Provider, ProviderId, ProviderInfo};
+use crate::models::{ClaudeMessage, ClaudeProject, ClaudeSession};
+use crate::utils::{is_safe_storage_id, SearchPattern, SearchScope};
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OpenFlags};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Zed saves assistant conversations as JSON files:
+//   {base}/conversations/*.json
+// in one of two shapes:
+// - text threads hold the whole buffer in `text`, with `messages[]` marking
+//   where each message starts (`start`, a byte offset) and its
+//   `metadata.role`
+// - agent threads have `messages[]` with `role`, `segments` (text/thinking),
+//   `tool_uses` and `tool_results`, plus `summary`, `updated_at`, `model` and
+//   the worktrees open when the thread began (`initial_project_snapshot`)
+// Newer Zed builds keep agent threads in `{base}/threads/threads.db` instead:
+// a SQLite `threads` table of `id`, `summary`, `updated_at`, `data_type` and
+// `data`, the JSON of the second shape (zstd-compressed when `data_type` is
+// "zstd"). Those sessions get a `zed-thread://{id}` path.
+// Neither shape records per-message times, so messages carry the thread's
+// `updated_at` or the file's modification time.
+
+/// Zed's data directory relative to the home directory
+#[cfg(target_os = "macos")]
+const ZED_DATA_DIR: &str = "Library/Application Support/Zed";
+#[cfg(target_os = "windows")]
+const ZED_DATA_DIR: &str = "AppData/Local/Zed";
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const ZED_DATA_DIR: &str = ".local/share/zed";
+
+/// Project path prefix; the rest is the worktree path, empty for threads
+/// without one
+const PROJECT_PREFIX: &str = "zed://";
+
+/// Session path prefix of threads stored in `threads.db`; the rest is the
+/// thread id
+const DB_THREAD_PREFIX: &str = "zed-thread://";
+
+/// Detect Zed installation
+pub fn detect() -> Option<ProviderInfo> {
+    let base_path = get_base_path()?;
+    let conversations = conversation_dirs();
+    let threads_db = threads_db_path();
+
+    Some(ProviderInfo {
+        id: "zed".to_string(),
+        display_name: "Zed".to_string(),
+        base_path,
+        is_available: !conversations.is_empty() || threads_db.is_some(),
+        // conversations/*.json, threads/threads.db and its WAL
+        last_activity: super::activity_timestamp(
+            conversations
+                .iter()
+                .map(PathBuf::as_path)
+                .chain(threads_db.as_deref().and_then(Path::parent))
+                .filter_map(|dir| super::latest_mtime(dir, 1))
+                .max(),
+        ),
+    })
+}
+
+/// Get the Zed data directory (`$ZED_DATA_HOME`, default
+/// `~/.local/share/zed`, or `~/Library/Application Support/Zed` on macOS)
+pub fn get_base_path() -> Option<String> {
+    super::resolve_base_path("Zed", "ZED_DATA_HOME", ZED_DATA_DIR)
+}
+
+/// Existing directories with saved conversations. On macOS Zed writes text
+/// threads to `~/.config/zed/conversations`, which is included unless
+/// `$ZED_DATA_HOME` points elsewhere.
+fn conversation_dirs() -> Vec<PathBuf> {
+    let mut candidates: Vec<PathBuf> = get_base_path()
+        .map(|base| Path::new(&base).join("conversations"))
+        .into_iter()
+        .collect();
+    if cfg!(target_os = "macos") && std::env::var_os("ZED_DATA_HOME").is_none() {
+        if let Some(home) = dirs::home_dir() {
+            candidates.push(home.join(".config").join("zed").join("conversations"));
+        }
+    }
+    candidates.retain(|dir| dir.is_dir());
+    candidates.dedup();
+    candidates
+}
+
+/// Zed's agent thread database, if there is one
+fn threads_db_path() -> Option<PathBuf> {
+    let db = Path::new(&get_base_path()?)
+        .join("threads")
+        .join("threads.db");
+    db.is_file().then_some(db)
+}
+
+/// Saved conversation files across all conversation directories
+fn list_thread_files() -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = conversation_dirs()
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(Iterator::flatten)
+        .filter(|e| !e.file_type().map_or(true, |ft| ft.is_symlink()))
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    files
+}
+
+fn read_thread_file(path: &Path) -> Option<Value> {
+    let content = fs::read_to_string(path).ok()?;
+    let val: Value = serde_json::from_str(&content).ok()?;
+    val.get("messages")
+        .is_some_and(Value::is_array)
+        .then_some(val)
+}
+
+/// A saved thread, from a conversation file or a `threads.db` row
+struct Thread {
+    val: Value,
+    id: String,
+    /// The conversation file, or `zed-thread://{id}` for database threads
+    session_path: String,
+    updated_at: String,
+    last_modified: String,
+}
+
+impl Thread {
+    fn from_file(path: &Path) -> Option<Self> {
+        let val = read_thread_file(path)?;
+        Some(Self {
+            id: thread_id(&val, path),
+            session_path: path.to_string_lossy().to_string(),
+            updated_at: thread_updated_at(&val, path),
+            last_modified: file_modified_rfc3339(path).unwrap_or_default(),
+            val,
+        })
+    }
+
+    fn worktree(&self) -> String {
+        thread_worktree(&self.val).unwrap_or_default()
+    }
+
+    fn messages(&self) -> Vec<ClaudeMessage> {
+        convert_thread(&self.val, &self.id, &self.updated_at)
+    }
+}
+
+/// Every readable thread: conversation files, then `threads.db` rows. A
+/// database that can't be read is logged and skipped.
+fn list_threads() -> Vec<Thread> {
+    let mut threads: Vec<Thread> = list_thread_files()
+        .iter()
+        .filter_map(|path| Thread::from_file(path))
+        .collect();
+    if let Some(db) = threads_db_path() {
+        match read_db_threads(&db, None) {
+            Ok(rows) => threads.extend(rows),
+            Err(e) => log::warn!("Skipping Zed thread database {}: {e}", db.display()),
+        }
+    }
+    threads
+}
+
+/// Threads stored in `threads.db`, or only the one with `only_id`. Rows whose
+/// data can't be decoded are skipped.
+fn read_db_threads(db: &Path, only_id: Option<&str>) -> Result<Vec<Thread>, String> {
+    // Zed keeps the database open; a read-only connection doesn't block it
+    let conn = Connection::open_with_flags(
+        db,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(|e| format!("Failed to open Zed thread database: {e}"))?;
+    let mut stmt = conn
+        .prepare("SELECT id, updated_at, data_type, data FROM threads WHERE ?1 IS NULL OR id = ?1")
+        .map_err(|e| format!("Failed to query Zed threads: {e}"))?;
+    let rows = stmt
+        .query_map([only_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Vec<u8>>(3)?,
+            ))
+        })
+        .map_err(|e| format!("Failed to query Zed threads: {e}"))?;
+
+    let threads = rows
+        .filter_map(Result::ok)
+        .filter_map(|(id, updated_at, data_type, data)| {
+            let val = decode_thread_data(&data_type, &data)?;
+            Some(Thread {
+                session_path: format!("{DB_THREAD_PREFIX}{id}"),
+                last_modified: updated_at.clone(),
+                updated_at,
+                id,
+                val,
+            })
+        })
+        .collect();
+    Ok(threads)
+}
+
+/// Thread JSON of a `threads.db` row; `data_type` is "zstd" or "json"
+fn decode_thread_data(data_type: &str, data: &[u8]) -> Option<Value> {
+    let val: Value = match data_type {
+        "zstd" => serde_json::from_slice(&zstd::decode_all(data).ok()?).ok()?,
+        "json" => serde_json::from_slice(data).ok()?,
+        _ => return None,
+    };
+    val.get("messages")
+        .is_some_and(Value::is_array)
+        .then_some(val)
+}
+
+fn file_modified_rfc3339(path: &Path) -> Option<String> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(DateTime::<Utc>::from(modified).to_rfc3339())
+}
+
+/// Make sure a session path points into a conversation directory
+fn validate_session_path(raw_session_path: &str) -> Result<PathBuf, String> {
+    let session = Path::new(raw_session_path)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve session path: {e}"))?;
+    let inside = conversation_dirs()
+        .iter()
+        .filter_map(|dir| dir.canonicalize().ok())
+        .any(|dir| session.starts_with(dir));
+
+    if !inside {
+        return Err(format!(
+            "Session path is outside Zed storage: {raw_session_path}"
+        ));
+    }
+    Ok(session)
+}
+
+/// Scan Zed projects: one per worktree that agent threads started in, plus
+/// one for threads without a worktree (all text threads)
+pub fn scan_projects() -> Result<Vec<ClaudeProject>, String> {
+    Ok(projects_of(&list_threads()))
+}
+
+fn projects_of(threads: &[Thread]) -> Vec<ClaudeProject> {
+    let mut projects: Vec<ClaudeProject> = Vec::new();
+
+    for thread in threads {
+        let actual_path = thread.worktree();
+        let last_modified = thread.updated_at.clone();
+        let project_path = format!("{PROJECT_PREFIX}{actual_path}");
+
+        if let Some(project) = projects.iter_mut().find(|p| p.path == project_path) {
+            project.session_count += 1;
+            if last_modified > project.last_modified {
+                project.last_modified = last_modified;
+            }
+            continue;
+        }
+
+        let name = Path::new(&actual_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Zed Assistant".to_string());
+        projects.push(ClaudeProject {
+            name,
+            path: project_path,
+            path_exists: Path::new(&actual_path).is_dir(),
+            actual_path,
+            session_count: 1,
+            message_count: 0,
+            last_modified,
+            git_info: None,
+            provider: Some("zed".to_string()),
+        });
+    }
+
+    projects.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
+    projects
+}
+
+/// Load sessions for a Zed project
+pub fn load_sessions(
+    project_path: &str,
+    _exclude_sidechain: bool,
+) -> Result<Vec<ClaudeSession>, String> {
+    let worktree = project_path
+        .strip_prefix(PROJECT_PREFIX)
+        .ok_or_else(|| format!("Invalid Zed project path: {project_path}"))?;
+    let mut sessions = Vec::new();
+
+    for thread in list_threads() {
+        if thread.worktree() != worktree {
+            continue;
+        }
+
+        let val = &thread.val;
+        let raw_messages = val
+            .get("messages")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let messages = thread.messages();
+        let blocks_of = |kind: &'static str| {
+            messages
+                .iter()
+                .filter_map(|m| m.content.as_ref()?.as_array())
+                .flatten()
+                .filter(move |block| block.get("type").and_then(Value::as_str) == Some(kind))
+        };
+        let has_tool_use = blocks_of("tool_use").next().is_some();
+        let has_errors = blocks_of("tool_result")
+            .any(|block| block.get("is_error").and_then(Value::as_bool) == Some(true));
+        let summary = val
+            .get("summary")
+            .and_then(Value::as_str)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .or_else(|| {
+                messages
+                    .iter()
+                    .find(|m| m.message_type == "user")
+                    .and_then(|m| first_text(m.content.as_ref()?))
+            });
+
+        sessions.push(ClaudeSession {
+            session_id: format!("zed://{}", thread.id),
+            actual_session_id: thread.id.clone(),
+            file_path: thread.session_path.clone(),
+            project_name: String::new(),
+            message_count: raw_messages.len(),
+            first_message_time: thread.updated_at.clone(),
+            last_message_time: thread.updated_at.clone(),
+            last_modified: thread.last_modified.clone(),
+            has_tool_use,
+            has_errors,
+            summary,
+            provider: Some("zed".to_string()),
+            data: None,
+        });
+    }
+
+    sessions.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
+    Ok(sessions)
+}
+
+/// Load messages from a saved Zed conversation or a `zed-thread://{id}`
+/// database thread
+pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
+    let thread = if let Some(id) = session_path.strip_prefix(DB_THREAD_PREFIX) {
+        if !is_safe_storage_id(id) {
+            return Err(format!("Invalid Zed thread id: {id}"));
+        }
+        let db = threads_db_path().ok_or_else(|| "Zed thread database not found".to_string())?;
+        read_db_threads(&db, Some(id))?
+            .pop()
+            .ok_or_else(|| format!("Zed thread not found: {id}"))?
+    } else {
+        let path = validate_session_path(session_path)?;
+        Thread::from_file(&path)
+            .ok_or_else(|| format!("Failed to parse Zed conversation: {session_path}"))?
+    };
+    Ok(thread.messages())
+}
+
+/// Search Zed conversations for a query string
+pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
+    search_matching(&SearchPattern::plain(query), &SearchScope::default(), limit)
+}
+
+/// [`search`] with an explicit [`SearchPattern`], e.g. a regex, keeping only
+/// messages in `scope`
+pub fn search_matching(
+    pattern: &SearchPattern,
+    scope: &SearchScope,
+    limit: usize,
+) -> Result<Vec<ClaudeMessage>, String> {
+    // Parse every thread once and search them grouped by project
+    let threads = list_threads();
+    let mut results = Vec::new();
+
+    for project in projects_of(&threads) {
+        for thread in threads
+            .iter()
+            .filter(|t| t.worktree() == project.actual_path)
+        {
+            for mut msg in thread.messages() {
+                if results.len() >= limit {
+                    return Ok(results);
+                }
+                if scope.includes(&msg)
+                    && msg
+                        .content
+                        .as_ref()
+                        .is_some_and(|c| pattern.matches_content(c))
+                {
+                    msg.project_name = Some(project.name.clone());
+                    msg.session_path = Some(thread.session_path.clone());
+                    msg.project_path = Some(project.path.clone());
+                    results.push(msg);
+                }
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+// ============================================================================
+// Internal helpers
+// ============================================================================
+
+/// Text threads carry their buffer in `text`; agent threads don't
+fn is_text_thread(val: &Value) -> bool {
+    val.get("text").is_some_and(Value::is_string)
+}
+
+fn thread_id(val: &Value, path: &Path) -> String {
+    val.get("id")
+        .and_then(Value::as_str)
+        .filter(|id| !id.is_empty())
+        .map(String::from)
+        .unwrap_or_else(|| {
+            path.file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string()
+        })
+}
+
+fn thread_updated_at(val: &Value, path: &Path) -> String {
+    val.get("updated_at")
+        .and_then(Value::as_str)
+        .map(String::from)
+        .or_else(|| file_modified_rfc3339(path))
+        .unwrap_or_else(|| Utc::now().to_rfc3339())
+}
+
+/// First worktree open when an agent thread started
+fn thread_worktree(val: &Value) -> Option<String> {
+    val.get("initial_project_snapshot")?
+        .get("worktree_snapshots")?
+        .as_array()?
+        .iter()
+        .find_map(|w| w.get("worktree_path").and_then(Value::as_str))
+        .filter(|p| !p.is_empty())
+        .map(String::from)
+}
+
+fn first_text(content: &Value) -> Option<String> {
+    content
+        .as_array()?
+        .iter()
+        .find(|block| block.get("type").and_then(Value::as_str) == Some("text"))
+        .and_then(|block| block.get("text").and_then(Value::as_str))
+        .map(String::from)
+}
+
+/// Claude Code tool names for Zed's built-in agent tools, so the existing
+/// renderers pick them up
+fn map_zed_tool_name(name: &str) -> &str {
+    match name {
+        "read_file" => "Read",
+        "terminal" => "Bash",
+        "grep" => "Grep",
+        "find_path" => "Glob",
+        "fetch" => "WebFetch",
+        "web_search" => "WebSearch",
+        _ => name,
+    }
+}
+
+fn normalize_zed_tool_input(tool_name: &str, input: Value) -> Value {
+    let Value::Object(mut input_obj) = input else {
+        return input;
+    };
+
+    let renames: &[(&str, &str)] = match tool_name {
+        "Read" => &[("path", "file_path")],
+        "Grep" => &[("regex", "pattern"), ("include_pattern", "glob")],
+        "Glob" => &[("glob", "pattern")],
+        _ => &[],
+    };
+    for (from, to) in renames {
+        if !input_obj.contains_key(*to) {
+            if let Some(value) = input_obj.remove(*from) {
+                input_obj.insert((*to).to_string(), value);
+            }
+        }
+    }
+
+    Value::Object(input_obj)
+}
+
+/// Tool result content is a plain string or a tagged `{"Text": ...}` value
+fn tool_result_content(result: &Value) -> Value {
+    match result.get("content") {
+        Some(Value::Object(obj)) => obj
+            .get("Text")
+            .or_else(|| obj.get("text"))
+            .cloned()
+            .unwrap_or_else(|| Value::Object(obj.clone())),
+        Some(content) => content.clone(),
+        None => Value::String(String::new()),
+    }
+}
+
+fn convert_thread(val: &Value, session_id: &str, timestamp: &str) -> Vec<ClaudeMessage> {
+    let model = val
+        .get("model")
+        .and_then(|m| m.get("model"))
+        .and_then(Value::as_str);
+    let messages = val
+        .get("messages")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    if is_text_thread(val) {
+        let text = val.get("text").and_then(Value::as_str).unwrap_or_default();
+        return text_thread_messages(text, messages)
+            .into_iter()
+            .enumerate()
+            .map(|(index, (role, body))| {
+                let content = json!([{ "type": "text", "text": body }]);
+                build_message(session_id, index, role, content, timestamp, None)
+            })
+            .collect();
+    }
+
+    messages
+        .iter()
+        .enumerate()
+        .filter_map(|(index, msg)| {
+            let role = msg.get("role").and_then(Value::as_str)?;
+            if role != "user" && role != "assistant" {
+                return None;
+            }
+            let content = agent_message_content(msg, session_id, index)?;
+            let model = (role == "assistant").then_some(model).flatten();
+            Some(build_message(
+                session_id, index, role, content, timestamp, model,
+            ))
+        })
+        .collect()
+}
+
+/// `(role, text)` of each user/assistant message in a text thread, sliced
+/// from the buffer between consecutive message starts
+fn text_thread_messages<'a>(text: &'a str, messages: &'a [Value]) -> Vec<(&'a str, &'a str)> {
+    let mut starts: Vec<(usize, &str)> = messages
+        .iter()
+        .filter_map(|msg| {
+            let start = usize::try_from(msg.get("start")?.as_u64()?).ok()?;
+            let role = msg.get("metadata")?.get("role")?.as_str()?;
+            Some((start.min(text.len()), role))
+        })
+        .collect();
+    starts.sort_by_key(|(start, _)| *start);
+
+    starts
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, role))| *role == "user" || *role == "assistant")
+        .filter_map(|(i, &(start, role))| {
+            let end = starts.get(i + 1).map_or(text.len(), |(next, _)| *next);
+            let body = text.get(start..end)?.trim();
+            (!body.is_empty()).then_some((role, body))
+        })
+        .collect()
+}
+
+/// Canonical content blocks of an agent thread message: text and thinking
+/// segments, then each tool use followed by its result
+fn agent_message_content(msg: &Value, session_id: &str, index: usize) -> Option<Value> {
+    let mut content_items = Vec::new();
+
+    for segment in msg
+        .get("segments")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let text = segment
+            .get("text")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        if text.trim().is_empty() {
+            continue;
+        }
+        match segment.get("type").and_then(Value::as_str) {
+            Some("text") => content_items.push(json!({ "type": "text", "text": text })),
+            Some("thinking") => {
+                content_items.push(json!({ "type": "thinking", "thinking": text }));
+            }
+            _ => {}
+        }
+    }
+
+    let results = msg
+        .get("tool_results")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for (call_index, tool_use) in msg
+        .get("tool_uses")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        let tool_id = tool_use
+            .get("id")
+            .and_then(Value::as_str)
+            .map(String::from)
+            .unwrap_or_else(|| format!("{session_id}-tool-{index}-{call_index}"));
+        let name = map_zed_tool_name(
+            tool_use
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown"),
+        );
+        let input = normalize_zed_tool_input(
+            name,
+            tool_use.get("input").cloned().unwrap_or_else(|| json!({})),
+        );
+        content_items.push(json!({
+            "type": "tool_use",
+            "id": tool_id,
+            "name": name,
+            "input": input,
+        }));
+
+        if let Some(result) = results
+            .iter()
+            .find(|r| r.get("tool_use_id").and_then(Value::as_str) == Some(&tool_id))
+        {
+            let mut tool_result = json!({
+                "type": "tool_result",
+                "tool_use_id": tool_id,
+                "content": tool_result_content(result),
+            });
+            if result.get("is_error").and_then(Value::as_bool) == Some(true) {
+                tool_result["is_error"] = Value::Bool(true);
+            }
+            content_items.push(tool_result);
+        }
+    }
+
+    (!content_items.is_empty()).then_some(Value::Array(content_items))
+}
+
+fn build_message(
+    session_id: &str,
+    index: usize,
+    role: &str,
+    content: Value,
+    timestamp: &str,
+    model: Option<&str>,
+) -> ClaudeMessage {
+    ClaudeMessage {
+        uuid: format!("{session_id}-{index}"),
+        parent_uuid: None,
+        session_id: session_id.to_string(),
+        timestamp: timestamp.to_string(),
+        message_type: role.to_string(),
+        content: Some(content),
+        project_name: None,
+        tool_use: None,
+        tool_use_result: None,
+        is_sidechain: None,
+        usage: None,
+        role: Some(role.to_string()),
+        model: model.map(String::from),
+        stop_reason: None,
+        cost_usd: None,
+        duration_ms: None,
+        message_id: None,
+        snapshot: None,
+        is_snapshot_update: None,
+        data: None,
+        tool_use_id: None,
+        parent_tool_use_id: None,
+        operation: None,
+        subtype: None,
+        level: None,
+        hook_count: None,
+        hook_infos: None,
+        stop_reason_system: None,
+        prevented_continuation: None,
+        compact_metadata: None,
+        microcompact_metadata: None,
+        provider: Some("zed".to_string()),
+        match_locations: None,
+        session_path: None,
+        project_path: None,
+    }
+}
+
+/// Zed entry in the provider [`registry`](super::registry)
+pub struct ZedProvider;
+
+impl Provider for ZedProvider {
+    fn id(&self) -> ProviderId {
+        ProviderId::Zed
+    }
+
+    fn detect(&self) -> Option<ProviderInfo> {
+        detect()
+    }
+
+    fn scan_projects(&self) -> Result<Vec<ClaudeProject>, String> {
+        scan_projects()
+    }
+
+    fn scan_roots(&self) -> Vec<PathBuf> {
+        let mut roots = conversation_dirs();
+        roots.extend(threads_db_path().and_then(|db| db.parent().map(Path::to_path_buf)));
+        roots
+    }
+
+    fn scan_depth(&self) -> usize {
+        // conversations/*.json, threads/threads.db and its WAL
+        1
+    }
+
+    fn load_sessions(
+        &self,
+        project_path: &str,
+        exclude_sidechain: bool,
+    ) -> Result<Vec<ClaudeSession>, String> {
+        load_sessions(project_path, exclude_sidechain)
+    }
+
+    fn load_messages(&self, session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
+        load_messages(session_path)
+    }
+
+    fn search(
+        &self,
+        pattern: &SearchPattern,
+        scope: &SearchScope,
+        limit: usize,
+    ) -> Result<Vec<ClaudeMessage>, String> {
+        search_matching(pattern, scope, limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::EnvVarGuard;
+    use serial_test::serial;
+    use tempfile::TempDir;
+
+    fn setup_zed_home() -> (TempDir, PathBuf, EnvVarGuard) {
+        let tmp = TempDir::new().expect("temp dir should be created");
+        let conversations = tmp.path().join("conversations");
+        fs::create_dir_all(&conversations).expect("conversations dir should be created");
+        let guard = EnvVarGuard::set("ZED_DATA_HOME", tmp.path());
+        (tmp, conversations, guard)
+    }
+
+    fn write_thread(dir: &Path, name: &str, thread: &Value) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, thread.to_string()).expect("thread file should be written");
+        path
+    }
+
+    #[test]
+    #[serial]
+    fn loads_agent_thread_with_canonical_tool_blocks() {
+        let (tmp, conversations, _guard) = setup_zed_home();
+        let worktree = tmp.path().join("my-app");
+        fs::create_dir_all(&worktree).expect("worktree should be created");
+        let worktree = worktree.to_string_lossy().to_string();
+        write_thread(
+            &conversations,
+            "agent.json",
+            &json!({
+                "version": "0.2.0",
+                "summary": "Fix the build",
+                "updated_at": "2025-06-01T10:00:00Z",
+                "model": { "provider": "anthropic", "model": "claude-sonnet-4" },
+                "initial_project_snapshot": {
+                    "worktree_snapshots": [{ "worktree_path": worktree }]
+                },
+                "messages": [
+                    {
+                        "id": 0,
+                        "role": "user",
+                        "segments": [{ "type": "text", "text": "Why does the build fail?" }],
+                        "tool_uses": [],
+                        "tool_results": []
+                    },
+                    {
+                        "id": 1,
+                        "role": "assistant",
+                        "segments": [
+                            { "type": "thinking", "text": "Check the manifest", "signature": "sig" },
+                            { "type": "text", "text": "Let me look." }
+                        ],
+                        "tool_uses": [
+                            { "id": "tool-1", "name": "read_file", "input": { "path": "Cargo.toml" } },
+                            { "id": "tool-2", "name": "terminal", "input": { "command": "cargo build", "cd": "my-app" } }
+                        ],
+                        "tool_results": [
+                            { "tool_use_id": "tool-1", "is_error": false, "content": { "Text": "[package]" } },
+                            { "tool_use_id": "tool-2", "is_error": true, "content": "error[E0425]" }
+                        ]
+                    }
+                ]
+            }),
+        );
+
+        let projects = scan_projects().expect("scan should succeed");
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].name, "my-app");
+        assert_eq!(projects[0].path, format!("zed://{worktree}"));
+        assert!(projects[0].path_exists);
+
+        let sessions = load_sessions(&projects[0].path, false).expect("sessions should load");
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].summary.as_deref(), Some("Fix the build"));
+        assert!(sessions[0].has_tool_use);
+        assert!(sessions[0].has_errors);
+
+        let messages = load_messages(&sessions[0].file_path).expect("messages should load");
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].message_type, "user");
+        assert_eq!(messages[0].model, None);
+        assert_eq!(messages[1].model.as_deref(), Some("claude-sonnet-4"));
+        assert_eq!(messages[1].timestamp, "2025-06-01T10:00:00Z");
+
+        let content = messages[1].content.as_ref().expect("content should be set");
+        assert_eq!(content[0]["type"], "thinking");
+        assert_eq!(content[1]["text"], "Let me look.");
+        assert_eq!(content[2]["type"], "tool_use");
+        assert_eq!(content[2]["name"], "Read");
+        assert_eq!(content[2]["input"]["file_path"], "Cargo.toml");
+        assert_eq!(content[3]["type"], "tool_result");
+        assert_eq!(content[3]["tool_use_id"], "tool-1");
+        assert_eq!(content[3]["content"], "[package]");
+        assert_eq!(content[4]["name"], "Bash");
+        assert_eq!(content[4]["input"]["command"], "cargo build");
+        assert_eq!(content[5]["is_error"], true);
+    }
+
+    #[test]
+    #[serial]
+    fn loads_text_thread_by_slicing_the_buffer() {
+        let (_tmp, conversations, _guard) = setup_zed_home();
+        let text = "Explain lifetimes\nA lifetime names a scope.\n";
+        let second_start = text
+            .find("A lifetime")
+            .expect("assistant text should exist");
+        write_thread(
+            &conversations,
+            "Lifetimes - 1.zed.json",
+            &json!({
+                "id": "ctx-1",
+                "zed": "context",
+                "version": "0.4.0",
+                "text": text,
+                "summary": "Lifetimes",
+                "messages": [
+                    { "id": { "replica_id": 0, "value": 0 }, "start": 0,
+                      "metadata": { "role": "user", "status": "Done" } },
+                    { "id": { "replica_id": 0, "value": 1 }, "start": second_start,
+                      "metadata": { "role": "assistant", "status": "Done" } }
+                ]
+            }),
+        );
+
+        let projects = scan_projects().expect("scan should succeed");
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].path, "zed://");
+        assert_eq!(projects[0].name, "Zed Assistant");
+
+        let sessions = load_sessions("zed://", false).expect("sessions should load");
+        assert_eq!(sessions[0].actual_session_id, "ctx-1");
+        assert!(!sessions[0].has_tool_use);
+
+        let messages = load_messages(&sessions[0].file_path).expect("messages should load");
+        let texts: Vec<_> = messages
+            .iter()
+            .map(|m| {
+                (
+                    m.message_type.as_str(),
+                    first_text(m.content.as_ref().unwrap()),
+                )
+            })
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                ("user", Some("Explain lifetimes".to_string())),
+                ("assistant", Some("A lifetime names a scope.".to_string())),
+            ]
+        );
+
+        let hits = search("scope", 10).expect("search should succeed");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].provider.as_deref(), Some("zed"));
+    }
+
+    #[test]
+    #[serial]
+    fn loads_zstd_compressed_threads_from_the_database() {
+        let (tmp, _conversations, _guard) = setup_zed_home();
+        let threads_dir = tmp.path().join("threads");
+        fs::create_dir_all(&threads_dir).expect("threads dir should be created");
+        let conn = Connection::open(threads_dir.join("threads.db")).expect("db should open");
+        conn.execute_batch(
+            "CREATE TABLE threads (id TEXT PRIMARY KEY, summary TEXT NOT NULL, \
+             updated_at TEXT NOT NULL, data_type TEXT NOT NULL, data BLOB NOT NULL)",
+        )
+        .expect("table should be created");
+        let thread = json!({
+            "version": "0.2.0",
+            "summary": "Rename the crate",
+            "updated_at": "2025-08-01T09:00:00Z",
+            "initial_project_snapshot": {
+                "worktree_snapshots": [{ "worktree_path": "/work/crate" }]
+            },
+            "messages": [
+                { "id": 0, "role": "user", "segments": [{ "type": "text", "text": "Rename the crate to hv" }],
+                  "tool_uses": [], "tool_results": [] },
+                { "id": 1, "role": "assistant", "segments": [{ "type": "text", "text": "Done, renamed in Cargo.toml" }],
+                  "tool_uses": [], "tool_results": [] }
+            ]
+        });
+        let compressed =
+            zstd::encode_all(thread.to_string().as_bytes(), 0).expect("data should compress");
+        conn.execute(
+            "INSERT INTO threads VALUES (?1, ?2, ?3, 'zstd', ?4)",
+            rusqlite::params![
+                "a1b2c3",
+                "Rename the crate",
+                "2025-08-01T09:00:00Z",
+                compressed
+            ],
+        )
+        .expect("row should be inserted");
+        conn.execute(
+            "INSERT INTO threads VALUES ('broken', '', '2025-08-01T09:00:00Z', 'zstd', x'00')",
+            [],
+        )
+        .expect("row should be inserted");
+        drop(conn);
+
+        let projects = scan_projects().expect("scan should succeed");
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].path, "zed:///work/crate");
+
+        let sessions = load_sessions(&projects[0].path, false).expect("sessions should load");
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].file_path, "zed-thread://a1b2c3");
+        assert_eq!(sessions[0].summary.as_deref(), Some("Rename the crate"));
+
+        let messages = load_messages(&sessions[0].file_path).expect("messages should load");
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].timestamp, "2025-08-01T09:00:00Z");
+        assert_eq!(
+            first_text(messages[1].content.as_ref().unwrap()).as_deref(),
+            Some("Done, renamed in Cargo.toml")
+        );
+        assert!(load_messages("zed-thread://../threads").is_err());
+        assert!(load_messages("zed-thread://missing").is_err());
+
+        let hits = search("cargo.toml", 10).expect("search should succeed");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_path.as_deref(), Some("zed-thread://a1b2c3"));
+    }
+
+    #[test]
+    #[serial]
+    fn load_messages_rejects_paths_outside_storage() {
+        let (tmp, _conversations, _guard) = setup_zed_home();
+        let outside = write_thread(tmp.path(), "outside.json", &json!({ "messages": [] }));
+        assert!(load_messages(&outside.to_string_lossy()).is_err());
+    }
+}
@@ -7,9 +7,10 @@
 
 use crate::models::*;
 use serde_json::json;
+use std::ffi::OsString;
 use std::fs::{self, File};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
 // Re-export commonly used test utilities
@@ -202,6 +203,9 @@ impl MessageBuilder {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            match_locations: None,
+            session_path: None,
+            project_path: None,
         }
     }
 
@@ -252,6 +256,32 @@ pub fn create_jsonl_content(messages: &[MessageBuilder]) -> String {
         .join("\n")
 }
 
+/// Sets an environment variable (e.g. a provider's data home) for the
+/// guard's lifetime and restores the previous value on drop. Tests using it
+/// should be `#[serial]`.
+pub struct EnvVarGuard {
+    key: &'static str,
+    original: Option<OsString>,
+}
+
+impl EnvVarGuard {
+    pub fn set(key: &'static str, value: &Path) -> Self {
+        let original = std::env::var_os(key);
+        std::env::set_var(key, value);
+        Self { key, original }
+    }
+}
+
+impl Drop for EnvVarGuard {
+    fn drop(&mut self) {
+        if let Some(value) = &self.original {
+            std::env::set_var(self.key, value);
+        } else {
+            std::env::remove_var(self.key);
+        }
+    }
+}
+
 /// Proptest strategies for generating test data
 pub mod strategies {
     use super::*;
@@ -1,7 +1,12 @@
-use crate::models::{GitInfo, GitWorktreeType};
+use crate::models::{ClaudeMessage, GitInfo, GitWorktreeType, MatchLocation};
 use chrono::{DateTime, Utc};
 use memchr::memchr_iter;
+use regex::{Regex, RegexBuilder};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::borrow::Cow;
 use std::fs;
+use std::io::Write;
 use std::path::{Component, Path};
 
 /// Estimated average bytes per JSONL line (used for capacity pre-allocation)
@@ -77,6 +82,53 @@ pub fn parse_rfc3339_utc(timestamp: &str) -> Option<DateTime<Utc>> {
         .map(|dt| dt.with_timezone(&Utc))
 }
 
+/// Percent-decode `value`, keeping it as-is if the result isn't valid UTF-8
+fn percent_decode(value: &str) -> String {
+    urlencoding::decode(value).map_or_else(|_| value.to_string(), Cow::into_owned)
+}
+
+/// UNC form (`\\host\rest`) of a host plus `/`-separated path
+fn to_unc_path(host: &str, path: &str) -> String {
+    format!("\\\\{host}{}", path.replace('/', "\\"))
+}
+
+/// Convert a `file://` or `vscode-remote://` URI into a filesystem path;
+/// anything else is returned unchanged.
+///
+/// - `file:///c%3A/Users/me` becomes `c:\Users\me` (Windows drive letter)
+/// - `file:///home/me` and `file://localhost/home/me` become `/home/me`
+/// - `file://wsl.localhost/Ubuntu/home/me` (or any other host) becomes the
+///   UNC path `\\wsl.localhost\Ubuntu\home\me`
+/// - `vscode-remote://wsl+Ubuntu/home/me` becomes the same UNC path; other
+///   remotes (SSH, containers) yield the remote path, `/home/me`
+pub fn uri_to_path(value: &str) -> String {
+    if let Some(rest) = value.strip_prefix("vscode-remote://") {
+        let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        let authority = percent_decode(authority);
+        let path = percent_decode(path);
+        return match authority.strip_prefix("wsl+") {
+            Some(distro) => to_unc_path("wsl.localhost", &format!("/{distro}{path}")),
+            None => path,
+        };
+    }
+
+    let Some(rest) = value.strip_prefix("file://") else {
+        return value.to_string();
+    };
+    let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let path = percent_decode(path);
+    if !host.is_empty() && !host.eq_ignore_ascii_case("localhost") {
+        return to_unc_path(&percent_decode(host), &path);
+    }
+
+    let bytes = path.as_bytes();
+    if bytes.len() >= 3 && bytes[0] == b'/' && bytes[1].is_ascii_alphabetic() && bytes[2] == b':' {
+        path[1..].replace('/', "\\")
+    } else {
+        path
+    }
+}
+
 /// Validates that `id` is a single, safe path component (no traversal).
 ///
 /// Returns `true` only if `id` is a single normal component (e.g. `"abc-123"`).
@@ -106,6 +158,374 @@ pub fn search_json_value_case_insensitive(value: &serde_json::Value, query_lower
     }
 }
 
+/// Content keys that carry structure (block kinds, ids, signatures, binary
+/// payloads) rather than text a user would search for.
+const NON_TEXT_CONTENT_KEYS: &[&str] = &["type", "id", "tool_use_id", "signature", "source"];
+
+/// Recursively searches the human-readable text of message content for a
+/// lowercase query.
+///
+/// Unlike [`search_json_value_case_insensitive`], string values under
+/// structural keys (see `NON_TEXT_CONTENT_KEYS`) are skipped, so a query like
+/// `"tool_use"` or a call id doesn't match every tool block.
+///
+/// `query_lower` must already be lowercased by the caller.
+pub fn content_text_matches_case_insensitive(value: &serde_json::Value, query_lower: &str) -> bool {
+    match value {
+        serde_json::Value::String(s) => s.to_lowercase().contains(query_lower),
+        serde_json::Value::Array(arr) => arr
+            .iter()
+            .any(|item| content_text_matches_case_insensitive(item, query_lower)),
+        serde_json::Value::Object(obj) => obj.iter().any(|(key, v)| {
+            !NON_TEXT_CONTENT_KEYS.contains(&key.as_str())
+                && content_text_matches_case_insensitive(v, query_lower)
+        }),
+        _ => false,
+    }
+}
+
+/// Locate each occurrence of a lowercase query in `text`, tagging whether it
+/// fell inside a triple-backtick fenced code block (fence lines included)
+/// or in prose.
+///
+/// Matching is per line, so queries spanning a line break are not reported.
+pub fn text_match_locations(text: &str, query_lower: &str) -> Vec<MatchLocation> {
+    if query_lower.is_empty() {
+        return Vec::new();
+    }
+    line_match_locations(text, &|line| {
+        line.to_lowercase().matches(query_lower).count()
+    })
+}
+
+/// Shared body of the match-location helpers: `hits_in_line` counts the
+/// matches on one line
+fn line_match_locations(text: &str, hits_in_line: &dyn Fn(&str) -> usize) -> Vec<MatchLocation> {
+    let mut locations = Vec::new();
+    let mut in_fence = false;
+    for line in text.lines() {
+        let is_fence = line.trim_start().starts_with("```");
+        let location = if in_fence || is_fence {
+            MatchLocation::Code
+        } else {
+            MatchLocation::Prose
+        };
+        let hits = hits_in_line(line);
+        locations.extend(std::iter::repeat(location).take(hits));
+        if is_fence {
+            in_fence = !in_fence;
+        }
+    }
+    locations
+}
+
+/// Collect [`text_match_locations`] over the human-readable text of message
+/// content, skipping structural keys like [`content_text_matches_case_insensitive`].
+pub fn content_match_locations(value: &serde_json::Value, query_lower: &str) -> Vec<MatchLocation> {
+    content_locations_with(value, &|text| text_match_locations(text, query_lower))
+}
+
+fn content_locations_with(
+    value: &serde_json::Value,
+    text_locations: &dyn Fn(&str) -> Vec<MatchLocation>,
+) -> Vec<MatchLocation> {
+    match value {
+        serde_json::Value::String(s) => text_locations(s),
+        serde_json::Value::Array(arr) => arr
+            .iter()
+            .flat_map(|item| content_locations_with(item, text_locations))
+            .collect(),
+        serde_json::Value::Object(obj) => obj
+            .iter()
+            .filter(|(key, _)| !NON_TEXT_CONTENT_KEYS.contains(&key.as_str()))
+            .flat_map(|(_, v)| content_locations_with(v, text_locations))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Which messages a search may return, checked by each provider before a
+/// match is collected so `limit` only counts messages in scope. The default
+/// scope includes everything.
+#[derive(Debug, Clone, Default)]
+pub struct SearchScope {
+    /// Roles to keep (e.g. `"user"`); a message without a role is checked by
+    /// its type. Compared case-insensitively; an empty list keeps all roles.
+    pub roles: Option<Vec<String>>,
+    /// Keep only messages with a `tool_use` block of this name, compared
+    /// case-insensitively. Names are the canonical Claude Code ones (`Read`,
+    /// `Edit`, `Bash`, ...) that providers normalize to; Gemini CLI tools
+    /// keep their native names (e.g. `run_shell_command`).
+    pub tool: Option<String>,
+}
+
+impl SearchScope {
+    pub fn includes(&self, message: &ClaudeMessage) -> bool {
+        self.includes_role(message) && self.includes_tool(message)
+    }
+
+    fn includes_role(&self, message: &ClaudeMessage) -> bool {
+        let Some(roles) = self.roles.as_ref().filter(|roles| !roles.is_empty()) else {
+            return true;
+        };
+        let role = message.role.as_deref().unwrap_or(&message.message_type);
+        roles.iter().any(|r| r.eq_ignore_ascii_case(role))
+    }
+
+    fn includes_tool(&self, message: &ClaudeMessage) -> bool {
+        let Some(tool) = self.tool.as_deref() else {
+            return true;
+        };
+        let Some(serde_json::Value::Array(blocks)) = message.content.as_ref() else {
+            return false;
+        };
+        blocks.iter().any(|block| {
+            block.get("type").and_then(serde_json::Value::as_str) == Some("tool_use")
+                && block
+                    .get("name")
+                    .and_then(serde_json::Value::as_str)
+                    .is_some_and(|name| name.eq_ignore_ascii_case(tool))
+        })
+    }
+}
+
+/// Characters of context kept on each side of the first match in a snippet
+const SNIPPET_CONTEXT_CHARS: usize = 40;
+
+/// How a search query is matched against message text: a case-insensitive
+/// substring (the default) or a case-insensitive regular expression.
+#[derive(Debug, Clone)]
+pub enum SearchPattern {
+    /// Lowercased query, matched as a substring
+    Plain(String),
+    Regex(Regex),
+}
+
+impl SearchPattern {
+    pub fn plain(query: &str) -> Self {
+        Self::Plain(query.to_lowercase())
+    }
+
+    /// Build a pattern, compiling `query` as a regex when `regex` is set
+    pub fn new(query: &str, regex: bool) -> Result<Self, String> {
+        if !regex {
+            return Ok(Self::plain(query));
+        }
+        RegexBuilder::new(query)
+            .case_insensitive(true)
+            .build()
+            .map(Self::Regex)
+            .map_err(|e| format!("Invalid regex: {e}"))
+    }
+
+    /// Whether the human-readable text of message content matches, skipping
+    /// structural keys like [`content_text_matches_case_insensitive`]
+    pub fn matches_content(&self, value: &serde_json::Value) -> bool {
+        match self {
+            Self::Plain(query_lower) => content_text_matches_case_insensitive(value, query_lower),
+            Self::Regex(re) => content_text_matches_regex(value, re),
+        }
+    }
+
+    /// [`content_match_locations`] for either kind of pattern
+    pub fn content_match_locations(&self, value: &serde_json::Value) -> Vec<MatchLocation> {
+        match self {
+            Self::Plain(query_lower) => content_match_locations(value, query_lower),
+            Self::Regex(re) => content_locations_with(value, &|text| {
+                line_match_locations(text, &|line| re.find_iter(line).count())
+            }),
+        }
+    }
+
+    /// Excerpt of the human-readable text of message content around the
+    /// first match, with the ranges of every match inside it.
+    ///
+    /// Newlines become spaces and cut-off ends are marked with `…`. Ranges
+    /// are in UTF-16 code units (see [`crate::models::SearchHit`]). Returns
+    /// `None` when the content has no text.
+    pub fn snippet(&self, value: &serde_json::Value) -> Option<(String, Vec<(usize, usize)>)> {
+        let mut parts = Vec::new();
+        collect_content_text(value, &mut parts);
+        if parts.is_empty() {
+            return None;
+        }
+        let text = parts.join("\n");
+
+        let matches: Vec<(usize, usize)> = match self {
+            Self::Plain(query_lower) => find_case_insensitive(&text, query_lower),
+            Self::Regex(re) => re
+                .find_iter(&text)
+                .filter(|m| !m.is_empty())
+                .map(|m| (m.start(), m.end()))
+                .collect(),
+        };
+
+        let (first_start, first_end) = matches.first().copied().unwrap_or((0, 0));
+        let start = text[..first_start]
+            .char_indices()
+            .rev()
+            .nth(SNIPPET_CONTEXT_CHARS - 1)
+            .map_or(0, |(i, _)| i);
+        let end = text[first_end..]
+            .char_indices()
+            .nth(SNIPPET_CONTEXT_CHARS)
+            .map_or(text.len(), |(i, _)| first_end + i);
+
+        let prefix = if start > 0 { "…" } else { "" };
+        let suffix = if end < text.len() { "…" } else { "" };
+        let snippet = format!(
+            "{prefix}{}{suffix}",
+            text[start..end].replace(['\n', '\r'], " ")
+        );
+
+        let utf16_offset =
+            |byte: usize| prefix.encode_utf16().count() + text[start..byte].encode_utf16().count();
+        let ranges = matches
+            .into_iter()
+            .filter(|&(s, e)| s >= start && e <= end)
+            .map(|(s, e)| (utf16_offset(s), utf16_offset(e)))
+            .collect();
+        Some((snippet, ranges))
+    }
+}
+
+/// Push every non-empty string of message content, skipping structural keys
+/// like [`content_text_matches_case_insensitive`]
+fn collect_content_text<'a>(value: &'a serde_json::Value, out: &mut Vec<&'a str>) {
+    match value {
+        serde_json::Value::String(s) if !s.is_empty() => out.push(s),
+        serde_json::Value::Array(arr) => {
+            for item in arr {
+                collect_content_text(item, out);
+            }
+        }
+        serde_json::Value::Object(obj) => {
+            for (key, v) in obj {
+                if !NON_TEXT_CONTENT_KEYS.contains(&key.as_str()) {
+                    collect_content_text(v, out);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Byte ranges of non-overlapping occurrences of a lowercase query in `text`,
+/// compared char by char so offsets stay valid even where lowercasing
+/// changes a character's length
+fn find_case_insensitive(text: &str, query_lower: &str) -> Vec<(usize, usize)> {
+    let query: Vec<char> = query_lower.chars().collect();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let lower = |c: char| c.to_lowercase().next().unwrap_or(c);
+
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i + query.len() <= chars.len() {
+        let is_match = query
+            .iter()
+            .zip(&chars[i..])
+            .all(|(&q, &(_, c))| lower(c) == q);
+        if is_match {
+            let end = chars.get(i + query.len()).map_or(text.len(), |&(b, _)| b);
+            ranges.push((chars[i].0, end));
+            i += query.len();
+        } else {
+            i += 1;
+        }
+    }
+    ranges
+}
+
+fn content_text_matches_regex(value: &serde_json::Value, re: &Regex) -> bool {
+    match value {
+        serde_json::Value::String(s) => re.is_match(s),
+        serde_json::Value::Array(arr) => {
+            arr.iter().any(|item| content_text_matches_regex(item, re))
+        }
+        serde_json::Value::Object(obj) => obj.iter().any(|(key, v)| {
+            !NON_TEXT_CONTENT_KEYS.contains(&key.as_str()) && content_text_matches_regex(v, re)
+        }),
+        _ => false,
+    }
+}
+
+// ===== Crash-safe JSON State Files =====
+
+/// Write `value` as pretty-printed JSON to `path` so a crash can never leave
+/// a half-written file: the JSON goes to a uniquely named sibling temp file,
+/// is synced to disk, then renamed over `path`. Missing parent directories
+/// are created.
+pub fn write_json_atomic<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), String> {
+    let content = serde_json::to_vec_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {e}", path.display()))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format!("Invalid file path: {}", path.display()))?
+        .to_string_lossy();
+    let nonce = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let temp_path = path.with_file_name(format!("{file_name}.{nonce}.tmp"));
+
+    let written = fs::File::create(&temp_path).and_then(|mut file| {
+        file.write_all(&content)?;
+        file.sync_all()
+    });
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp_path);
+        return Err(format!("Failed to write temp file: {e}"));
+    }
+
+    crate::commands::fs_utils::atomic_rename(&temp_path, path)
+}
+
+/// Read JSON state written by [`write_json_atomic`].
+///
+/// A missing file yields `fallback()`. So does a file that doesn't parse,
+/// after it is moved aside to `{name}.corrupt-{unix seconds}` for
+/// inspection, so one bad file can't keep the app from starting. Only I/O
+/// errors are returned.
+pub fn read_json_or_recover<T: DeserializeOwned>(
+    path: &Path,
+    fallback: impl FnOnce() -> T,
+) -> Result<T, String> {
+    let content = match fs::read(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(fallback()),
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+
+    match serde_json::from_slice(&content) {
+        Ok(value) => Ok(value),
+        Err(parse_error) => {
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            let backup_path =
+                path.with_file_name(format!("{file_name}.corrupt-{}", Utc::now().timestamp()));
+            match fs::rename(path, &backup_path) {
+                Ok(()) => log::warn!(
+                    "{} is corrupt ({parse_error}); moved it to {} and started fresh",
+                    path.display(),
+                    backup_path.display()
+                ),
+                Err(e) => log::warn!(
+                    "{} is corrupt ({parse_error}) and could not be backed up: {e}",
+                    path.display()
+                ),
+            }
+            Ok(fallback())
+        }
+    }
+}
+
 // ===== Git Worktree Detection =====
 
 /// Decode Claude session storage path to actual project path
@@ -253,6 +673,16 @@ fn extract_main_git_dir(gitdir: &str) -> Option<String> {
     None
 }
 
+/// Branch checked out in the git directory `git_dir`, read from its `HEAD`
+/// (`ref: refs/heads/{branch}`). `None` for a detached HEAD.
+fn read_head_branch(git_dir: &Path) -> Option<String> {
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    head.trim()
+        .strip_prefix("ref: refs/heads/")
+        .filter(|branch| !branch.is_empty())
+        .map(String::from)
+}
+
 /// Detect git worktree information for a project
 ///
 /// Detection method:
@@ -260,6 +690,8 @@ fn extract_main_git_dir(gitdir: &str) -> Option<String> {
 /// 2. If `.git` is a file → Parse content to get [`Linked`] (linked worktree)
 /// 3. If `.git` doesn't exist → [`NotGit`]
 ///
+/// For [`Main`] and [`Linked`], `branch` comes from the worktree's `HEAD`.
+///
 /// [`Main`]: GitWorktreeType::Main
 /// [`Linked`]: GitWorktreeType::Linked
 /// [`NotGit`]: GitWorktreeType::NotGit
@@ -271,6 +703,7 @@ pub fn detect_git_worktree_info(project_path: &str) -> Option<GitInfo> {
         return Some(GitInfo {
             worktree_type: GitWorktreeType::NotGit,
             main_project_path: None,
+            branch: None,
         });
     }
 
@@ -279,6 +712,7 @@ pub fn detect_git_worktree_info(project_path: &str) -> Option<GitInfo> {
         return Some(GitInfo {
             worktree_type: GitWorktreeType::Main,
             main_project_path: None,
+            branch: read_head_branch(&git_path),
         });
     }
 
@@ -298,6 +732,7 @@ pub fn detect_git_worktree_info(project_path: &str) -> Option<GitInfo> {
                     return Some(GitInfo {
                         worktree_type: GitWorktreeType::Linked,
                         main_project_path,
+                        branch: read_head_branch(&Path::new(&actual_path).join(gitdir)),
                     });
                 }
             }
@@ -308,13 +743,47 @@ pub fn detect_git_worktree_info(project_path: &str) -> Option<GitInfo> {
     Some(GitInfo {
         worktree_type: GitWorktreeType::NotGit,
         main_project_path: None,
+        branch: None,
     })
 }
 
+/// [`detect_git_worktree_info`] for a project's real directory, as other
+/// providers record it. `None` when the path is empty or no longer exists.
+pub fn git_info_for_path(actual_path: &str) -> Option<GitInfo> {
+    if actual_path.is_empty() || !Path::new(actual_path).is_dir() {
+        return None;
+    }
+    detect_git_worktree_info(actual_path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_uri_to_path() {
+        assert_eq!(uri_to_path("file:///home/me/my%20app"), "/home/me/my app");
+        assert_eq!(uri_to_path("file://localhost/srv/repo"), "/srv/repo");
+        assert_eq!(uri_to_path("file:///C:/Users/me/app"), r"C:\Users\me\app");
+        assert_eq!(
+            uri_to_path("file:///c%3A/Users/me/proj"),
+            r"c:\Users\me\proj"
+        );
+        assert_eq!(
+            uri_to_path("file://wsl.localhost/Ubuntu/home/me/proj"),
+            r"\\wsl.localhost\Ubuntu\home\me\proj"
+        );
+        assert_eq!(
+            uri_to_path("vscode-remote://wsl%2Bubuntu/home/me/proj"),
+            r"\\wsl.localhost\ubuntu\home\me\proj"
+        );
+        assert_eq!(
+            uri_to_path("vscode-remote://ssh-remote%2Bbox/srv/app"),
+            "/srv/app"
+        );
+        assert_eq!(uri_to_path("/already/a/path"), "/already/a/path");
+    }
+
     // ===== Line Utils Tests =====
 
     #[test]
@@ -477,6 +946,139 @@ mod tests {
         assert!(search_json_value_case_insensitive(&value, "hello"));
     }
 
+    #[test]
+    fn test_content_text_matches_skips_structural_values() {
+        let value = serde_json::json!([
+            { "type": "tool_use", "id": "call_abc", "name": "Bash", "input": { "command": "pwd" } },
+            { "type": "tool_result", "tool_use_id": "call_abc", "content": "/tmp/project" }
+        ]);
+        assert!(!content_text_matches_case_insensitive(&value, "tool_use"));
+        assert!(!content_text_matches_case_insensitive(&value, "call_abc"));
+        assert!(content_text_matches_case_insensitive(&value, "pwd"));
+        assert!(content_text_matches_case_insensitive(
+            &value,
+            "/tmp/project"
+        ));
+    }
+
+    #[test]
+    fn test_search_pattern_regex_matches_text_only() {
+        let content = serde_json::json!([
+            { "type": "text", "text": "TODO: split parser\n```\n// FIXME later\n```" },
+            { "type": "tool_use", "id": "todo_1", "input": { "command": "ls" } }
+        ]);
+        let pattern = SearchPattern::new("todo|fixme", true).expect("regex should compile");
+        assert!(pattern.matches_content(&content));
+        assert_eq!(
+            pattern.content_match_locations(&content),
+            vec![MatchLocation::Prose, MatchLocation::Code]
+        );
+
+        let ids_only = SearchPattern::new(r"^todo_\d$", true).expect("regex should compile");
+        assert!(!ids_only.matches_content(&content));
+
+        let plain = SearchPattern::new("todo|fixme", false).expect("plain never fails");
+        assert!(!plain.matches_content(&content));
+
+        assert!(SearchPattern::new("(unclosed", true)
+            .unwrap_err()
+            .starts_with("Invalid regex"));
+    }
+
+    #[test]
+    fn test_json_state_round_trips_and_recovers_from_corruption() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("state").join("favorites.json");
+
+        // Missing file: fallback, nothing created
+        let empty: Vec<String> = read_json_or_recover(&path, Vec::new).unwrap();
+        assert!(empty.is_empty());
+        assert!(!path.exists());
+
+        write_json_atomic(&path, &["a", "b"]).unwrap();
+        let loaded: Vec<String> = read_json_or_recover(&path, Vec::new).unwrap();
+        assert_eq!(loaded, vec!["a", "b"]);
+        let leftovers: Vec<_> = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .flatten()
+            .filter(|e| e.file_name().to_string_lossy().ends_with(".tmp"))
+            .collect();
+        assert!(leftovers.is_empty());
+
+        // A torn write is backed up and replaced by the fallback
+        fs::write(&path, r#"["a", "b"#).unwrap();
+        let recovered: Vec<String> =
+            read_json_or_recover(&path, || vec!["default".to_string()]).unwrap();
+        assert_eq!(recovered, vec!["default"]);
+        assert!(!path.exists());
+        let backups: Vec<_> = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.to_string_lossy().contains("favorites.json.corrupt-"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read_to_string(&backups[0]).unwrap(), r#"["a", "b"#);
+
+        // The next save works normally again
+        write_json_atomic(&path, &["c"]).unwrap();
+        let loaded: Vec<String> = read_json_or_recover(&path, Vec::new).unwrap();
+        assert_eq!(loaded, vec!["c"]);
+    }
+
+    #[test]
+    fn test_search_pattern_snippet_windows_first_match() {
+        let long_prefix = "a".repeat(60);
+        let content = serde_json::json!([
+            { "type": "text", "text": format!("{long_prefix} Parser\nthen parser again") },
+            { "type": "tool_use", "id": "parser_1", "input": {} }
+        ]);
+        let (snippet, ranges) = SearchPattern::plain("parser")
+            .snippet(&content)
+            .expect("text content should give a snippet");
+        assert_eq!(
+            snippet,
+            format!("…{} Parser then parser again", "a".repeat(39))
+        );
+        assert_eq!(ranges, vec![(41, 47), (53, 59)]);
+
+        // Ranges count UTF-16 units, so characters before the match shift them
+        let (snippet, ranges) = SearchPattern::new("fix(me)?", true)
+            .expect("regex should compile")
+            .snippet(&serde_json::json!("🚀 FIXME"))
+            .expect("string content should give a snippet");
+        assert_eq!(snippet, "🚀 FIXME");
+        assert_eq!(ranges, vec![(3, 8)]);
+
+        assert!(SearchPattern::plain("x")
+            .snippet(&serde_json::json!({ "type": "image" }))
+            .is_none());
+    }
+
+    #[test]
+    fn test_text_match_locations_distinguishes_code_from_prose() {
+        let text =
+            "Run the parser first.\n```rust\nlet parser = Parser::new();\n```\nThe parser is fast.";
+        assert_eq!(
+            text_match_locations(text, "parser"),
+            vec![
+                MatchLocation::Prose,
+                MatchLocation::Code,
+                MatchLocation::Code,
+                MatchLocation::Prose,
+            ]
+        );
+
+        let content = serde_json::json!([
+            { "type": "text", "text": "```\nparser\n```" },
+            { "type": "tool_use", "id": "parser", "input": {} }
+        ]);
+        assert_eq!(
+            content_match_locations(&content, "parser"),
+            vec![MatchLocation::Code]
+        );
+    }
+
     // ===== Git Worktree Detection Tests =====
 
     #[test]
@@ -539,6 +1141,33 @@ mod tests {
         assert!(info.main_project_path.is_none());
     }
 
+    #[test]
+    fn test_git_info_for_path_reads_branch_and_skips_missing_paths() {
+        use tempfile::TempDir;
+        let temp_dir = TempDir::new().unwrap();
+        let git_dir = temp_dir.path().join(".git");
+        fs::create_dir(&git_dir).unwrap();
+        fs::write(git_dir.join("HEAD"), "ref: refs/heads/feature/login\n").unwrap();
+
+        let info = git_info_for_path(temp_dir.path().to_str().unwrap()).unwrap();
+        assert_eq!(info.worktree_type, GitWorktreeType::Main);
+        assert_eq!(info.branch.as_deref(), Some("feature/login"));
+
+        fs::write(
+            git_dir.join("HEAD"),
+            "0123456789abcdef0123456789abcdef01234567\n",
+        )
+        .unwrap();
+        let detached = git_info_for_path(temp_dir.path().to_str().unwrap()).unwrap();
+        assert_eq!(detached.branch, None);
+
+        assert_eq!(git_info_for_path(""), None);
+        assert_eq!(
+            git_info_for_path(temp_dir.path().join("gone").to_str().unwrap()),
+            None
+        );
+    }
+
     #[test]
     fn test_detect_git_worktree_info_linked() {
         use std::io::Write;
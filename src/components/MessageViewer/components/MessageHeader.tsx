@@ -48,7 +48,11 @@ export const MessageHeader: React.FC<MessageHeaderProps> = ({ message }) => {
                     ? "Codex"
                     : message.provider === "opencode"
                       ? "OpenCode"
-                      : t("messageViewer.claude"))
+                      : message.provider === "gemini"
+                        ? "Gemini"
+                        : message.provider === "zed"
+                          ? "Zed"
+                          : t("messageViewer.claude"))
                   : t("messageViewer.system")}
         </span>
         <span>·</span>
@@ -58,7 +58,8 @@ export const ProjectItem: React.FC<ProjectItemProps> = ({
         !isGrouped && isExpanded && "bg-accent/10 border-l-accent pl-5",
         isGrouped && isMain && "hover:bg-accent/10",
         isGrouped && isWorktree && "hover:bg-emerald-500/10",
-        isGrouped && isExpanded && (isMain ? "bg-accent/15" : "bg-emerald-500/15")
+        isGrouped && isExpanded && (isMain ? "bg-accent/15" : "bg-emerald-500/15"),
+        project.path_exists === false && "opacity-50"
       )}
     >
       {/* Expand Icon */}
@@ -142,6 +143,16 @@ export const ProjectItem: React.FC<ProjectItemProps> = ({
         {displayName}
       </span>
 
+      {/* Branch */}
+      {project.git_info?.branch && !isWorktree && (
+        <span
+          className="max-w-24 truncate text-2xs font-mono text-muted-foreground/60"
+          title={project.git_info.branch}
+        >
+          {project.git_info.branch}
+        </span>
+      )}
+
       {/* Provider Badge */}
       {showProviderBadge && (
         <span
@@ -149,7 +160,9 @@ export const ProjectItem: React.FC<ProjectItemProps> = ({
             "px-1.5 py-0.5 text-2xs font-medium rounded-full flex-shrink-0 leading-none",
             providerId === "claude" && "bg-amber-500/15 text-amber-700 dark:text-amber-300",
             providerId === "codex" && "bg-green-500/15 text-green-600 dark:text-green-400",
-            providerId === "opencode" && "bg-blue-500/15 text-blue-600 dark:text-blue-400"
+            providerId === "opencode" && "bg-blue-500/15 text-blue-600 dark:text-blue-400",
+            providerId === "gemini" && "bg-sky-500/15 text-sky-600 dark:text-sky-400",
+            providerId === "zed" && "bg-violet-500/15 text-violet-600 dark:text-violet-400"
           )}
         >
           {providerLabel}
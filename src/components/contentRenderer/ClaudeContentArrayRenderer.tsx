@@ -228,6 +228,10 @@ export const ClaudeContentArrayRenderer = memo(({
                 />
               );
             }
+            // Encrypted reasoning from other providers: {"type":"thinking","redacted":true}
+            if (item.redacted === true) {
+              return <RedactedThinkingRenderer key={entry.key} data="" />;
+            }
             return null;
 
           case "tool_use":
@@ -11,7 +11,7 @@ import {
 } from "lucide-react";
 import { Dialog, DialogContent, Input } from "@/components/ui";
 import { useAppStore } from "@/store/useAppStore";
-import type { ClaudeMessage, ClaudeSession, ContentItem } from "@/types";
+import type { ClaudeMessage, ClaudeSession, ContentItem, MessageList } from "@/types";
 import { getProviderLabel, hasNonDefaultProvider } from "@/utils/providers";
 
 type GlobalSearchResult = ClaudeMessage;
@@ -95,12 +95,22 @@ export const GlobalSearchModal = ({
             setIsSearching(true);
             try {
                 const hasNonClaudeProviders = hasNonDefaultProvider(activeProviders);
-                const searchResults = await invoke<GlobalSearchResult[]>(
-                    hasNonClaudeProviders ? "search_all_providers" : "search_messages",
-                    hasNonClaudeProviders
-                        ? { claudePath, query: trimmedQuery, activeProviders, filters: {}, limit: MAX_RESULTS }
-                        : { claudePath, query: trimmedQuery, filters: {}, limit: MAX_RESULTS },
-                );
+                const searchResults = hasNonClaudeProviders
+                    ? (
+                          await invoke<MessageList<GlobalSearchResult>>("search_all_providers", {
+                              claudePath,
+                              query: trimmedQuery,
+                              activeProviders,
+                              filters: {},
+                              limit: MAX_RESULTS,
+                          })
+                      ).messages
+                    : await invoke<GlobalSearchResult[]>("search_messages", {
+                          claudePath,
+                          query: trimmedQuery,
+                          filters: {},
+                          limit: MAX_RESULTS,
+                      });
                 setResults(searchResults);
                 setSelectedIndex(0);
             } catch (error) {
@@ -133,12 +143,13 @@ export const GlobalSearchModal = ({
     // Navigate to selected result
     const handleSelectResult = useCallback(
         async (result: GlobalSearchResult) => {
+            const isResultSession = (s: ClaudeSession) =>
+                (result.sessionPath != null && s.file_path === result.sessionPath) ||
+                s.session_id === result.sessionId ||
+                s.actual_session_id === result.sessionId;
+
             // First, check if the session is in the currently loaded sessions
-            let targetSession = sessions.find(
-                (s) =>
-                    s.session_id === result.sessionId ||
-                    s.actual_session_id === result.sessionId,
-            );
+            let targetSession = sessions.find(isResultSession);
 
             if (targetSession) {
                 // Session is in current project, just select it
@@ -147,8 +158,15 @@ export const GlobalSearchModal = ({
                 return;
             }
 
-            // Session not in current project - search through all projects
-            for (const project of projects) {
+            // Session not in current project - try the owning project first,
+            // then search through all projects
+            const owningProject = projects.find(
+                (p) => p.path === result.projectPath,
+            );
+            const candidates = owningProject
+                ? [owningProject, ...projects.filter((p) => p !== owningProject)]
+                : projects;
+            for (const project of candidates) {
                 try {
                     // Load sessions for this project via provider-aware invoke
                     const projectProvider = project.provider ?? "claude";
@@ -161,11 +179,7 @@ export const GlobalSearchModal = ({
                     );
 
                     // Check if any session matches
-                    targetSession = projectSessions.find(
-                        (s) =>
-                            s.session_id === result.sessionId ||
-                            s.actual_session_id === result.sessionId,
-                    );
+                    targetSession = projectSessions.find(isResultSession);
 
                     if (targetSession) {
                         // Found it! Select the project first, then the session
@@ -93,7 +93,10 @@ export type CommonKeys =
   | 'common.provider.claude'
   | 'common.provider.codex'
   | 'common.provider.detectError'
+  | 'common.provider.gemini'
   | 'common.provider.opencode'
+  | 'common.provider.scanWarning'
+  | 'common.provider.zed'
   | 'common.refresh'
   | 'common.remove'
   | 'common.restartApp'
@@ -1954,7 +1957,10 @@ export type TranslationKey =
   | 'common.provider.claude'
   | 'common.provider.codex'
   | 'common.provider.detectError'
+  | 'common.provider.gemini'
   | 'common.provider.opencode'
+  | 'common.provider.scanWarning'
+  | 'common.provider.zed'
   | 'common.refresh'
   | 'common.remove'
   | 'common.restartApp'
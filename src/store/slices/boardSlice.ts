@@ -9,7 +9,7 @@ import type {
     SessionDepth,
 } from "../../types/board.types";
 import type { ActiveBrush } from "@/utils/brushMatchers";
-import type { ClaudeMessage, ClaudeSession } from "../../types";
+import type { ClaudeMessage, ClaudeSession, MessageList } from "../../types";
 import { analyzeSessionMessages } from "../../utils/sessionAnalytics";
 import { isAbsolutePath } from "../../utils/pathUtils";
 
@@ -144,7 +144,7 @@ export const createBoardSlice: StateCreator<
                         return null;
                     }
                     const provider = session.provider ?? "claude";
-                    const messages = await invoke<ClaudeMessage[]>(
+                    const { messages } = await invoke<MessageList>(
                         "load_provider_messages",
                         { provider, sessionPath: session.file_path }
                     );
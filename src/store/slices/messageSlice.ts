@@ -8,6 +8,7 @@ import { invoke } from "@tauri-apps/api/core";
 import type {
   ClaudeMessage,
   ClaudeSession,
+  MessageList,
   PaginationState,
   SessionTokenStats,
   ProjectStatsSummary,
@@ -150,7 +151,7 @@ export const createMessageSlice: StateCreator<
       const start = performance.now();
 
       const provider = session.provider ?? "claude";
-      const allMessages = await invoke<ClaudeMessage[]>("load_provider_messages", {
+      const { messages: allMessages } = await invoke<MessageList>("load_provider_messages", {
         provider,
         sessionPath,
       });
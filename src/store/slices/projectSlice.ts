@@ -6,7 +6,14 @@
 
 import { invoke } from "@tauri-apps/api/core";
 import { load } from "@tauri-apps/plugin-store";
-import type { ClaudeProject, ClaudeSession, AppError } from "../../types";
+import { toast } from "sonner";
+import i18n from "../../i18n";
+import type {
+  ClaudeProject,
+  ClaudeSession,
+  AppError,
+  ProviderScanResult,
+} from "../../types";
 import { AppErrorType } from "../../types";
 import type { StateCreator } from "zustand";
 import type { FullAppStore } from "./types";
@@ -17,7 +24,7 @@ import {
   type DirectoryGroupingResult,
 } from "../../utils/worktreeUtils";
 import type { GroupingMode } from "../../types/metadata.types";
-import { DEFAULT_PROVIDER_ID } from "../../utils/providers";
+import { DEFAULT_PROVIDER_ID, getProviderLabel } from "../../utils/providers";
 import { INITIAL_PAGINATION } from "./messageSlice";
 import { nextRequestId, getRequestId } from "../../utils/requestId";
 
@@ -174,14 +181,18 @@ export const createProjectSlice: StateCreator<
         .map((provider) => provider.id);
       const scanProviders = availableProviders.length > 0 ? availableProviders : [DEFAULT_PROVIDER_ID];
       const hasNonClaudeProviders = scanProviders.some((provider) => provider !== DEFAULT_PROVIDER_ID);
-      const projects = hasNonClaudeProviders
-        ? await invoke<ClaudeProject[]>("scan_all_projects", {
+      const { projects, warnings } = hasNonClaudeProviders
+        ? await invoke<ProviderScanResult>("scan_all_projects_with_warnings", {
             claudePath,
             activeProviders: scanProviders,
+            includeOrphans: true,
           })
-        : await invoke<ClaudeProject[]>("scan_projects", {
-            claudePath,
-          });
+        : {
+            projects: await invoke<ClaudeProject[]>("scan_projects", {
+              claudePath,
+            }),
+            warnings: [],
+          };
       const duration = performance.now() - start;
       if (import.meta.env.DEV) {
         console.log(
@@ -192,6 +203,17 @@ export const createProjectSlice: StateCreator<
         return;
       }
       set({ projects });
+      for (const warning of warnings) {
+        toast.warning(
+          i18n.t("common.provider.scanWarning", {
+            provider: getProviderLabel(
+              (key, fallback) => i18n.t(key, fallback),
+              warning.provider
+            ),
+            error: warning.error,
+          })
+        );
+      }
 
       // Auto-enable worktree grouping if worktrees are detected
       // Only auto-enable if user has never explicitly set the preference
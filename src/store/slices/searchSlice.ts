@@ -5,7 +5,7 @@
  */
 
 import { invoke } from "@tauri-apps/api/core";
-import type { ClaudeMessage, SearchFilters } from "../../types";
+import type { ClaudeMessage, MessageList, SearchFilters } from "../../types";
 import { AppErrorType } from "../../types";
 import type { StateCreator } from "zustand";
 import { searchMessages as searchMessagesFromIndex } from "../../utils/searchIndex";
@@ -88,12 +88,14 @@ export const createSearchSlice: StateCreator<
     try {
       const hasNonClaudeProviders = hasNonDefaultProvider(activeProviders);
       const results = hasNonClaudeProviders
-        ? await invoke<ClaudeMessage[]>("search_all_providers", {
-            claudePath,
-            query,
-            activeProviders,
-            filters,
-          })
+        ? (
+            await invoke<MessageList>("search_all_providers", {
+              claudePath,
+              query,
+              activeProviders,
+              filters,
+            })
+          ).messages
         : await invoke<ClaudeMessage[]>("search_messages", {
             claudePath,
             query,
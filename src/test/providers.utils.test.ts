@@ -32,6 +32,12 @@ describe("providers utils", () => {
   });
 
   it("keeps provider id list stable for all known providers", () => {
-    expect(PROVIDER_IDS).toEqual(["claude", "codex", "opencode"]);
+    expect(PROVIDER_IDS).toEqual([
+      "claude",
+      "codex",
+      "opencode",
+      "gemini",
+      "zed",
+    ]);
   });
 });
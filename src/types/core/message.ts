@@ -158,8 +158,14 @@ export interface BaseClaudeMessage {
   timestamp: string;
   /** Project name (extracted from file path during search) */
   projectName?: string;
-  /** Provider identifier (claude, codex, opencode) */
+  /** Provider identifier (claude, codex, opencode, gemini, zed) */
   provider?: import("./session").ProviderId;
+  /** Where each search match fell (search results only) */
+  matchLocations?: ("code" | "prose")[];
+  /** Session file or virtual path holding the message (search results only) */
+  sessionPath?: string;
+  /** Path of the owning project (search results only) */
+  projectPath?: string;
   isSidechain?: boolean;
   content?: string | ContentItem[] | Record<string, unknown>;
 }
@@ -204,7 +210,12 @@ export interface ClaudeSystemMessage extends BaseClaudeMessage {
   preventedContinuation?: boolean;
 
   // boundary fields
-  compactMetadata?: { trigger?: string; preTokens?: number };
+  compactMetadata?: {
+    trigger?: string;
+    preTokens?: number;
+    tokens?: number;
+    cost?: number;
+  };
   microcompactMetadata?: { trigger?: string; preTokens?: number };
 }
 
@@ -268,12 +279,47 @@ export interface MessageNode {
 // ============================================================================
 
 export interface MessagePage {
+  /** Normalizer version that produced `messages` */
+  schema_version: number;
   messages: ClaudeMessage[];
   total_count: number;
   has_more: boolean;
   next_offset: number;
 }
 
+/** Paging state of a `MessageList` holding one page of a session */
+export interface PageInfo {
+  total_count: number;
+  has_more: boolean;
+  next_offset: number;
+}
+
+/** Envelope returned by `load_provider_messages` and `search_all_providers` */
+export interface MessageList<T = ClaudeMessage> {
+  /** Normalizer version that produced `messages` */
+  schema_version: number;
+  messages: T[];
+  /** Set when `load_provider_messages` was given `offset` or `limit` */
+  page?: PageInfo;
+}
+
+/** Event sent over the channel of `stream_provider_messages` */
+export type MessageStreamEvent =
+  | { event: "messages"; data: { messages: ClaudeMessage[] } }
+  | { event: "done"; data: { schema_version: number; total_count: number } };
+
+// ============================================================================
+// Search
+// ============================================================================
+
+/** A search result with a one-line excerpt around the first match */
+export interface SearchHit {
+  message: ClaudeMessage;
+  snippet: string;
+  /** [start, end) of each match within `snippet`, as string indices */
+  match_ranges: [number, number][];
+}
+
 /**
  * @deprecated Pagination is no longer used as we load all messages at once.
  * Kept for backward compatibility.
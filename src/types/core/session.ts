@@ -8,13 +8,26 @@
 // Provider Types
 // ============================================================================
 
-export type ProviderId = "claude" | "codex" | "opencode";
+export type ProviderId = "claude" | "codex" | "opencode" | "gemini" | "zed";
 
 export interface ProviderInfo {
   id: ProviderId;
   display_name: string;
   base_path: string;
   is_available: boolean;
+  /** Latest modification time in the provider's storage (RFC 3339) */
+  last_activity?: string | null;
+}
+
+/** A provider whose scan failed, returned next to the projects that loaded */
+export interface ProviderWarning {
+  provider: ProviderId;
+  error: string;
+}
+
+export interface ProviderScanResult {
+  projects: ClaudeProject[];
+  warnings: ProviderWarning[];
 }
 
 // ============================================================================
@@ -28,6 +41,8 @@ export interface GitInfo {
   worktree_type: GitWorktreeType;
   /** 메인 레포의 프로젝트 경로 (링크드 워크트리인 경우) */
   main_project_path?: string;
+  /** 체크아웃된 브랜치 (detached HEAD이면 없음) */
+  branch?: string;
 }
 
 export interface GitCommit {
@@ -53,8 +68,10 @@ export interface ClaudeProject {
   last_modified: string;
   /** Git worktree 정보 */
   git_info?: GitInfo;
-  /** Provider identifier (claude, codex, opencode) */
+  /** Provider identifier (claude, codex, opencode, gemini, zed) */
   provider?: ProviderId;
+  /** Whether actual_path still exists as a directory on disk */
+  path_exists?: boolean;
 }
 
 export interface ClaudeSession {
@@ -70,8 +87,10 @@ export interface ClaudeSession {
   has_errors: boolean;
   summary?: string;
   relevance?: number;
-  /** Provider identifier (claude, codex, opencode) */
+  /** Provider identifier (claude, codex, opencode, gemini, zed) */
   provider?: ProviderId;
+  /** Small provider-specific metadata bag (e.g. OpenCode model ids) */
+  data?: Record<string, unknown>;
 }
 
 // ============================================================================
@@ -80,6 +99,10 @@ export interface ClaudeSession {
 
 export interface SearchFilters {
   dateRange?: [Date, Date];
+  /** Single calendar day (YYYY-MM-DD), matched as [00:00, 24:00) local time */
+  onDate?: string;
+  /** IANA timezone for `onDate` (e.g. "Asia/Tokyo"); defaults to the system timezone */
+  timezone?: string;
   projects?: string[];
   messageType?: "user" | "assistant" | "all";
   hasToolCalls?: boolean;
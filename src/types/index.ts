@@ -33,8 +33,12 @@ export type {
   ClaudeProgressMessage,
   ClaudeQueueMessage,
   MessageNode,
+  MessageList,
   MessagePage,
+  MessageStreamEvent,
+  PageInfo,
   PaginationState,
+  SearchHit,
 } from "./core/message";
 
 // Content Types
@@ -101,6 +105,8 @@ export type {
   GitCommit,
   ProviderId,
   ProviderInfo,
+  ProviderWarning,
+  ProviderScanResult,
   ClaudeProject,
   ClaudeSession,
   SearchFilters,
@@ -198,6 +204,8 @@ export type {
   ActivityHeatmap,
   ToolUsageStats,
   ModelStats,
+  ModelTokenTotals,
+  TokenTotals,
   DateRange,
   ProjectStatsSummary,
   ProjectRanking,
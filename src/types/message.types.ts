@@ -205,7 +205,12 @@ export interface ClaudeSystemMessage extends BaseClaudeMessage {
   preventedContinuation?: boolean;
 
   // boundary fields
-  compactMetadata?: { trigger?: string; preTokens?: number };
+  compactMetadata?: {
+    trigger?: string;
+    preTokens?: number;
+    tokens?: number;
+    cost?: number;
+  };
   microcompactMetadata?: { trigger?: string; preTokens?: number };
 }
 
@@ -269,6 +274,8 @@ export interface MessageNode {
 // ============================================================================
 
 export interface MessagePage {
+  /** Normalizer version that produced `messages` */
+  schema_version: number;
   messages: ClaudeMessage[];
   total_count: number;
   has_more: boolean;
@@ -67,6 +67,10 @@ export interface ClaudeSession {
 
 export interface SearchFilters {
   dateRange?: [Date, Date];
+  /** Single calendar day (YYYY-MM-DD), matched as [00:00, 24:00) local time */
+  onDate?: string;
+  /** IANA timezone for `onDate` (e.g. "Asia/Tokyo"); defaults to the system timezone */
+  timezone?: string;
   projects?: string[];
   messageType?: "user" | "assistant" | "all";
   hasToolCalls?: boolean;
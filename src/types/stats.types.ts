@@ -80,6 +80,29 @@ export interface ModelStats {
   cache_read_tokens: number;
 }
 
+/** Token and cost sums for one model within a session */
+export interface ModelTokenTotals {
+  model: string;
+  input_tokens: number;
+  output_tokens: number;
+  cache_creation_input_tokens: number;
+  cache_read_input_tokens: number;
+  total_tokens: number;
+  cost_usd: number | null;
+}
+
+/** Session-wide token and cost sums (`session_token_totals`) */
+export interface TokenTotals {
+  input_tokens: number;
+  output_tokens: number;
+  cache_creation_input_tokens: number;
+  cache_read_input_tokens: number;
+  total_tokens: number;
+  cost_usd: number | null;
+  /** Largest total_tokens first */
+  by_model: ModelTokenTotals[];
+}
+
 // ============================================================================
 // Date Range
 // ============================================================================
@@ -1,6 +1,12 @@
 import type { ProviderId } from "../types";
 
-export const PROVIDER_IDS: ProviderId[] = ["claude", "codex", "opencode"];
+export const PROVIDER_IDS: ProviderId[] = [
+  "claude",
+  "codex",
+  "opencode",
+  "gemini",
+  "zed",
+];
 export const DEFAULT_PROVIDER_ID: ProviderId = "claude";
 
 const PROVIDER_TRANSLATIONS: Record<
@@ -10,6 +16,8 @@ const PROVIDER_TRANSLATIONS: Record<
   claude: { key: "common.provider.claude", fallback: "Claude Code" },
   codex: { key: "common.provider.codex", fallback: "Codex CLI" },
   opencode: { key: "common.provider.opencode", fallback: "OpenCode" },
+  gemini: { key: "common.provider.gemini", fallback: "Gemini CLI" },
+  zed: { key: "common.provider.zed", fallback: "Zed" },
 };
 
 type TranslateFn = (key: string, defaultValue: string) => string;
@@ -18,6 +26,8 @@ export function getProviderId(provider?: ProviderId | string): ProviderId {
   switch (provider) {
     case "codex":
     case "opencode":
+    case "gemini":
+    case "zed":
     case "claude":
       return provider;
     default: